@@ -0,0 +1,44 @@
+use std::{env, fs, path::Path};
+
+// Test inputs that take too long for debug builds. Their tests only run in release builds, with
+// "cargo test --release".
+const SLOW_INPUTS: [&str; 1] = ["day19_test"];
+
+// Generates a test for each test input (inputs/*_test.txt), for tests/inputs.rs.
+fn main() {
+    println!("cargo:rerun-if-changed=inputs");
+
+    // Without inputs/ (like in a copy of the code without the puzzle inputs), there are no tests.
+    let entries = match fs::read_dir("inputs") {
+        Ok(entries) => entries.collect(),
+        Err(error) => {
+            println!("cargo:warning=could not read inputs/ ({error}), so there are no input tests");
+            Vec::new()
+        }
+    };
+
+    let mut names = entries
+        .into_iter()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|file_name| Some(file_name.strip_suffix("_test.txt")?.to_string() + "_test"))
+        .collect::<Vec<String>>();
+
+    names.sort();
+
+    let tests = names
+        .iter()
+        .map(|name| {
+            let ignore = if SLOW_INPUTS.contains(&name.as_str()) {
+                "#[cfg_attr(debug_assertions, ignore = \"slow in debug builds\")]\n"
+            } else {
+                ""
+            };
+
+            format!("#[test]\n{ignore}fn {name}() {{\n    check(\"{name}\");\n}}\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("input_tests.rs"), tests).unwrap();
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc22::{day18::Day18, solution::Solution};
+use libfuzzer_sys::fuzz_target;
+
+// Malformed inputs should be rejected with an error, never panic.
+fuzz_target!(|input: &str| {
+    let _ = Day18::parse(input);
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use aoc22::day25::SNAFU;
+use libfuzzer_sys::fuzz_target;
+
+// Numbers should survive a round trip through SNAFU digits, and valid digits a round trip through
+// numbers (up to leading zeros).
+fuzz_target!(|input: (i128, &str)| {
+    let (number, digits) = input;
+
+    if let Ok(snafu) = SNAFU::try_from(number) {
+        let parsed = snafu.to_string().parse::<SNAFU>().unwrap();
+        assert_eq!(parsed.to_int(), number, "{}", snafu);
+    }
+
+    if let Ok(snafu) = digits.parse::<SNAFU>() {
+        let number = snafu.to_int();
+        let again = SNAFU::try_from(number).unwrap();
+        assert_eq!(again.to_int(), number, "{}", digits);
+    }
+});
@@ -0,0 +1,29 @@
+#!/bin/sh
+# Seeds the corpus of each day's fuzz target with the day's inputs from inputs/, and with a few
+# small generated inputs, so that fuzzing starts from inputs that parse. Run from anywhere.
+
+set -e
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+corpus="$root/fuzz/corpus"
+
+cargo build --release --quiet --manifest-path "$root/Cargo.toml"
+aoc22="$root/target/release/aoc22"
+
+for day in $(seq 1 25); do
+    mkdir -p "$corpus/day$day"
+
+    for input in "$root"/inputs/day"$day".txt "$root"/inputs/day"$day"_*.txt; do
+        if [ -f "$input" ]; then
+            cp "$input" "$corpus/day$day/"
+        fi
+    done
+
+    for seed in 1 2 3; do
+        "$aoc22" generate "day$day" --seed "$seed" --size 3 \
+            > "$corpus/day$day/generated_$seed.txt" 2> /dev/null
+    done
+done
+
+mkdir -p "$corpus/snafu"
+cp "$root/inputs/day25.txt" "$corpus/snafu/"
@@ -0,0 +1,185 @@
+use std::fs;
+
+use crate::{
+    error::Error,
+    solution::{Answer, Day, Report},
+};
+
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// The expected answers for one input. A part is `None` when there is no known answer to check.
+#[derive(Debug, PartialEq)]
+pub struct Expected {
+    pub input: String,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+    // The params to run the input with, as names and values, like `--param <name>=<value>`.
+    pub params: Vec<(String, String)>,
+}
+
+impl Expected {
+    // The params to run the input with, checked against the day's.
+    pub fn overrides(&self, day: &Day) -> Result<Vec<(&'static str, i64)>, Error> {
+        self.params
+            .iter()
+            .map(|(name, value)| day.param(name, value))
+            .collect()
+    }
+
+    // Describes each part of the report that doesn't match the expected answer.
+    pub fn mismatches(&self, report: &Report) -> Vec<String> {
+        [
+            ("part 1", &self.part1, &report.part1),
+            ("part 2", &self.part2, &report.part2),
+        ]
+        .into_iter()
+        .filter_map(|(part, expected, actual)| match expected {
+            Some(expected) if expected != actual => {
+                Some(format!("{part}: expected {expected}, got {actual}"))
+            }
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+pub fn load() -> Result<Vec<Expected>, Error> {
+    let contents = fs::read_to_string(ANSWERS_FILE)
+        .map_err(|error| Error::new(format!("could not read {}: {}", ANSWERS_FILE, error)))?;
+
+    parse(&contents).map_err(|error| error.in_day(ANSWERS_FILE))
+}
+
+// Each line is "<input> <part 1> <part 2>", where "-" stands for a part with no known answer,
+// followed by the params to run the input with, if any, like "row=10". Empty lines and lines
+// starting with "#" are ignored.
+fn parse(contents: &str) -> Result<Vec<Expected>, Error> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<&str>>();
+
+            match columns[..] {
+                [input, part1, part2, ref params @ ..] => Ok(Expected {
+                    input: input.to_string(),
+                    part1: parse_answer(part1),
+                    part2: parse_answer(part2),
+                    params: params
+                        .iter()
+                        .map(|param| {
+                            let (name, value) = param.split_once('=').ok_or_else(|| {
+                                Error::at(contents, param, "expected `<name>=<value>`")
+                            })?;
+                            Ok((name.to_string(), value.to_string()))
+                        })
+                        .collect::<Result<_, Error>>()?,
+                }),
+                _ => Err(Error::at(
+                    contents,
+                    line,
+                    "expected `<input> <part 1> <part 2>`",
+                )),
+            }
+        })
+        .collect()
+}
+
+fn parse_answer(column: &str) -> Option<Answer> {
+    match column {
+        "-" => None,
+        answer => Some(answer.parse().unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let contents =
+            "# A comment\n\nday10 15680 ZFBFHGUP\nday17_test  3068  -\nday15_test 26 56000011 row=10\n";
+
+        assert_eq!(
+            parse(contents),
+            Ok(vec![
+                Expected {
+                    input: "day10".to_string(),
+                    part1: Some(Answer::Number(15680)),
+                    part2: Some(Answer::Text("ZFBFHGUP".to_string())),
+                    params: Vec::new(),
+                },
+                Expected {
+                    input: "day17_test".to_string(),
+                    part1: Some(Answer::Number(3068)),
+                    part2: None,
+                    params: Vec::new(),
+                },
+                Expected {
+                    input: "day15_test".to_string(),
+                    part1: Some(Answer::Number(26)),
+                    part2: Some(Answer::Number(56000011)),
+                    params: vec![("row".to_string(), "10".to_string())],
+                },
+            ])
+        );
+
+        assert_eq!(
+            parse("day1 1\n").unwrap_err().to_string(),
+            "line 1 col 1: expected `<input> <part 1> <part 2>`"
+        );
+        assert_eq!(
+            parse("day15_test 26 56000011 row\n")
+                .unwrap_err()
+                .to_string(),
+            "line 1 col 24: expected `<name>=<value>`"
+        );
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let expected = Expected {
+            input: "day16_test".to_string(),
+            part1: Some(Answer::Number(1651)),
+            part2: Some(Answer::Number(1707)),
+            params: Vec::new(),
+        };
+
+        let mut report = Report {
+            part1: Answer::Number(1651),
+            part2: Answer::Number(1327),
+            parse_time: Duration::ZERO,
+            part1_time: Duration::ZERO,
+            part2_time: Duration::ZERO,
+            diagnostics: Vec::new(),
+        };
+
+        assert_eq!(
+            expected.mismatches(&report),
+            vec!["part 2: expected 1707, got 1327"]
+        );
+
+        report.part2 = Answer::Number(1707);
+        assert!(expected.mismatches(&report).is_empty());
+
+        let expected = Expected {
+            part2: None,
+            ..expected
+        };
+        report.part2 = Answer::Unsolved;
+        assert!(expected.mismatches(&report).is_empty());
+    }
+
+    #[test]
+    fn test_parse_answer() {
+        assert_eq!(parse_answer("-"), None);
+        assert_eq!(parse_answer("-12"), Some(Answer::Number(-12)));
+        assert_eq!(
+            parse_answer("2011-=2=-1020-1===-1"),
+            Some(Answer::Text("2011-=2=-1020-1===-1".to_string()))
+        );
+    }
+}
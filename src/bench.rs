@@ -0,0 +1,276 @@
+use std::{collections::HashMap, fs, time::Duration};
+
+use aoc22::{
+    error::{parse_number, Error},
+    solution::{Day, Report},
+};
+
+pub const BASELINE_FILE: &str = "bench_baseline.txt";
+
+const STEPS: [&str; 3] = ["parse", "part 1", "part 2"];
+
+/// Summary statistics of the timings of one step (parsing or one of the parts) over many runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    pub fn new(mut timings: Vec<Duration>) -> Self {
+        assert!(!timings.is_empty(), "Can't compute stats without timings");
+
+        timings.sort();
+
+        // Nearest-rank percentile.
+        let percentile = |percent: usize| timings[(timings.len() * percent).div_ceil(100) - 1];
+
+        Stats {
+            min: timings[0],
+            median: percentile(50),
+            p95: percentile(95),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bench {
+    pub name: String,
+    // One entry per step, in the same order as STEPS.
+    pub steps: [Stats; 3],
+}
+
+// Runs the day `warmup` times without looking at the timings, then `runs` more times. The name is
+// the input's (like "day11_test") rather than the day's, so that both inputs can have a baseline.
+pub fn bench(
+    name: &str,
+    day: &Day,
+    input: &str,
+    warmup: usize,
+    runs: usize,
+) -> Result<Bench, Error> {
+    for _ in 0..warmup {
+        (day.run)(input)?;
+    }
+
+    let reports = (0..runs)
+        .map(|_| (day.run)(input))
+        .collect::<Result<Vec<Report>, Error>>()?;
+
+    Ok(Bench {
+        name: name.to_string(),
+        steps: [
+            Stats::new(reports.iter().map(|report| report.parse_time).collect()),
+            Stats::new(reports.iter().map(|report| report.part1_time).collect()),
+            Stats::new(reports.iter().map(|report| report.part2_time).collect()),
+        ],
+    })
+}
+
+/// The median timings of each step of each day, as saved by an earlier bench run.
+pub type Baseline = HashMap<String, [Duration; 3]>;
+
+// Missing baseline files are fine, since there's nothing to compare against before the first save.
+pub fn load_baseline() -> Result<Baseline, Error> {
+    match fs::read_to_string(BASELINE_FILE) {
+        Ok(contents) => parse_baseline(&contents).map_err(|error| error.in_day(BASELINE_FILE)),
+        Err(_) => Ok(Baseline::new()),
+    }
+}
+
+// Updates the baseline with the results of this run, keeping the entries of days that weren't
+// benchmarked this time.
+pub fn save_baseline(mut baseline: Baseline, benches: &[Bench]) -> Result<(), Error> {
+    for bench in benches {
+        baseline.insert(bench.name.clone(), bench.steps.map(|stats| stats.median));
+    }
+
+    fs::write(BASELINE_FILE, format_baseline(&baseline))
+        .map_err(|error| Error::new(format!("could not write {}: {}", BASELINE_FILE, error)))
+}
+
+// Each line is "<day> <parse> <part 1> <part 2>", with the median timings in nanoseconds.
+fn parse_baseline(contents: &str) -> Result<Baseline, Error> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_baseline_line(line).map_err(|error| error.within(contents, line)))
+        .collect()
+}
+
+fn parse_baseline_line(line: &str) -> Result<(String, [Duration; 3]), Error> {
+    match line.split_whitespace().collect::<Vec<&str>>()[..] {
+        [name, parse, part1, part2] => {
+            let nanos = |column| parse_number(line, column).map(Duration::from_nanos);
+            Ok((
+                name.to_string(),
+                [nanos(parse)?, nanos(part1)?, nanos(part2)?],
+            ))
+        }
+        _ => Err(Error::at(
+            line,
+            line,
+            "expected `<day> <parse> <part 1> <part 2>`",
+        )),
+    }
+}
+
+fn format_baseline(baseline: &Baseline) -> String {
+    let mut names = baseline.keys().collect::<Vec<&String>>();
+    names.sort_by_key(|name| (day_number(name), name.to_string()));
+
+    names
+        .into_iter()
+        .map(|name| {
+            let [parse, part1, part2] = baseline[name].map(|timing| timing.as_nanos());
+            format!("{name} {parse} {part1} {part2}\n")
+        })
+        .collect()
+}
+
+// Sorts "day9" before "day10", and "day10" before "day10_test".
+fn day_number(name: &str) -> Option<u32> {
+    let number = name.strip_prefix("day")?;
+    let digits = number
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(number.len());
+
+    number[..digits].parse().ok()
+}
+
+pub fn table(benches: &[Bench], baseline: &Baseline) -> String {
+    let mut rows = vec![[
+        "Day".to_string(),
+        "Step".to_string(),
+        "Min".to_string(),
+        "Median".to_string(),
+        "P95".to_string(),
+        "Baseline".to_string(),
+        "Change".to_string(),
+    ]];
+
+    for bench in benches {
+        for (step_index, (step, stats)) in STEPS.iter().zip(bench.steps).enumerate() {
+            let baseline_median = baseline.get(&bench.name).map(|timings| timings[step_index]);
+
+            rows.push([
+                if step_index == 0 {
+                    bench.name.clone()
+                } else {
+                    String::new()
+                },
+                step.to_string(),
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.p95),
+                baseline_median
+                    .map(|median| format!("{:.2?}", median))
+                    .unwrap_or_default(),
+                baseline_median
+                    .map(|median| change(median, stats.median))
+                    .unwrap_or_default(),
+            ]);
+        }
+    }
+
+    let mut widths = [0; 7];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+
+    for row in rows {
+        let mut line = String::new();
+
+        for (index, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if index > 0 {
+                line.push_str("  ");
+            }
+
+            // Day and step names are left-aligned, timings are right-aligned.
+            if index < 2 {
+                line.push_str(&format!("{:<width$}", cell));
+            } else {
+                line.push_str(&format!("{:>width$}", cell));
+            }
+        }
+
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+fn change(baseline: Duration, current: Duration) -> String {
+    if current.is_zero() || baseline.is_zero() {
+        return "-".to_string();
+    }
+
+    let ratio = baseline.as_secs_f64() / current.as_secs_f64();
+
+    if ratio >= 1.0 {
+        format!("{:.2}x faster", ratio)
+    } else {
+        format!("{:.2}x slower", 1.0 / ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let timings = (1..=20).rev().map(Duration::from_millis).collect();
+
+        assert_eq!(
+            Stats::new(timings),
+            Stats {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(10),
+                p95: Duration::from_millis(19),
+            }
+        );
+
+        let single = Stats::new(vec![Duration::from_millis(3)]);
+        assert_eq!(single.min, single.p95);
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let mut baseline = Baseline::new();
+        baseline.insert("day10".to_string(), [1, 2, 3].map(Duration::from_micros));
+        baseline.insert("day9".to_string(), [4, 5, 6].map(Duration::from_micros));
+        baseline.insert(
+            "day10_test".to_string(),
+            [7, 8, 9].map(Duration::from_micros),
+        );
+
+        let contents = format_baseline(&baseline);
+        assert_eq!(
+            contents,
+            [
+                "day9 4000 5000 6000",
+                "day10 1000 2000 3000",
+                "day10_test 7000 8000 9000",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(parse_baseline(&contents), Ok(baseline));
+    }
+
+    #[test]
+    fn test_change() {
+        let millis = Duration::from_millis;
+
+        assert_eq!(change(millis(30), millis(10)), "3.00x faster");
+        assert_eq!(change(millis(10), millis(25)), "2.50x slower");
+        assert_eq!(change(millis(10), Duration::ZERO), "-");
+    }
+}
@@ -0,0 +1,104 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A cycle in the states of a simulation, found by running it until a state repeats, with the
+/// value of a metric (like the height of a tower) after each step until then, so that the metric
+/// can be extrapolated to any number of steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    /// The number of steps before the states start repeating.
+    pub start: usize,
+    /// The number of steps until the state after `start` steps comes back.
+    pub length: usize,
+    /// How much the metric changes over each cycle.
+    pub delta: i64,
+    metrics: Vec<i64>,
+}
+
+impl Cycle {
+    // Runs a simulation until its state repeats. `initial` is the snapshot of its state and the
+    // metric before any step, and `step` runs the given step (from 1) and returns the snapshot
+    // and the metric after it. The snapshots must capture everything that affects the next steps.
+    // None if the state didn't repeat after `max_steps`.
+    pub fn find<S: Hash + Eq>(
+        initial: (S, i64),
+        max_steps: usize,
+        mut step: impl FnMut(usize) -> (S, i64),
+    ) -> Option<Cycle> {
+        let (state, metric) = initial;
+        let mut seen = HashMap::from([(state, 0)]);
+        let mut metrics = vec![metric];
+
+        for steps in 1..=max_steps {
+            let (state, metric) = step(steps);
+            metrics.push(metric);
+
+            if let Some(start) = seen.insert(state, steps) {
+                return Some(Cycle {
+                    start,
+                    length: steps - start,
+                    delta: metric - metrics[start],
+                    metrics,
+                });
+            }
+        }
+
+        None
+    }
+
+    // The metric after the given number of steps, simulated or extrapolated. None if it doesn't
+    // fit in an i64.
+    pub fn metric(&self, steps: usize) -> Option<i64> {
+        if steps < self.metrics.len() {
+            return Some(self.metrics[steps]);
+        }
+
+        let cycles = (steps - self.start) / self.length;
+        let offset = (steps - self.start) % self.length;
+
+        i64::try_from(cycles)
+            .ok()?
+            .checked_mul(self.delta)?
+            .checked_add(self.metrics[self.start + offset])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The states go 0, 1, 2, 3, 4, 2, 3, 4, ... and the metric is their running sum.
+    fn state(steps: usize) -> i64 {
+        match steps {
+            0..=2 => steps as i64,
+            _ => 2 + ((steps - 2) % 3) as i64,
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let mut sum = 0;
+        let cycle = Cycle::find((0, 0), 100, |steps| {
+            sum += state(steps);
+            (state(steps), sum)
+        })
+        .unwrap();
+
+        assert_eq!(cycle.start, 2);
+        assert_eq!(cycle.length, 3);
+        assert_eq!(cycle.delta, 9);
+
+        let mut sum = 0;
+        for steps in 0..50 {
+            sum += state(steps);
+            assert_eq!(cycle.metric(steps), Some(sum));
+        }
+
+        assert_eq!(cycle.metric(2 + 3 * 1_000_000), Some(3 + 9 * 1_000_000));
+        assert_eq!(cycle.metric(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_no_cycle() {
+        assert_eq!(Cycle::find((0, 0), 100, |steps| (steps, 0)), None);
+    }
+}
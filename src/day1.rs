@@ -1,19 +1,71 @@
-pub fn run(input: &str) {
-    let mut sorted_asc = input
-        .split("\n\n")
-        .map(|line_chunks| line_chunks.split("\n"))
-        .map(|chunk| {
-            chunk
-                .map(|calorie| calorie.parse::<i32>().unwrap())
-                .sum::<i32>()
-        })
-        .collect::<Vec<i32>>();
-
-    sorted_asc.sort();
-    sorted_asc.reverse();
-    sorted_asc.truncate(3);
-
-    let top_3_sum: i32 = sorted_asc.iter().sum();
-
-    println!("{:?}", top_3_sum);
+use crate::{
+    error::{parse_number, Error},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    // Calories carried by each elf, sorted from the elf carrying the most to the one carrying
+    // the least.
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let mut sorted_desc = input
+            .trim()
+            .split("\n\n")
+            .map(|chunk| {
+                chunk.split("\n").try_fold(0, |total: i32, calorie| {
+                    total
+                        .checked_add(parse_number::<i32>(input, calorie)?)
+                        .ok_or_else(|| Error::at(input, chunk, "too many calories for one elf"))
+                })
+            })
+            .collect::<Result<Vec<i32>, Error>>()?;
+
+        sorted_desc.sort();
+        sorted_desc.reverse();
+        Ok(sorted_desc)
+    }
+
+    fn part1(calories: &Self::Input) -> Answer {
+        calories[0].into()
+    }
+
+    // Summed in an i64, since three elves can carry more than an i32 together.
+    fn part2(calories: &Self::Input) -> Answer {
+        let top_3_sum: i64 = calories
+            .iter()
+            .take(3)
+            .map(|&calories| i64::from(calories))
+            .sum();
+        top_3_sum.into()
+    }
+
+    // `size` elves, carrying a few snacks each.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let elves = (0..size.max(1))
+            .map(|_| {
+                (0..rng.range(1..8))
+                    .map(|_| rng.range(1000..20000).to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect::<Vec<String>>();
+
+        Some(elves.join("\n\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part2_overflowing_an_i32() {
+        let calories = Day1::parse("2000000000\n\n2000000000\n\n1").unwrap();
+
+        assert_eq!(Day1::part2(&calories), Answer::Number(4000000001));
+    }
 }
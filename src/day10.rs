@@ -1,37 +1,240 @@
-pub fn run(input: &str) {
+use crate::{
+    error::{parse_lines, parse_number, Error},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
+const SCREEN_WIDTH: usize = 40;
+const SCREEN_HEIGHT: usize = 6;
+
+// Letters on the CRT are 4 pixels wide (plus one column of spacing) and 6 pixels tall.
+const LETTER_WIDTH: usize = 5;
+const LETTERS: [(char, &str); 16] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Instruction {
+    Noop,
+    Addx(i32),
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let instructions = parse_lines(input, |line| {
+            if line == "noop" {
+                Ok(Instruction::Noop)
+            } else if line.starts_with("addx") {
+                let (_, x) = line.split_at("addx".len());
+                Ok(Instruction::Addx(parse_number(line, x)?))
+            } else {
+                Err(Error::at(
+                    line,
+                    line,
+                    format!("unknown instruction `{}`", line),
+                ))
+            }
+        })?;
+
+        let cycles = instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Noop => 1,
+                Instruction::Addx(_) => 2,
+            })
+            .sum::<usize>();
+
+        // The screen is drawn until its last pixel.
+        if cycles < SCREEN_WIDTH * SCREEN_HEIGHT {
+            return Err(Error::new(format!(
+                "expected instructions for {} cycles, got {}",
+                SCREEN_WIDTH * SCREEN_HEIGHT,
+                cycles
+            )));
+        }
+
+        Ok(instructions)
+    }
+
+    // The sum of the signal strengths during the 20th, 60th, ..., 220th cycles.
+    fn part1(instructions: &Self::Input) -> Answer {
+        let registers = register_values(instructions);
+
+        [20, 60, 100, 140, 180, 220]
+            .iter()
+            .map(|cycle| cycle * registers[*cycle as usize - 1])
+            .sum::<i32>()
+            .into()
+    }
+
+    fn part2(instructions: &Self::Input) -> Answer {
+        let screen = draw_screen(&register_values(instructions));
+
+        match read_letters(&screen) {
+            Some(letters) => letters.into(),
+            None => format!("\n{}", screen).into(),
+        }
+    }
+
+    // Enough instructions for the 240 cycles of the screen, adding values up to `size` to the
+    // register while keeping the sprite around the screen.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut instructions = String::new();
+        let (mut cycles, mut register) = (0, 1);
+        let size = size.max(1) as i64;
+
+        while cycles < SCREEN_WIDTH * SCREEN_HEIGHT {
+            if rng.chance(0.3) {
+                instructions += "noop\n";
+                cycles += 1;
+                continue;
+            }
+
+            let mut value = rng.range(-size..size + 1);
+
+            if !(-2..SCREEN_WIDTH as i64 + 2).contains(&(register + value)) {
+                value = -value;
+            }
+
+            register += value;
+            instructions += &format!("addx {}\n", value);
+            cycles += 2;
+        }
+
+        Some(instructions)
+    }
+}
+
+// Returns the value of the register *during* each of the 240 cycles.
+fn register_values(instructions: &[Instruction]) -> Vec<i32> {
     let mut register: i32 = 1;
-    let mut lines = input.lines();
+    let mut instructions = instructions.iter();
 
     let mut addx_cycles = 0;
     let mut next_addx_value = 0;
 
-    for cycle in 0..240 {
-        let row_cycle = cycle % 40;
+    let mut values = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT);
 
-        if row_cycle == register || row_cycle == register - 1 || row_cycle == register + 1 {
-            print!("#");
-        } else {
-            print!(".");
-        }
+    for _cycle in 0..SCREEN_WIDTH * SCREEN_HEIGHT {
+        values.push(register);
 
         if addx_cycles == 1 {
             register += next_addx_value;
             addx_cycles = 0;
             next_addx_value = 0;
         } else {
-            let next_line = lines.next().expect("No more lines");
-
-            if next_line == "noop" {
-                ();
-            } else if next_line.starts_with("addx") {
-                let (_, x) = next_line.split_at("addx".len());
-                next_addx_value = x.trim().parse::<i32>().unwrap();
-                addx_cycles = 1;
+            match instructions.next().expect("No more instructions") {
+                Instruction::Noop => (),
+                Instruction::Addx(x) => {
+                    next_addx_value = *x;
+                    addx_cycles = 1;
+                }
             }
         }
+    }
+
+    values
+}
 
-        if cycle % 40 == 39 {
-            println!("");
+fn draw_screen(registers: &[i32]) -> String {
+    let mut screen = String::new();
+
+    for (cycle, register) in registers.iter().enumerate() {
+        let row_cycle = (cycle % SCREEN_WIDTH) as i32;
+
+        if row_cycle == *register || row_cycle == register - 1 || row_cycle == register + 1 {
+            screen.push('#');
+        } else {
+            screen.push('.');
         }
+
+        if cycle % SCREEN_WIDTH == SCREEN_WIDTH - 1 && cycle != registers.len() - 1 {
+            screen.push('\n');
+        }
+    }
+
+    screen
+}
+
+// Reads the capital letters drawn on the screen, if all of them are known.
+fn read_letters(screen: &str) -> Option<String> {
+    let rows = screen.lines().collect::<Vec<&str>>();
+
+    (0..SCREEN_WIDTH / LETTER_WIDTH)
+        .map(|letter_index| {
+            let start = letter_index * LETTER_WIDTH;
+
+            let pixels = rows
+                .iter()
+                .map(|row| row.get(start..start + LETTER_WIDTH - 1))
+                .collect::<Option<String>>()?;
+
+            LETTERS
+                .iter()
+                .find(|(_, letter_pixels)| *letter_pixels == pixels)
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_values() {
+        let mut instructions = vec![
+            Instruction::Noop,
+            Instruction::Addx(3),
+            Instruction::Addx(-5),
+        ];
+        instructions.extend((0..240).map(|_| Instruction::Noop));
+
+        let values = register_values(&instructions);
+        assert_eq!(&values[0..6], &[1, 1, 1, 4, 4, -1]);
+    }
+
+    #[test]
+    fn test_parse_with_too_few_instructions() {
+        assert_eq!(
+            Day10::parse("noop\naddx 3").unwrap_err().to_string(),
+            "expected instructions for 240 cycles, got 3"
+        );
+        assert!(Day10::parse("").is_err());
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let screen = [
+            "####.####.###..####.#..#..##..#..#.###..",
+            "...#.#....#..#.#....#..#.#..#.#..#.#..#.",
+            "..#..###..###..###..####.#....#..#.#..#.",
+            ".#...#....#..#.#....#..#.#.##.#..#.###..",
+            "#....#....#..#.#....#..#.#..#.#..#.#....",
+            "####.#....###..#....#..#..###..##..#....",
+        ]
+        .join("\n");
+
+        assert_eq!(read_letters(&screen), Some("ZFBFHGUP".to_string()));
+        assert_eq!(read_letters(&screen.replace('#', ".")), None);
     }
 }
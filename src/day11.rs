@@ -1,6 +1,36 @@
-use std::str::Lines;
-
-const ROUNDS: u16 = 10000;
+use crate::{
+    error::{parse_number, Error},
+    parse::{blocks, Pattern},
+    random::Rng,
+    solution::{diagnostic, Answer, Param, Solution},
+};
+
+const RELIEF_ROUNDS: Param = Param {
+    name: "relief_rounds",
+    default: 20,
+    min: 0,
+    max: u16::MAX as i64,
+    description: "Rounds while worry levels are divided by 3 (part 1)",
+};
+
+const ROUNDS: Param = Param {
+    name: "rounds",
+    default: 10000,
+    min: 0,
+    max: u16::MAX as i64,
+    description: "Rounds without relief (part 2)",
+};
+
+static MONKEY: Pattern = Pattern::new(
+    "
+    Monkey {_}:
+      Starting items: {items}
+      Operation: new = {operation}
+      Test: divisible by {divisible_by}
+        If true: throw to monkey {if_true}
+        If false: throw to monkey {if_false}
+    ",
+);
 
 #[derive(Debug, PartialEq, Clone)]
 enum Operation {
@@ -9,8 +39,17 @@ enum Operation {
     Square,
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone, Copy)]
+enum WorryManagement {
+    // Worry levels are divided by three after each inspection.
+    Relief,
+    // Worry levels are kept within the product of all the monkeys' divisors, which doesn't change
+    // the result of any of their tests.
+    Modulo(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Monkey {
     items: Vec<u64>,
     operation: Operation,
     divisible_by: u64,
@@ -20,91 +59,152 @@ struct Monkey {
 }
 
 impl Monkey {
-    pub fn new(input: &str) -> Monkey {
-        let mut lines = input.trim().lines();
-
-        let (_, starting_items) = lines.next().unwrap().split_once(":").unwrap();
-        let items = starting_items
-            .split(",")
-            .map(|x| x.trim().parse::<u64>().unwrap())
-            .collect();
-
-        let (_, operation) = lines.next().unwrap().split_once(":").unwrap();
-        let operation = Self::parse_operation(operation.trim());
-
-        let (divisible_by, monkey_index_if_true, monkey_index_if_false) = Self::parse_action(lines);
-
-        Monkey {
-            items,
-            operation,
-            divisible_by,
-            monkey_index_if_true,
-            monkey_index_if_false,
+    // Errors are relative to the given input.
+    pub fn new(input: &str) -> Result<Monkey, Error> {
+        let monkey = MONKEY.parse(input)?;
+
+        Ok(Monkey {
+            items: monkey.numbers("items", ",")?,
+            operation: Self::parse_operation(input, monkey.get("operation"))?,
+            divisible_by: monkey.number("divisible_by")?,
+            monkey_index_if_true: monkey.number("if_true")?,
+            monkey_index_if_false: monkey.number("if_false")?,
             inspected_items: 0,
+        })
+    }
+
+    fn parse_operation(input: &str, operation: &str) -> Result<Operation, Error> {
+        let parts = operation.split(" ").collect::<Vec<&str>>();
+
+        match parts[..] {
+            ["old", "*", "old"] => Ok(Operation::Square),
+            ["old", "+", value] => Ok(Operation::Sum(parse_number(input, value)?)),
+            ["old", "*", value] => Ok(Operation::Product(parse_number(input, value)?)),
+            _ => Err(Error::at(
+                input,
+                operation,
+                "expected `old + <number>`, `old * <number>` or `old * old`",
+            )),
         }
     }
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
 
-    fn parse_operation(op: &str) -> Operation {
-        let (_, operation) = op.split_once("=").unwrap();
-        let parts = operation.trim().split(" ").collect::<Vec<&str>>();
+    const PARAMS: &'static [Param] = &[RELIEF_ROUNDS, ROUNDS];
 
-        match (parts[0], parts[1], parts[2]) {
-            ("old", "*", "old") => Operation::Square,
-            ("old", "+", value) => Operation::Sum(value.parse::<u64>().unwrap()),
-            ("old", "*", value) => Operation::Product(value.parse::<u64>().unwrap()),
-            _ => panic!("Unknown operation"),
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let monkeys = blocks(input, |block| Ok((block, Monkey::new(block)?)))?;
+
+        // The monkey business is between the two most active monkeys.
+        if monkeys.len() < 2 {
+            return Err(Error::new("expected at least two monkeys"));
         }
-    }
 
-    fn parse_action(mut lines: Lines) -> (u64, usize, usize) {
-        let (_, divisible_by) = lines.next().unwrap().split_once("divisible by").unwrap();
-        let (_, monkey_index_if_true) = lines.next().unwrap().split_once("monkey").unwrap();
-        let (_, monkey_index_if_false) = lines.next().unwrap().split_once("monkey").unwrap();
+        for (index, (block, monkey)) in monkeys.iter().enumerate() {
+            // The fields are parsed again to report the error where they are.
+            let error = |field, message: String| {
+                let fields = MONKEY.parse(block)?;
+                Err(Error::at(block, fields.get(field), message).within(input, block))
+            };
+
+            if monkey.divisible_by == 0 {
+                return error("divisible_by", "can't test divisibility by 0".to_string());
+            }
+
+            for (field, target) in [
+                ("if_true", monkey.monkey_index_if_true),
+                ("if_false", monkey.monkey_index_if_false),
+            ] {
+                if target >= monkeys.len() {
+                    return error(field, format!("no monkey {}", target));
+                }
+
+                if target == index {
+                    return error(field, format!("monkey {} throws to itself", index));
+                }
+            }
+        }
 
-        (
-            divisible_by.trim().parse::<u64>().unwrap(),
-            monkey_index_if_true.trim().parse::<usize>().unwrap(),
-            monkey_index_if_false.trim().parse::<usize>().unwrap(),
-        )
+        Ok(monkeys.into_iter().map(|(_, monkey)| monkey).collect())
     }
-}
 
-#[cfg(test)]
-mod monkey_tests {
-    use super::*;
+    fn part1(monkeys: &Self::Input) -> Answer {
+        monkey_business(monkeys, RELIEF_ROUNDS.value(), WorryManagement::Relief)
+    }
 
-    #[test]
-    fn test_new_monkey() {
-        let input = r#"
-            Starting items: 79, 98
-            Operation: new = old * 19
-            Test: divisible by 23
-                If true: throw to monkey 2
-                If false: throw to monkey 3
-        "#;
+    fn part2(monkeys: &Self::Input) -> Answer {
+        let Some(number_space) = monkeys
+            .iter()
+            .try_fold(1_u64, |product, x| product.checked_mul(x.divisible_by))
+        else {
+            diagnostic!("The product of the divisors doesn't fit in 64 bits");
+            return Answer::Unsolved;
+        };
 
-        let monkey = Monkey::new(input);
+        monkey_business(
+            monkeys,
+            ROUNDS.value(),
+            WorryManagement::Modulo(number_space),
+        )
+    }
 
-        assert_eq!(monkey.items, vec![79, 98]);
-        assert_eq!(monkey.operation, Operation::Product(19));
-        assert_eq!(monkey.divisible_by, 23);
-        assert_eq!(monkey.monkey_index_if_true, 2);
-        assert_eq!(monkey.monkey_index_if_false, 3);
-        assert_eq!(monkey.inspected_items, 0);
+    // `size` monkeys (from 2 to 9), with a different prime divisor each. Worry levels have to stay
+    // small while they're divided by 3: monkeys only multiply them by 2 or 3, and only the first
+    // monkey can square them, in which case no monkey throws to it.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let count = size.clamp(2, 9);
+        let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        rng.shuffle(&mut divisors);
+
+        let squares = count >= 4 && rng.chance(0.5);
+        let mut monkeys = Vec::new();
+
+        for (index, divisor) in divisors.iter().take(count).enumerate() {
+            let items = (0..rng.range(1..5))
+                .map(|_| rng.range(50..100).to_string())
+                .collect::<Vec<String>>();
+
+            let operation = match rng.below(2) {
+                _ if index == 0 && squares => "old * old".to_string(),
+                0 => format!("old + {}", rng.range(1..9)),
+                _ => format!("old * {}", rng.range(2..4)),
+            };
+
+            let mut targets = (0..count)
+                .filter(|target| *target != index && !(*target == 0 && squares))
+                .collect::<Vec<usize>>();
+            rng.shuffle(&mut targets);
+            let if_false = targets.get(1).unwrap_or(&targets[0]);
+
+            monkeys.push(format!(
+                "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                index,
+                items.join(", "),
+                operation,
+                divisor,
+                targets[0],
+                if_false
+            ));
+        }
+
+        Some(monkeys.join("\n"))
     }
 }
 
-pub fn run(input: &str) {
-    let mut monkeys = input
-        .split("\n\n")
-        .map(parse_monkey)
-        .collect::<Vec<Monkey>>();
-
-    let number_space = monkeys.iter().map(|x| x.divisible_by).product::<u64>();
+// Unsolved if a worry level or the monkey business doesn't fit in 64 bits.
+fn monkey_business(monkeys: &[Monkey], rounds: u16, worry_management: WorryManagement) -> Answer {
+    let mut monkeys = monkeys.to_vec();
 
-    for _round in 1..=ROUNDS {
+    for _round in 1..=rounds {
         for monkey_index in 0..monkeys.len() {
-            monkey_run(&mut monkeys, monkey_index, number_space);
+            if monkey_run(&mut monkeys, monkey_index, worry_management).is_none() {
+                diagnostic!("A worry level doesn't fit in 64 bits");
+                return Answer::Unsolved;
+            }
         }
     }
 
@@ -115,34 +215,38 @@ pub fn run(input: &str) {
 
     inspected_items.sort();
     inspected_items.reverse();
-    println!(
-        "Monkey business: {}",
-        inspected_items[0] * inspected_items[1]
-    );
-}
-
-fn parse_monkey(input: &str) -> Monkey {
-    let (_, rest) = input.split_once("\n").unwrap();
-    Monkey::new(rest)
+    inspected_items[0]
+        .checked_mul(inspected_items[1])
+        .map_or(Answer::Unsolved, Answer::from)
 }
 
-fn monkey_run(monkeys: &mut Vec<Monkey>, index: usize, number_space: u64) {
+// The new worry levels are worked out in 128 bits, where squaring a 64-bit level can't overflow.
+// None if a level doesn't fit in 64 bits again after the relief.
+fn monkey_run(
+    monkeys: &mut [Monkey],
+    index: usize,
+    worry_management: WorryManagement,
+) -> Option<()> {
     let items = monkeys[index].items.clone();
     let operation = monkeys[index].operation.clone();
-    let divisible_by = monkeys[index].divisible_by.clone();
-    let monkey_index_if_true = monkeys[index].monkey_index_if_true.clone();
-    let monkey_index_if_false = monkeys[index].monkey_index_if_false.clone();
+    let divisible_by = monkeys[index].divisible_by;
+    let monkey_index_if_true = monkeys[index].monkey_index_if_true;
+    let monkey_index_if_false = monkeys[index].monkey_index_if_false;
 
-    for item in &items {
+    for &item in &items {
+        let item = u128::from(item);
         let new_item = match operation {
-            Operation::Sum(value) => item + value,
-            Operation::Product(value) => item * value,
+            Operation::Sum(value) => item + u128::from(value),
+            Operation::Product(value) => item * u128::from(value),
             Operation::Square => item * item,
         };
 
-        let new_item = new_item % number_space;
+        let new_item = match worry_management {
+            WorryManagement::Relief => u64::try_from(new_item / 3).ok()?,
+            WorryManagement::Modulo(number_space) => (new_item % u128::from(number_space)) as u64,
+        };
 
-        let throw_index = if new_item % divisible_by == 0 {
+        let throw_index = if new_item.is_multiple_of(divisible_by) {
             monkey_index_if_true
         } else {
             monkey_index_if_false
@@ -153,6 +257,64 @@ fn monkey_run(monkeys: &mut Vec<Monkey>, index: usize, number_space: u64) {
 
     monkeys[index].items = Vec::new();
     monkeys[index].inspected_items += items.len() as u64;
+    Some(())
+}
+
+#[cfg(test)]
+mod monkey_tests {
+    use super::*;
+
+    #[test]
+    fn test_new_monkey() {
+        let input = r#"
+            Monkey 0:
+            Starting items: 79, 98
+            Operation: new = old * 19
+            Test: divisible by 23
+                If true: throw to monkey 2
+                If false: throw to monkey 3
+        "#;
+
+        let monkey = Monkey::new(input).unwrap();
+
+        assert_eq!(monkey.items, vec![79, 98]);
+        assert_eq!(monkey.operation, Operation::Product(19));
+        assert_eq!(monkey.divisible_by, 23);
+        assert_eq!(monkey.monkey_index_if_true, 2);
+        assert_eq!(monkey.monkey_index_if_false, 3);
+        assert_eq!(monkey.inspected_items, 0);
+    }
+
+    #[test]
+    fn test_new_monkey_error() {
+        let input = "Monkey 0:\n  Starting items: 79, 98\n  Operation: new = old * 19\n  Test: divided by 23";
+
+        assert_eq!(
+            Day11::parse(input).unwrap_err().to_string(),
+            "line 4 col 3: expected `Test: divisible by`"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let monkey = |index, if_true, if_false| {
+            format!(
+                "Monkey {}:\n  Starting items: 79\n  Operation: new = old * 19\n  Test: divisible by 23\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                index, if_true, if_false
+            )
+        };
+
+        assert_eq!(
+            Day11::parse(&monkey(0, 1, 1)).unwrap_err().to_string(),
+            "expected at least two monkeys"
+        );
+        assert_eq!(
+            Day11::parse(&[monkey(0, 1, 1), monkey(1, 0, 1)].join("\n"))
+                .unwrap_err()
+                .to_string(),
+            "line 13 col 31: monkey 1 throws to itself"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -188,11 +350,31 @@ mod tests {
             },
         ];
 
-        monkey_run(&mut monkeys, 1, 1000000);
+        assert_eq!(
+            monkey_run(&mut monkeys, 1, WorryManagement::Modulo(1000000)),
+            Some(())
+        );
 
         assert_eq!(monkeys[0].items, vec![5, 2, 9]);
         assert_eq!(monkeys[1].items, vec![]);
         assert_eq!(monkeys[1].inspected_items, 2);
         assert_eq!(monkeys[2].items, vec![18]);
+
+        monkeys[2].items = vec![u64::MAX];
+        assert_eq!(monkey_run(&mut monkeys, 2, WorryManagement::Relief), None);
+        monkeys[2].items = vec![u64::MAX];
+        assert_eq!(
+            monkey_run(&mut monkeys, 2, WorryManagement::Modulo(u64::MAX - 1)),
+            Some(())
+        );
+        assert_eq!(monkeys[1].items, vec![1]);
+    }
+
+    #[test]
+    fn test_overflowing_worry_levels() {
+        let input = include_str!("../inputs/day11_test.txt");
+        let monkeys = Day11::parse(&input.replace("divisible by ", "divisible by 10000")).unwrap();
+
+        assert_eq!(Day11::part2(&monkeys), Answer::Unsolved);
     }
 }
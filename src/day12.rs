@@ -1,210 +1,277 @@
-use infinitable::*;
-
-use std::collections::{HashMap, HashSet};
-
-#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
-struct Node(i32, i32);
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    grid::{Grid, Position},
+    random::Rng,
+    search,
+    solution::{diagnostic, Answer, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+// Heights from green lowlands ('a') to snowy peaks ('z'), and the path walked ('*' up to '@').
+const PALETTE: Palette = &palette();
+
+const fn palette() -> [(char, Rgb); 28] {
+    let mut palette = [('*', Rgb(230, 60, 40)); 28];
+    palette[27] = ('@', Rgb(255, 220, 0));
+
+    let mut height = 0;
+
+    while height < 26 {
+        let step = height as u8;
+        palette[height] = (
+            (b'a' + step) as char,
+            Rgb(30 + step * 8, 70 + step * 7, 30 + step * 8),
+        );
+        height += 1;
+    }
 
-type DistanceMap = HashMap<Node, Infinitable<i32>>;
+    palette
+}
 
 #[derive(Debug, Clone)]
-struct Graph {
-    nodes: HashMap<Node, char>,
+pub struct Graph {
+    nodes: Grid<char>,
 }
 
 impl Graph {
-    pub fn from(input: &str) -> Graph {
-        let mut nodes = HashMap::new();
+    pub fn from(input: &str) -> Result<Graph, Error> {
+        let nodes = Grid::parse(input, "a height (a-z, S or E)", |c| {
+            (c.is_ascii_lowercase() || c == 'S' || c == 'E').then_some(c)
+        })?;
 
-        for (row_index, row) in input.trim().lines().enumerate() {
-            for (column_index, char) in row.trim().chars().enumerate() {
-                nodes.insert(Node(row_index as i32, column_index as i32), char);
-            }
-        }
+        Ok(Graph { nodes })
+    }
 
-        Graph { nodes }
+    fn find_node(&self, target: char) -> Option<Position> {
+        self.nodes
+            .iter()
+            .find(|(_, char)| **char == target)
+            .map(|(node, _)| node)
     }
 
-    fn distance_between_nodes(&self, node1: Node, node2: Node) -> u16 {
-        let node1_val = self.nodes[&node1] as i32;
-        let node2_val = self.nodes[&node2] as i32;
+    // The heightmap, with the path walked up to its step'th node.
+    fn frame(&self, path: &[Position], step: usize) -> Frame {
+        let mut frame = Frame::new(format!("Step {} of {}", step, path.len() - 1), PALETTE)
+            .with_focus(path[step]);
 
-        if node2_val == node1_val {
-            2
-        } else {
-            1
-        }
-    }
+        frame.draw(&self.nodes, |_, height| Some(*height));
 
-    fn find_node(&self, target: char) -> Option<Node> {
-        for (node, char) in &self.nodes {
-            if *char == target {
-                return Some(node.clone());
-            }
+        for node in &path[..step] {
+            frame.set(*node, '*');
         }
 
-        return None;
+        frame.set(path[step], '@');
+        frame
     }
 
-    fn neighbors(&self, node: Node) -> Vec<Node> {
-        let mut nodes = vec![];
-        let char_at_node = self.nodes[&node];
+    fn neighbors(&self, node: Position) -> Vec<Position> {
+        let char_at_node = self.nodes[node];
 
-        let north_node = Node(node.0 - 1, node.1);
-        let south_node = Node(node.0 + 1, node.1);
-        let east_node = Node(node.0, node.1 + 1);
-        let west_node = Node(node.0, node.1 - 1);
-
-        if let Some(char) = self.nodes.get(&north_node) {
-            if chars_are_connectable(&char_at_node, &char) {
-                nodes.push(north_node);
-            }
-        }
-        if let Some(char) = self.nodes.get(&south_node) {
-            if chars_are_connectable(&char_at_node, &char) {
-                nodes.push(south_node);
-            }
-        }
-        if let Some(char) = self.nodes.get(&east_node) {
-            if chars_are_connectable(&char_at_node, &char) {
-                nodes.push(east_node);
-            }
-        }
-        if let Some(char) = self.nodes.get(&west_node) {
-            if chars_are_connectable(&char_at_node, &char) {
-                nodes.push(west_node);
-            }
-        }
-
-        nodes
+        self.nodes
+            .neighbors4(node)
+            .filter(|(_, char)| chars_are_connectable(&char_at_node, char))
+            .map(|(neighbor, _)| neighbor)
+            .collect()
     }
 }
 
-pub fn run(input: &str) {
-    let mut graph = Graph::from(input);
-    let start_node = graph.find_node('S').unwrap();
-    let end_node = graph.find_node('E').unwrap();
+pub struct Day12;
 
-    graph.nodes.insert(start_node, 'a');
-    graph.nodes.insert(end_node, 'z');
+impl Solution for Day12 {
+    // The heightmap, with the start and end nodes.
+    type Input = (Graph, Position, Position);
 
-    let mut dijkstras = vec![];
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let mut graph = Graph::from(input)?;
+        let start_node = graph
+            .find_node('S')
+            .ok_or_else(|| Error::new("no start position (S)"))?;
+        let end_node = graph
+            .find_node('E')
+            .ok_or_else(|| Error::new("no best signal position (E)"))?;
 
-    for (node, char) in &graph.nodes {
-        if *char == 'a' {
-            println!("Running Dijkstra's from node {:?}", node);
+        graph.nodes[start_node] = 'a';
+        graph.nodes[end_node] = 'z';
 
-            let mut graph = graph.clone();
+        Ok((graph, start_node, end_node))
+    }
 
-            match dijkstra(&mut graph, *node, end_node) {
-                Some(hops) => dijkstras.push(hops),
-                None => continue,
-            };
+    // A* towards the end, which is at least as many steps away as its Manhattan distance.
+    fn part1((graph, start_node, end_node): &Self::Input) -> Answer {
+        let Some((steps, path)) = search::astar(
+            *start_node,
+            |node| {
+                graph
+                    .neighbors(*node)
+                    .into_iter()
+                    .map(|neighbor| (neighbor, 1))
+            },
+            |node| node.manhattan_distance(*end_node) as u64,
+            |node| node == end_node,
+        ) else {
+            diagnostic!("No path from the start to the best signal");
+            return Answer::Unsolved;
+        };
+
+        diagnostic!("Path: {:?}", path);
+
+        if visualize::is_playing() {
+            for step in 0..path.len() {
+                visualize::emit(|| graph.frame(&path, step));
+            }
         }
-    }
 
-    let hops = dijkstras.iter().min().unwrap();
+        steps.into()
+    }
 
-    println!("Path with the least hops has {} hops", hops);
-}
+    // The fewest steps from any square at elevation 'a', searching from all of them at once.
+    fn part2((graph, _start_node, end_node): &Self::Input) -> Answer {
+        let starts = graph
+            .nodes
+            .iter()
+            .filter(|(_, char)| **char == 'a')
+            .map(|(node, _)| node)
+            .collect::<Vec<Position>>();
 
-fn dijkstra(graph: &mut Graph, start_node: Node, end_node: Node) -> Option<u32> {
-    let mut unvisited_set: HashSet<Node> = HashSet::new();
-    let mut distances: DistanceMap = HashMap::new();
-    let mut prev: HashMap<Node, Option<Node>> = HashMap::new();
+        diagnostic!("Searching from {} squares at elevation 'a'", starts.len());
 
-    for node in graph.nodes.keys() {
-        unvisited_set.insert(node.clone());
-        distances.insert(node.clone(), Infinity);
-        prev.insert(node.clone(), None);
+        search::bfs(starts, |node| graph.neighbors(*node))
+            .distance(end_node)
+            .map_or(Answer::Unsolved, Answer::from)
     }
 
-    distances.insert(start_node, Finite(0));
-
-    while unvisited_set.len() > 0 {
-        // Pick the position with minimum distance that is in the unvisited set.
-        let u = find_node_with_min_distance(&distances, &unvisited_set);
+    // A heightmap of `size` rows, climbing from the left to the right. A path climbs one step at
+    // each column (or stays at the same height) from the start to the best signal.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let rows = size.max(1);
+        let columns = (3 * rows).max(26);
+        // The height of the path in each column, from 'a' to 'z'.
+        let height = |column: usize| (column * 25 / (columns - 1)) as u8;
+
+        let mut map = (0..rows)
+            .map(|_| {
+                (0..columns)
+                    .map(|column| height(column).saturating_sub(rng.below(4) as u8))
+                    .collect::<Vec<u8>>()
+            })
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut row = rng.below(rows);
+        let start = row;
+
+        for column in 0..columns {
+            let next_row = (row as i64 + rng.range(-2..3)).clamp(0, rows as i64 - 1) as usize;
+
+            for heights in &mut map[row.min(next_row)..=row.max(next_row)] {
+                heights[column] = height(column);
+            }
 
-        if u == end_node {
-            break;
+            row = next_row;
         }
 
-        unvisited_set.remove(&u);
+        let mut input = String::new();
 
-        for v in graph
-            .neighbors(u)
-            .iter()
-            .filter(|node| unvisited_set.contains(*node))
-        {
-            let alt = match distances[&u] {
-                Infinity => continue,
-                NegativeInfinity => continue,
-                Finite(alt) => alt,
-            } + graph.distance_between_nodes(u, v.clone()) as i32;
-
-            if Finite(alt) < distances[v] {
-                distances.insert(v.clone(), Finite(alt));
-                prev.insert(v.clone(), Some(u));
+        for (index, heights) in map.iter().enumerate() {
+            for (column, height) in heights.iter().enumerate() {
+                input.push(match (index, column) {
+                    (index, 0) if index == start => 'S',
+                    (index, column) if index == row && column == columns - 1 => 'E',
+                    _ => (b'a' + height) as char,
+                });
             }
+
+            input.push('\n');
         }
+
+        Some(input)
     }
 
-    let mut s = vec![];
-    let mut u = Some(end_node);
+    // Shortens the distance to every square through its neighbours, over and over, until no
+    // distance gets any shorter: from the start for part 1, from every 'a' for part 2.
+    fn reference((graph, start_node, end_node): &Self::Input) -> Option<(Answer, Answer)> {
+        let steps = |starts: Vec<Position>| {
+            let mut distances = starts
+                .into_iter()
+                .map(|start| (start, 0))
+                .collect::<HashMap<Position, u64>>();
+            let mut changed = true;
+
+            while changed {
+                changed = false;
+
+                for (node, height) in graph.nodes.iter() {
+                    let Some(&distance) = distances.get(&node) else {
+                        continue;
+                    };
+
+                    for (neighbor, neighbor_height) in graph.nodes.neighbors4(node) {
+                        let climbable = *neighbor_height as u32 <= *height as u32 + 1;
+
+                        if climbable && distances.get(&neighbor).is_none_or(|&d| d > distance + 1) {
+                            distances.insert(neighbor, distance + 1);
+                            changed = true;
+                        }
+                    }
+                }
+            }
 
-    if let Some(_) = prev[&u.unwrap()] {
-        while let Some(u1) = u {
-            s.insert(0, u1);
-            u = prev[&u1];
-        }
-    } else {
-        return None;
-    }
+            distances
+                .get(end_node)
+                .map_or(Answer::Unsolved, |&distance| Answer::from(distance))
+        };
+
+        let lowest = graph
+            .nodes
+            .iter()
+            .filter(|(_, height)| **height == 'a')
+            .map(|(node, _)| node)
+            .collect();
 
-    Some((s.len() - 1) as u32)
+        Some((steps(vec![*start_node]), steps(lowest)))
+    }
 }
 
 fn chars_are_connectable(char1: &char, char2: &char) -> bool {
     (*char2 as i32) <= (*char1 as i32) + 1
 }
 
-fn find_node_with_min_distance(distances: &DistanceMap, set: &HashSet<Node>) -> Node {
-    set.iter()
-        .min_by_key(|node| distances[*node])
-        .unwrap()
-        .clone()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::geometry::Point2;
 
     #[test]
     fn test_parse_grid() {
         let input = "abc\ndef\nghi\n";
+        let nodes = Graph::from(input).unwrap().nodes;
 
-        assert_eq!(
-            Graph::from(input).nodes,
-            HashMap::from([
-                (Node(0, 0), 'a'),
-                (Node(0, 1), 'b'),
-                (Node(0, 2), 'c'),
-                (Node(1, 0), 'd'),
-                (Node(1, 1), 'e'),
-                (Node(1, 2), 'f'),
-                (Node(2, 0), 'g'),
-                (Node(2, 1), 'h'),
-                (Node(2, 2), 'i'),
-            ])
-        );
+        assert_eq!(nodes.size(), (3, 3));
+        assert_eq!(nodes[Point2::new(0, 0)], 'a');
+        assert_eq!(nodes[Point2::new(2, 1)], 'f');
+        assert_eq!(nodes[Point2::new(1, 2)], 'h');
+        assert_eq!(nodes.to_string(), input.trim());
     }
 
     #[test]
     fn test_find_node() {
-        let graph = Graph::from("abc\ndef\nEhi\n");
+        let graph = Graph::from("abc\ndef\nEhi\n").unwrap();
+
+        assert_eq!(graph.find_node('E').unwrap(), Point2::new(0, 2));
+        assert_eq!(graph.find_node('a').unwrap(), Point2::new(0, 0));
+    }
+
+    #[test]
+    fn test_unreachable_end() {
+        let input = Day12::parse("SbcdE\n").unwrap();
 
-        assert_eq!(graph.find_node('E').unwrap(), Node(2, 0));
-        assert_eq!(graph.find_node('a').unwrap(), Node(0, 0));
+        assert_eq!(Day12::part1(&input), Answer::Unsolved);
+        assert_eq!(Day12::part2(&input), Answer::Unsolved);
+        assert_eq!(
+            Day12::reference(&input),
+            Some((Answer::Unsolved, Answer::Unsolved))
+        );
     }
 
     #[test]
@@ -216,50 +283,33 @@ mod tests {
         assert!(chars_are_connectable(&'c', &'a'));
     }
 
-    #[test]
-    fn test_find_position_with_min_distance() {
-        let distances = HashMap::from([
-            (Node(0, 0), Finite(1)),
-            (Node(0, 1), Finite(0)),
-            (Node(1, 0), Finite(1)),
-            (Node(1, 1), Finite(2)),
-        ]);
-
-        let set = HashSet::from([Node(0, 0), Node(0, 1), Node(1, 0)]);
-        assert_eq!(find_node_with_min_distance(&distances, &set), Node(0, 1));
-    }
-
-    #[test]
-    fn test_distance_between_nodes() {
-        let graph = Graph::from("abc\ndef\ndhi\n");
-
-        assert_eq!(graph.distance_between_nodes(Node(0, 0), Node(0, 1)), 1);
-        assert_eq!(graph.distance_between_nodes(Node(1, 1), Node(1, 0)), 1);
-        assert_eq!(graph.distance_between_nodes(Node(1, 0), Node(2, 0)), 2);
-    }
-
     #[test]
     fn test_connected_nodes() {
-        let graph = Graph::from(
-            r#"
-        abc
-        def
-        dfi
-        "#,
-        );
+        let graph = Graph::from("abc\ndef\ndfi\n").unwrap();
 
-        assert_eq!(graph.neighbors(Node(0, 0)), vec![Node(0, 1)]);
+        assert_eq!(graph.neighbors(Point2::new(0, 0)), vec![Point2::new(1, 0)]);
 
-        let mut cns = graph.neighbors(Node(0, 1));
+        let mut cns = graph.neighbors(Point2::new(1, 0));
         cns.sort();
-        assert_eq!(cns, vec![Node(0, 0), Node(0, 2)]);
+        assert_eq!(cns, vec![Point2::new(0, 0), Point2::new(2, 0)]);
 
-        let mut cns = graph.neighbors(Node(1, 0));
+        let mut cns = graph.neighbors(Point2::new(0, 1));
         cns.sort();
-        assert_eq!(cns, vec![Node(0, 0), Node(1, 1), Node(2, 0)]);
+        assert_eq!(
+            cns,
+            vec![Point2::new(0, 0), Point2::new(0, 2), Point2::new(1, 1)]
+        );
 
-        let mut cns = graph.neighbors(Node(1, 1));
+        let mut cns = graph.neighbors(Point2::new(1, 1));
         cns.sort();
-        assert_eq!(cns, vec![Node(0, 1), Node(1, 0), Node(1, 2), Node(2, 1)]);
+        assert_eq!(
+            cns,
+            vec![
+                Point2::new(0, 1),
+                Point2::new(1, 0),
+                Point2::new(1, 2),
+                Point2::new(2, 1)
+            ]
+        );
     }
 }
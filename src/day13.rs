@@ -1,54 +1,95 @@
 use std::cmp::Ordering;
 
+use crate::{
+    error::{parse_number, Error},
+    random::Rng,
+    solution::{trace, Answer, Solution},
+};
+
+// Parsing, comparing and dropping packets recurse once per item and nested list, so longer
+// packets are rejected rather than overflowing the stack. The real ones are under 250 characters.
+const MAX_PACKET_LENGTH: usize = 1000;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
-enum Value {
+pub enum Value {
     Int(u16),
     List(Box<LinkedList>),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-enum LinkedList {
+#[derive(Debug, Clone)]
+pub enum LinkedList {
     Empty,
     Cons(Value, Box<LinkedList>),
 }
 
 impl LinkedList {
-    pub fn from_string(string: &str) -> LinkedList {
-        let mut chars = string.chars().skip(1);
-        Self::from_chars(&mut chars)
+    pub fn from_string(string: &str) -> Result<LinkedList, Error> {
+        if !string.starts_with('[') {
+            return Err(Error::at(string, string, "expected `[`"));
+        }
+
+        if string.len() > MAX_PACKET_LENGTH {
+            return Err(Error::at(
+                string,
+                string,
+                format!(
+                    "packets can't be longer than {} characters",
+                    MAX_PACKET_LENGTH
+                ),
+            ));
+        }
+
+        let mut chars = string.char_indices().skip(1);
+        Self::from_chars(string, &mut chars)
     }
 
-    fn from_chars(chars: &mut impl Iterator<Item = char>) -> LinkedList {
+    // Errors are relative to the whole string, which the characters come from.
+    fn from_chars(
+        string: &str,
+        chars: &mut impl Iterator<Item = (usize, char)>,
+    ) -> Result<LinkedList, Error> {
         let mut char_digits = String::new();
+        let mut digits_start = 0;
 
         loop {
-            match chars.next().unwrap() {
+            let (index, char) = chars
+                .next()
+                .ok_or_else(|| Error::at(string, &string[string.len()..], "expected `]`"))?;
+
+            match char {
                 '[' => {
-                    return LinkedList::Cons(
-                        Value::List(Box::new(Self::from_chars(chars))),
-                        Box::new(Self::from_chars(chars)),
-                    );
+                    return Ok(LinkedList::Cons(
+                        Value::List(Box::new(Self::from_chars(string, chars)?)),
+                        Box::new(Self::from_chars(string, chars)?),
+                    ));
                 }
                 ']' => {
                     if char_digits.is_empty() {
-                        return LinkedList::Empty;
+                        return Ok(LinkedList::Empty);
                     } else {
-                        let int = char_digits.parse::<u16>().unwrap();
-                        return LinkedList::Cons(Value::Int(int), Box::new(LinkedList::Empty));
+                        let int = parse_number::<u16>(string, &string[digits_start..index])?;
+                        return Ok(LinkedList::Cons(
+                            Value::Int(int),
+                            Box::new(LinkedList::Empty),
+                        ));
                     }
                 }
                 ',' => {
                     if !char_digits.is_empty() {
-                        let int = char_digits.parse::<u16>().unwrap();
-                        return LinkedList::Cons(
+                        let int = parse_number::<u16>(string, &string[digits_start..index])?;
+                        return Ok(LinkedList::Cons(
                             Value::Int(int),
-                            Box::new(Self::from_chars(chars)),
-                        );
+                            Box::new(Self::from_chars(string, chars)?),
+                        ));
                     } else {
                         continue;
                     }
                 }
                 char => {
+                    if char_digits.is_empty() {
+                        digits_start = index;
+                    }
+
                     char_digits.push(char);
                 }
             }
@@ -115,31 +156,210 @@ impl PartialOrd for LinkedList {
     }
 }
 
+// Packets are equal when they're in the same place in the order, like `[1]` and `[[1]]`, so that
+// equality agrees with `Ord`.
+impl PartialEq for LinkedList {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for LinkedList {}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<LinkedList>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let lines = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<&str>>();
+
+        let packets = lines
+            .iter()
+            .map(|line| LinkedList::from_string(line).map_err(|error| error.within(input, line)))
+            .collect::<Result<Vec<LinkedList>, Error>>()?;
+
+        // The packets come in pairs.
+        if packets.len() % 2 == 1 {
+            return Err(Error::at(
+                input,
+                lines[lines.len() - 1],
+                "expected another packet in the pair",
+            ));
+        }
+
+        Ok(packets)
+    }
+
+    fn part1(packets: &Self::Input) -> Answer {
+        let mut sum_of_ordered_indexes = 0;
+
+        for (pair_index, pair) in packets.chunks(2).enumerate() {
+            let pair_index = pair_index + 1;
+
+            let (left, right) = (&pair[0], &pair[1]);
+
+            if left < right {
+                sum_of_ordered_indexes += pair_index;
+                trace!("Pair {pair_index} is ordered");
+            } else if left > right {
+                trace!("Pair {pair_index} is not ordered");
+            } else {
+                trace!("Pair {pair_index} is equal");
+            }
+        }
+
+        sum_of_ordered_indexes.into()
+    }
+
+    fn part2(packets: &Self::Input) -> Answer {
+        let divider_packet1 = LinkedList::from_string("[[2]]").unwrap();
+        let divider_packet2 = LinkedList::from_string("[[6]]").unwrap();
+
+        // The divider packets go first, so that the sort (which is stable) keeps them before any
+        // packet that's equal to them, including copies of them.
+        let mut packets = [
+            &[divider_packet1.clone(), divider_packet2.clone()],
+            &packets[..],
+        ]
+        .concat();
+        packets.sort();
+
+        let position1 = packets
+            .iter()
+            .position(|packet| packet == &divider_packet1)
+            .unwrap()
+            + 1;
+        let position2 = packets
+            .iter()
+            .position(|packet| packet == &divider_packet2)
+            .unwrap()
+            + 1;
+
+        (position1 * position2).into()
+    }
+
+    // `size` pairs of packets.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let pairs = (0..size.max(1))
+            .map(|_| format!("{}\n{}\n", generate_packet(rng, 0), generate_packet(rng, 0)))
+            .collect::<Vec<String>>();
+
+        Some(pairs.join("\n"))
+    }
+
+    // Compares the packets as plain nested lists, straight from the rules, and finds where the
+    // dividers go by counting the packets before them instead of sorting. Packets equal to a
+    // divider go after it.
+    fn reference(packets: &Self::Input) -> Option<(Answer, Answer)> {
+        let packets = packets.iter().map(Packet::from).collect::<Vec<Packet>>();
+
+        let ordered_pairs = packets
+            .chunks(2)
+            .enumerate()
+            .filter(|(_, pair)| pair.len() == 2 && compare_packets(&pair[0], &pair[1]).is_lt())
+            .map(|(index, _)| index + 1)
+            .sum::<usize>();
+
+        let divider = |int| Packet::List(vec![Packet::List(vec![Packet::Int(int)])]);
+        let (divider1, divider2) = (divider(2), divider(6));
+        let position = |divider: &Packet, other_divider: &Packet| {
+            let before = packets
+                .iter()
+                .filter(|packet| compare_packets(packet, divider).is_lt())
+                .count();
+
+            before + usize::from(compare_packets(other_divider, divider).is_lt()) + 1
+        };
+
+        let decoder_key = position(&divider1, &divider2) * position(&divider2, &divider1);
+
+        Some((ordered_pairs.into(), decoder_key.into()))
+    }
+}
+
+// A packet as nested vectors, for the reference solver.
+enum Packet {
+    Int(u16),
+    List(Vec<Packet>),
+}
+
+impl From<&LinkedList> for Packet {
+    fn from(mut list: &LinkedList) -> Self {
+        let mut values = Vec::new();
+
+        while let LinkedList::Cons(value, tail) = list {
+            values.push(match value {
+                Value::Int(int) => Packet::Int(*int),
+                Value::List(list) => Packet::from(list.as_ref()),
+            });
+            list = tail;
+        }
+
+        Packet::List(values)
+    }
+}
+
+fn compare_packets(left: &Packet, right: &Packet) -> Ordering {
+    match (left, right) {
+        (Packet::Int(left), Packet::Int(right)) => left.cmp(right),
+        (Packet::Int(int), list) => compare_packets(&Packet::List(vec![Packet::Int(*int)]), list),
+        (list, Packet::Int(int)) => compare_packets(list, &Packet::List(vec![Packet::Int(*int)])),
+        (Packet::List(left), Packet::List(right)) => {
+            for (left, right) in left.iter().zip(right) {
+                match compare_packets(left, right) {
+                    Ordering::Equal => continue,
+                    ordering => return ordering,
+                }
+            }
+
+            left.len().cmp(&right.len())
+        }
+    }
+}
+
+// A list of up to 5 values, some of them lists nested up to 4 levels deep.
+fn generate_packet(rng: &mut Rng, depth: usize) -> String {
+    let values = (0..rng.range(0..6))
+        .map(|_| match rng.chance(0.3) {
+            true if depth < 4 => generate_packet(rng, depth + 1),
+            _ => rng.range(0..11).to_string(),
+        })
+        .collect::<Vec<String>>();
+
+    format!("[{}]", values.join(","))
+}
+
 #[cfg(test)]
 mod linked_list_tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
     fn test_from_string_with_empty_list() {
-        let list = LinkedList::from_string("[]");
+        let list = LinkedList::from_string("[]").unwrap();
         assert_eq!(list, LinkedList::Empty);
     }
 
     #[test]
     fn test_from_string_with_list_with_one_element() {
         assert_eq!(
-            LinkedList::from_string("[7]"),
+            LinkedList::from_string("[7]").unwrap(),
             LinkedList::Cons(Value::Int(7), Box::new(LinkedList::Empty))
         );
         assert_eq!(
-            LinkedList::from_string("[23]"),
+            LinkedList::from_string("[23]").unwrap(),
             LinkedList::Cons(Value::Int(23), Box::new(LinkedList::Empty))
         );
     }
 
     #[test]
     fn test_from_string_with_list_with_two() {
-        let list = LinkedList::from_string("[7,3]");
+        let list = LinkedList::from_string("[7,3]").unwrap();
         assert_eq!(
             list,
             LinkedList::Cons(
@@ -151,7 +371,7 @@ mod linked_list_tests {
 
     #[test]
     fn test_from_string_with_nested_empty_lists() {
-        let list = LinkedList::from_string("[[]]");
+        let list = LinkedList::from_string("[[]]").unwrap();
 
         assert_eq!(
             list,
@@ -164,7 +384,7 @@ mod linked_list_tests {
 
     #[test]
     fn test_from_string_with_complex_nested_list() {
-        let list = LinkedList::from_string("[[],1,[2,3],4,[5,[6],7]]");
+        let list = LinkedList::from_string("[[],1,[2,3],4,[5,[6],7]]").unwrap();
 
         assert_eq!(
             list,
@@ -202,93 +422,108 @@ mod linked_list_tests {
         );
     }
 
+    #[test]
+    fn test_from_string_with_long_packets() {
+        let deepest = format!("{}{}", "[".repeat(500), "]".repeat(500));
+        let longest = format!("[{}]", vec!["1"; 499].join(","));
+        let packets = [deepest, longest].map(|packet| LinkedList::from_string(&packet).unwrap());
+        assert_eq!(packets[0].cmp(&packets[1]), Ordering::Less);
+
+        let too_deep = format!("{}{}", "[".repeat(501), "]".repeat(501));
+        assert!(LinkedList::from_string(&too_deep).is_err());
+    }
+
     #[test]
     fn test_partial_ord() {
-        let list1 = LinkedList::from_string("[]");
-        let list2 = LinkedList::from_string("[]");
+        let list1 = LinkedList::from_string("[]").unwrap();
+        let list2 = LinkedList::from_string("[]").unwrap();
         assert!(list1 == list2);
 
-        let list1 = LinkedList::from_string("[]");
-        let list2 = LinkedList::from_string("[1]");
+        let list1 = LinkedList::from_string("[]").unwrap();
+        let list2 = LinkedList::from_string("[1]").unwrap();
         assert!(list1 < list2);
 
-        let list1 = LinkedList::from_string("[1]");
-        let list2 = LinkedList::from_string("[1]");
+        let list1 = LinkedList::from_string("[1]").unwrap();
+        let list2 = LinkedList::from_string("[1]").unwrap();
         assert!(list1 == list2);
 
-        let list1 = LinkedList::from_string("[1]");
-        let list2 = LinkedList::from_string("[3]");
+        let list1 = LinkedList::from_string("[1]").unwrap();
+        let list2 = LinkedList::from_string("[3]").unwrap();
         assert!(list1 < list2);
 
-        let list1 = LinkedList::from_string("[1]");
-        let list2 = LinkedList::from_string("[[1]]");
+        let list1 = LinkedList::from_string("[1]").unwrap();
+        let list2 = LinkedList::from_string("[[1]]").unwrap();
         assert_eq!(list1.partial_cmp(&list2), Some(Ordering::Equal));
 
-        let list1 = LinkedList::from_string("[[1],[2,3,4]]");
-        let list2 = LinkedList::from_string("[[1],4]");
+        let list1 = LinkedList::from_string("[[1],[2,3,4]]").unwrap();
+        let list2 = LinkedList::from_string("[[1],4]").unwrap();
         assert!(list1 < list2);
     }
 
     #[test]
     fn test_partial_ord_with_tricky_case() {
-        let list1 = LinkedList::from_string("[[10]]");
-        let list2 = LinkedList::from_string("[5]");
+        let list1 = LinkedList::from_string("[[10]]").unwrap();
+        let list2 = LinkedList::from_string("[5]").unwrap();
         assert!(list1 > list2);
     }
-}
 
-pub fn run(input: &str) {
-    let mut sum_of_ordered_indexes = 0;
+    #[test]
+    fn test_parse_with_unpaired_packet() {
+        assert_eq!(
+            Day13::parse("[1]\n[2]\n\n[3]").unwrap_err().to_string(),
+            "line 4 col 1: expected another packet in the pair"
+        );
+    }
+
+    fn list(values: Vec<Value>) -> LinkedList {
+        values
+            .into_iter()
+            .rev()
+            .fold(LinkedList::Empty, |tail, value| {
+                LinkedList::Cons(value, Box::new(tail))
+            })
+    }
 
-    for (pair_index, pair) in input.split("\n\n").enumerate() {
-        let pair_index = pair_index + 1;
+    // Small packets with few distinct integers, so that equal packets and ties are common.
+    fn packets() -> impl Strategy<Value = LinkedList> {
+        let value = (0_u16..4)
+            .prop_map(Value::Int)
+            .prop_recursive(4, 24, 3, |value| {
+                prop::collection::vec(value, 0..3)
+                    .prop_map(|values| Value::List(Box::new(list(values))))
+            });
 
-        let (left, right) = pair.split_once("\n").unwrap();
-        let left = LinkedList::from_string(left);
-        let right = LinkedList::from_string(right);
+        prop::collection::vec(value, 0..4).prop_map(list)
+    }
 
-        if left < right {
-            sum_of_ordered_indexes += pair_index;
-            println!("Pair {pair_index} is ordered");
-        } else if left > right {
-            println!("Pair {pair_index} is not ordered");
-        } else {
-            println!("Pair {pair_index} is equal");
+    proptest! {
+        #[test]
+        fn ord_is_antisymmetric(a in packets(), b in packets()) {
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            prop_assert_eq!(a == b, a.cmp(&b) == Ordering::Equal);
         }
-    }
 
-    println!("Sum of ordered indexs is {sum_of_ordered_indexes}");
-
-    // Part 2
-    println!("\n== Part 2 ==");
-
-    let mut packets = input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(LinkedList::from_string)
-        .collect::<Vec<LinkedList>>();
-
-    // Push the divider packets.
-    let divider_packet1 = LinkedList::from_string("[[2]]");
-    let divider_packet2 = LinkedList::from_string("[[6]]");
-    packets.push(divider_packet1.clone());
-    packets.push(divider_packet2.clone());
-
-    packets.sort();
-
-    let position1 = packets
-        .iter()
-        .position(|packet| packet == &divider_packet1)
-        .unwrap()
-        + 1;
-    let position2 = packets
-        .iter()
-        .position(|packet| packet == &divider_packet2)
-        .unwrap()
-        + 1;
-
-    println!(
-        "Position of packet 1 is {position1}, packet 2 is {position2}, key is {}",
-        position1 * position2
-    );
+        #[test]
+        fn ord_is_transitive(a in packets(), b in packets(), c in packets()) {
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+
+            if a == b && b == c {
+                prop_assert!(a == c);
+            }
+        }
+
+        #[test]
+        fn sorted_packets_are_in_order(mut packets in prop::collection::vec(packets(), 0..12)) {
+            packets.sort();
+
+            for (index, packet) in packets.iter().enumerate() {
+                for later in &packets[index..] {
+                    prop_assert!(packet <= later);
+                }
+            }
+        }
+    }
 }
@@ -1,197 +1,295 @@
-use std::collections::HashMap;
-
-type Point = (usize, usize);
-
-struct World {
-    points: HashMap<Point, char>,
-    floor_y: usize,
+use crate::{
+    error::{parse_lines, parse_number, split_once, Error},
+    geometry::{Direction8, Point2},
+    grid::{Grid, Position},
+    random::Rng,
+    solution::{trace, Answer, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+const SAND_STARTING_POINT: Position = Point2::new(500, 0);
+
+const PALETTE: Palette = &[
+    ('#', Rgb(130, 130, 140)),
+    ('o', Rgb(230, 190, 90)),
+    ('+', Rgb(240, 80, 60)),
+];
+
+// Only has the rocks and the sand, everything else (above the floor) is air.
+#[derive(Clone)]
+pub struct World {
+    points: Grid<char>,
+    floor_y: i64,
 }
 
 impl World {
-    fn at_point(&self, point: &Point) -> Option<&char> {
+    fn at_point(&self, point: Position) -> Option<&char> {
         match self.points.get(point) {
             Some(char) => Some(char),
-            None if point.1 == self.floor_y => Some(&'#'),
-            None if point.1 < self.floor_y => Some(&'.'),
+            None if point.y == self.floor_y => Some(&'#'),
+            None if point.y < self.floor_y => Some(&'.'),
             None => None,
         }
     }
 }
 
-#[cfg(test)]
-mod world_tests {
-    use super::*;
+pub struct Day14;
 
-    #[test]
-    fn test_at_point() {
-        let world = World {
-            points: HashMap::from([((500, 0), '#')]),
-            floor_y: 10,
+impl Solution for Day14 {
+    type Input = World;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let points = parse_lines(input, parse_line)?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<Position>>();
+
+        if points.is_empty() {
+            return Err(Error::new("expected at least one rock path"));
+        }
+
+        let max_y = points.iter().map(|point| point.y).max().unwrap();
+
+        let mut world = World {
+            points: Grid::sparse(),
+            floor_y: max_y + 2,
         };
 
-        assert_eq!(world.at_point(&(500, 0)), Some(&'#'));
-        assert_eq!(world.at_point(&(500, 1)), Some(&'.'));
-        assert_eq!(world.at_point(&(499, 9)), Some(&'.'));
-        assert_eq!(world.at_point(&(501, 9)), Some(&'.'));
-        assert_eq!(world.at_point(&(500, 10)), Some(&'#'));
-        assert_eq!(world.at_point(&(500, 10)), Some(&'#'));
-        assert_eq!(world.at_point(&(499, 10)), Some(&'#'));
-        assert_eq!(world.at_point(&(501, 10)), Some(&'#'));
-        assert_eq!(world.at_point(&(500, 11)), None);
+        for point in points {
+            world.points.set(point, '#');
+        }
+
+        Ok(world)
     }
-}
 
-pub fn run(input: &str) {
-    let points = input
-        .lines()
-        .map(parse_line)
-        .flatten()
-        .collect::<Vec<Point>>();
-
-    let sand_starting_point = (500, 0);
-
-    let min_x = points.iter().map(|(x, _)| x).min().unwrap();
-    let max_x = points.iter().map(|(x, _)| x).max().unwrap();
-    let min_y = points.iter().map(|(_, y)| y).min().unwrap().min(&0);
-    let max_y = points.iter().map(|(_, y)| y).max().unwrap();
-
-    let mut world = World {
-        points: HashMap::new(),
-        floor_y: max_y + 2,
-    };
-
-    for y in *min_y..=*max_y {
-        for x in *min_x..=*max_x {
-            if let Some((_, _)) = points.iter().find(|(x1, y1)| x1 == &x && y1 == &y) {
-                world.points.insert((x, y), '#');
-            } else {
-                world.points.insert((x, y), '.');
+    // Sand that falls below the lowest rock (so, right above the floor) would flow into the abyss
+    // if there was no floor, so we count the units that came to rest before that happens.
+    fn part1(world: &Self::Input) -> Answer {
+        let mut world = world.clone();
+        let mut units_of_sand_to_rest: u32 = 0;
+
+        loop {
+            let rest_point = pour_sand(&mut world, SAND_STARTING_POINT);
+
+            if rest_point.y == world.floor_y - 1 {
+                break;
             }
+
+            units_of_sand_to_rest += 1;
+
+            visualize::emit(|| {
+                let caption = format!("Part 1: {} units of sand", units_of_sand_to_rest);
+                world_frame(&world, caption, rest_point, false)
+            });
         }
+
+        units_of_sand_to_rest.into()
     }
 
-    println!("Starting world:");
-    draw_world(&world);
+    fn part2(world: &Self::Input) -> Answer {
+        let mut world = world.clone();
 
-    let mut units_of_send_to_rest = 0;
+        trace!("Starting world:\n{}", draw_world(&world));
 
-    loop {
-        let rest_point = pour_sand(&mut world, sand_starting_point);
-        units_of_send_to_rest += 1;
+        let mut units_of_send_to_rest: u32 = 0;
 
-        if rest_point == sand_starting_point {
-            break;
+        loop {
+            let rest_point = pour_sand(&mut world, SAND_STARTING_POINT);
+            units_of_send_to_rest += 1;
+
+            visualize::emit(|| {
+                let caption = format!("Part 2: {} units of sand", units_of_send_to_rest);
+                world_frame(&world, caption, rest_point, true)
+            });
+
+            if rest_point == SAND_STARTING_POINT {
+                break;
+            }
         }
+
+        trace!("\nEnd world:\n{}", draw_world(&world));
+
+        units_of_send_to_rest.into()
     }
 
-    println!("\nEnd world:");
-    draw_world(&world);
+    // `size` paths of rock, of up to 4 straight lines each, below the source of the sand. They
+    // start a few rows down, because rock right below the source could block it before any sand
+    // falls into the abyss, and then part 1 would never end.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let spread = 5 + 2 * size as i64;
+        let top = 2 + spread / 2;
+        let mut input = String::new();
+
+        for _ in 0..size.max(1) {
+            let mut point = Point2::new(
+                SAND_STARTING_POINT.x + rng.range(-spread / 2..spread / 2 + 1),
+                rng.range(top..top + spread),
+            );
+            let mut points = vec![point];
+
+            for line in 0..rng.range(1..5) {
+                let length = rng.range(-6..7);
+
+                point = match line % 2 {
+                    0 => point + Point2::new(length, 0),
+                    _ => Point2::new(point.x, (point.y + length).max(top)),
+                };
+                points.push(point);
+            }
+
+            let points = points
+                .iter()
+                .map(|point| format!("{},{}", point.x, point.y))
+                .collect::<Vec<String>>();
+            input += &points.join(" -> ");
+            input.push('\n');
+        }
 
-    println!("\nTotal units of sand that came to rest: {units_of_send_to_rest}");
+        Some(input)
+    }
 }
 
-fn parse_line(line: &str) -> Vec<Point> {
-    line.split("->")
+fn parse_line(line: &str) -> Result<Vec<Position>, Error> {
+    let points = line
+        .split("->")
         .map(|s| {
-            let (x, y) = s.trim().split_once(",").unwrap();
-            (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap())
+            let (x, y) = split_once(line, s, ",")?;
+            let point = Point2::new(
+                i64::from(parse_number::<u32>(line, x)?),
+                i64::from(parse_number::<u32>(line, y)?),
+            );
+            Ok((s, point))
         })
-        .collect::<Vec<Point>>()
+        .collect::<Result<Vec<(&str, Position)>, Error>>()?;
+
+    for pair in points.windows(2) {
+        let ((_, point1), (s, point2)) = (pair[0], pair[1]);
+
+        if point1.x != point2.x && point1.y != point2.y {
+            return Err(Error::at(
+                line,
+                s.trim(),
+                "rock paths must be horizontal or vertical",
+            ));
+        }
+    }
+
+    Ok(points
+        .into_iter()
+        .map(|(_, point)| point)
+        .collect::<Vec<Position>>()
         .windows(2)
         .flat_map(|pair_of_points| {
-            let (x1, y1) = pair_of_points[0];
-            let (x2, y2) = pair_of_points[1];
-
-            let mut path = vec![(x1, y1)];
+            let (start, end) = (pair_of_points[0], pair_of_points[1]);
+            let step = (end - start).signum();
 
-            if x1 == x2 {
-                let min_y = y1.min(y2);
-                let max_y = y1.max(y2);
+            (0..=start.manhattan_distance(end)).map(move |distance| start + step * distance)
+        })
+        .collect::<Vec<Position>>())
+}
 
-                for y in min_y..=max_y {
-                    path.push((x1, y));
-                }
-            } else {
-                let min_x = x1.min(x2);
-                let max_x = x1.max(x2);
+fn draw_world(world: &World) -> String {
+    let (min, max) = world.points.bounds().unwrap();
 
-                for x in min_x..=max_x {
-                    path.push((x, y1));
-                }
-            }
+    let drawing = world.points.render_area(
+        Point2::new(min.x, min.y.min(0)),
+        Point2::new(max.x, world.floor_y),
+        |point, _| *world.at_point(point).unwrap(),
+    );
 
-            path
-        })
-        .collect::<Vec<Point>>()
+    drawing + "\n"
 }
 
-fn draw_world(world: &World) {
-    let min_x = world.points.iter().map(|((x, _), _)| x).min().unwrap();
-    let max_x = world.points.iter().map(|((x, _), _)| x).max().unwrap();
-    let min_y = world
-        .points
-        .iter()
-        .map(|((_, y), _)| y)
-        .min()
-        .unwrap()
-        .min(&0);
-
-    for y in *min_y..=world.floor_y {
-        for x in *min_x..=*max_x {
-            print!("{}", world.at_point(&(x, y)).unwrap());
+// The rocks and the sand at rest, with the source of the sand, following the last unit to rest.
+// The floor is drawn a bit wider than the rest.
+fn world_frame(world: &World, caption: String, sand: Position, with_floor: bool) -> Frame {
+    let mut frame = Frame::new(caption, PALETTE).with_focus(sand);
+
+    frame.draw(&world.points, |_, cell| Some(*cell));
+    frame.set(SAND_STARTING_POINT, '+');
+
+    if with_floor {
+        let (min, max) = world.points.bounds().unwrap();
+
+        for x in min.x - 2..=max.x + 2 {
+            frame.set(Point2::new(x, world.floor_y), '#');
         }
-        println!();
     }
+
+    frame
 }
 
 // Returns the point where the sand comes to rest.
-fn pour_sand(world: &mut World, sand_starting_point: Point) -> Point {
+fn pour_sand(world: &mut World, sand_starting_point: Position) -> Position {
     let mut sand_point = sand_starting_point;
 
     loop {
-        let down = (sand_point.0, sand_point.1 + 1);
-        let down_left = (sand_point.0 - 1, sand_point.1 + 1);
-        let down_right = (sand_point.0 + 1, sand_point.1 + 1);
+        let down = sand_point + Direction8::South.step();
+        let down_left = sand_point + Direction8::SouthWest.step();
+        let down_right = sand_point + Direction8::SouthEast.step();
 
         match (
-            world.at_point(&down),
-            world.at_point(&down_left),
-            world.at_point(&down_right),
+            world.at_point(down),
+            world.at_point(down_left),
+            world.at_point(down_right),
         ) {
             // There is space right below, so we move the sand down and keep going.
             (Some('.'), _, _) => {
-                world.points.insert(sand_point, '.');
-                world.points.insert(down, '+');
+                world.points.remove(sand_point);
+                world.points.set(down, '+');
                 sand_point = down;
             }
 
             // Space below is taken by sand or rock, but down left is free.
             (Some('#' | 'o'), Some('.'), _) => {
-                world.points.insert(sand_point, '.');
-                world.points.insert(down_left, '+');
+                world.points.remove(sand_point);
+                world.points.set(down_left, '+');
                 sand_point = down_left;
             }
 
             // Spaces below *and* down left are taken by sand or rock, but down right is free.
             (Some('#' | 'o'), Some('#' | 'o'), Some('.')) => {
-                world.points.insert(sand_point, '.');
-                world.points.insert(down_right, '+');
+                world.points.remove(sand_point);
+                world.points.set(down_right, '+');
                 sand_point = down_right;
             }
 
             // All spaces are taken, so the sand comes to rest at the current point.
             (Some('#' | 'o'), Some('#' | 'o'), Some('#' | 'o')) => {
-                world.points.insert(sand_point, 'o');
+                world.points.set(sand_point, 'o');
                 return sand_point;
             }
-
             (a, b, c) => {
-                println!(
+                panic!(
                     "Unexpected state at {:?}: {:?} is {:?}, {:?} is {:?}, {:?} is {:?}",
                     sand_point, down, a, down_left, b, down_right, c
                 );
-                panic!();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod world_tests {
+    use super::*;
+
+    #[test]
+    fn test_at_point() {
+        let mut points = Grid::sparse();
+        points.set(Point2::new(500, 0), '#');
+        let world = World {
+            points,
+            floor_y: 10,
+        };
+
+        assert_eq!(world.at_point(Point2::new(500, 0)), Some(&'#'));
+        assert_eq!(world.at_point(Point2::new(500, 1)), Some(&'.'));
+        assert_eq!(world.at_point(Point2::new(499, 9)), Some(&'.'));
+        assert_eq!(world.at_point(Point2::new(501, 9)), Some(&'.'));
+        assert_eq!(world.at_point(Point2::new(500, 10)), Some(&'#'));
+        assert_eq!(world.at_point(Point2::new(500, 10)), Some(&'#'));
+        assert_eq!(world.at_point(Point2::new(499, 10)), Some(&'#'));
+        assert_eq!(world.at_point(Point2::new(501, 10)), Some(&'#'));
+        assert_eq!(world.at_point(Point2::new(500, 11)), None);
+    }
+}
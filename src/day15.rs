@@ -1,14 +1,44 @@
-use ansi_term::Colour;
-use std::{
-    collections::{HashMap, HashSet},
-    ops::RangeInclusive,
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    error::{parse_lines, parse_number, split_once, Error},
+    geometry::Point2,
+    interval::IntervalSet,
+    random::Rng,
+    solution::{diagnostic, Answer, Param, Progress, Solution},
+    visualize::{self, Frame, Palette, Rgb},
 };
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Point(i64, i64);
+pub type Point = Point2;
+
+const ROW: Param = Param {
+    name: "row",
+    default: 2000000,
+    // The coordinates are 32-bit.
+    min: i32::MIN as i64,
+    max: i32::MAX as i64,
+    description: "Row on which to count the positions where a beacon cannot be (part 1)",
+};
+
+const SEARCH_AREA_MAX: Param = Param {
+    name: "search_area",
+    default: 4000000,
+    min: 0,
+    max: i32::MAX as i64,
+    description: "Largest x and y of the area the distress beacon is in (part 2)",
+};
+
+const PALETTE: Palette = &[
+    ('#', Rgb(50, 70, 120)),
+    ('S', Rgb(240, 80, 60)),
+    ('B', Rgb(250, 210, 70)),
+    ('!', Rgb(90, 230, 90)),
+];
+
+const MAX_FRAME_AREA: i64 = 100_000;
 
 #[derive(Debug)]
-struct Grid {
+pub struct Grid {
     sensors_and_closest_beacons: HashMap<Point, Point>,
     top_left_corner: Point,
     bottom_right_corner: Point,
@@ -22,51 +52,53 @@ impl Grid {
         let mut max_y = 0;
 
         for (sensor, beacon) in &sensors_and_beacons {
-            let distance = Self::manhattan_distance(sensor, beacon);
-            min_x = min_x.min(sensor.0 + distance).min(sensor.0 - distance);
-            max_x = max_x.max(sensor.0 + distance).max(sensor.0 - distance);
-            min_y = min_y.min(sensor.1 + distance).min(sensor.1 - distance);
-            max_y = max_y.max(sensor.1 + distance).max(sensor.1 - distance);
+            let distance = sensor.manhattan_distance(*beacon);
+            min_x = min_x.min(sensor.x + distance).min(sensor.x - distance);
+            max_x = max_x.max(sensor.x + distance).max(sensor.x - distance);
+            min_y = min_y.min(sensor.y + distance).min(sensor.y - distance);
+            max_y = max_y.max(sensor.y + distance).max(sensor.y - distance);
         }
 
         Self {
             sensors_and_closest_beacons: HashMap::from_iter(sensors_and_beacons.iter().cloned()),
-            top_left_corner: Point(min_x, min_y),
-            bottom_right_corner: Point(max_x, max_y),
+            top_left_corner: Point::new(min_x, min_y),
+            bottom_right_corner: Point::new(max_x, max_y),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn draw(&self) {
-        for y in self.top_left_corner.1..=self.bottom_right_corner.1 {
-            print!("{:3} ", y);
-
-            let sensors: HashSet<Point> =
-                HashSet::from_iter(self.sensors_and_closest_beacons.keys().cloned());
-            let beacons: HashSet<Point> =
-                HashSet::from_iter(self.sensors_and_closest_beacons.values().cloned());
+    // Only small grids, like the sample's, are visualized. The area of grids with sensors far
+    // apart can overflow, and those aren't small either.
+    fn is_drawable(&self) -> bool {
+        let size = self.bottom_right_corner - self.top_left_corner;
+        size.x
+            .checked_mul(size.y)
+            .is_some_and(|area| area <= MAX_FRAME_AREA)
+    }
 
-            for x in self.top_left_corner.0..=self.bottom_right_corner.0 {
-                let point = Point(x, y);
+    // The sensors, their beacons, the positions the sensors cover, and the distress beacon.
+    fn frame(&self, distress_beacon: Point) -> Frame {
+        let mut frame = Frame::new(
+            "Sensors (S), their beacons (B) and the distress beacon (!)",
+            PALETTE,
+        );
 
-                if x % 5 == 0 {
-                    print!("{}", Colour::Blue.prefix());
-                }
+        for y in self.top_left_corner.y..=self.bottom_right_corner.y {
+            for x in self.top_left_corner.x..=self.bottom_right_corner.x {
+                let point = Point::new(x, y);
 
-                if sensors.contains(&point) {
-                    print!("{}", Colour::Red.paint("S"));
-                } else if beacons.contains(&point) {
-                    print!("B");
-                } else if self.is_in_sensor_range(&point) {
-                    print!("#");
-                } else {
-                    print!(".");
+                if self.is_in_sensor_range(&point) {
+                    frame.set(point, '#');
                 }
-
-                print!("{}", Colour::Blue.suffix());
             }
-            println!();
         }
+
+        for (sensor, beacon) in &self.sensors_and_closest_beacons {
+            frame.set(*sensor, 'S');
+            frame.set(*beacon, 'B');
+        }
+
+        frame.set(distress_beacon, '!');
+        frame.with_focus(distress_beacon)
     }
 
     fn is_in_sensor_range(&self, point: &Point) -> bool {
@@ -74,160 +106,268 @@ impl Grid {
             .iter()
             .filter(|(sensor, beacon)| point != *beacon && point != *sensor)
             .any(|(sensor, beacon)| {
-                Self::manhattan_distance(sensor, point) <= Self::manhattan_distance(sensor, beacon)
+                sensor.manhattan_distance(*point) <= sensor.manhattan_distance(*beacon)
             })
     }
 
-    fn manhattan_distance(a: &Point, b: &Point) -> i64 {
-        (a.0 - b.0).abs() + (a.1 - b.1).abs()
-    }
+    // Returns the positions detected by the sensors on a single row.
+    fn detected_ranges_at_row(&self, y: i64) -> IntervalSet {
+        let mut ranges = IntervalSet::new();
 
-    fn detected_ranges(&self) -> HashMap<i64, Vec<RangeInclusive<i64>>> {
-        let mut ranges_by_row = HashMap::new();
+        for (sensor, beacon) in &self.sensors_and_closest_beacons {
+            let distance = sensor.manhattan_distance(*beacon);
+            let offset = distance - (sensor.y - y).abs();
 
-        for y in self.top_left_corner.1..=self.bottom_right_corner.1 {
-            ranges_by_row.insert(y, Vec::new());
+            if offset >= 0 {
+                ranges.insert((sensor.x - offset)..(sensor.x + offset + 1));
+            }
         }
 
-        for (sensor, beacon) in &self.sensors_and_closest_beacons {
-            println!(
-                "Building ranges for sensor {:?} and its beacon {:?}",
-                sensor, beacon
-            );
+        ranges
+    }
+}
 
-            let distance = Self::manhattan_distance(sensor, beacon);
+pub struct Day15;
 
-            for current_distance in -distance..=distance {
-                let y = sensor.1 + current_distance;
+impl Solution for Day15 {
+    type Input = Grid;
 
-                let offset = (current_distance.abs() - distance).abs();
+    const PARAMS: &'static [Param] = &[ROW, SEARCH_AREA_MAX];
 
-                let range = (sensor.0 - offset)..=(sensor.0 + offset);
-                ranges_by_row.get_mut(&y).unwrap().push(range);
-            }
-        }
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let sensors_and_beacons = parse_lines(input, parse_sensor_and_beacon)?
+            .into_iter()
+            .collect();
+        Ok(Grid::new(sensors_and_beacons))
+    }
 
-        println!("Built all ranges, now merging them.");
+    // The number of positions on the row where a beacon cannot be.
+    fn part1(grid: &Self::Input) -> Answer {
+        let row = ROW.value::<i64>();
 
-        // Merge ranges on each row.
-        let mut merged_ranges = HashMap::new();
-        for (row, mut ranges) in ranges_by_row {
-            ranges.sort_by_key(|range| range.start().clone());
+        let covered = grid.detected_ranges_at_row(row).len();
 
-            let mut mut_ranges = vec![ranges[0].clone()];
+        let beacons_on_row = grid
+            .sensors_and_closest_beacons
+            .values()
+            .filter(|beacon| beacon.y == row)
+            .collect::<HashSet<&Point>>()
+            .len() as i64;
 
-            for i in 1..ranges.len() {
-                let next_range = ranges[i].clone();
-                let current_range = mut_ranges.pop().unwrap();
+        (covered - beacons_on_row).into()
+    }
 
-                if current_range.end() >= next_range.start() {
-                    let min = current_range.start().min(next_range.start());
-                    let max = current_range.end().max(next_range.end());
-                    mut_ranges.push(*min..=*max);
-                } else {
-                    mut_ranges.push(current_range);
-                    mut_ranges.push(next_range);
-                }
-            }
+    // The tuning frequency of the only position in the search area that no sensor detects.
+    fn part2(grid: &Self::Input) -> Answer {
+        let search_area_max = SEARCH_AREA_MAX.value::<i64>();
+        let search_row = IntervalSet::from(0..search_area_max + 1);
+        let mut progress = Progress::new("Examining rows", search_area_max as usize + 1);
 
-            merged_ranges.insert(row, mut_ranges);
-        }
+        for y in 0..=search_area_max {
+            progress.set(y as usize);
 
-        merged_ranges
-    }
-}
+            let ranges = grid.detected_ranges_at_row(y);
 
-#[cfg(test)]
-mod grid_tests {
-    use super::*;
+            if let Some(undetected) = search_row.difference(&ranges).iter().next() {
+                diagnostic!("Found the line with a space! It's line {y}");
+                diagnostic!("It has ranges: {:?}", ranges.iter().collect::<Vec<_>>());
 
-    #[test]
-    fn test_manhattan_distance() {
-        let a = Point(23, 12);
-        assert_eq!(Grid::manhattan_distance(&a, &a), 0);
+                let x = undetected.start;
 
-        let a = Point(1, 1);
-        let b = Point(2, 2);
-        assert_eq!(Grid::manhattan_distance(&a, &b), 2);
+                if grid.is_drawable() {
+                    visualize::emit(|| grid.frame(Point::new(x, y)));
+                }
 
-        let a = Point(1, 2);
-        let b = Point(2, 2);
-        assert_eq!(Grid::manhattan_distance(&a, &b), 1);
+                return (x * 4000000 + y).into();
+            }
+        }
 
-        let a = Point(-1, -1);
-        let b = Point(2, 2);
-        assert_eq!(Grid::manhattan_distance(&a, &b), 6);
+        diagnostic!("No undetected position in the search area");
+        Answer::Unsolved
     }
 
-    #[test]
-    fn test_is_in_sensor_range() {
-        let grid = Grid::new(vec![
-            (Point(0, 0), Point(0, 1)),
-            (Point(10, 10), Point(5, 5)),
-        ]);
-        assert!(grid.is_in_sensor_range(&Point(7, 6)));
-        assert!(grid.is_in_sensor_range(&Point(5, 6)));
-        assert!(!grid.is_in_sensor_range(&Point(4, 4)));
-        assert!(!grid.is_in_sensor_range(&Point(0, 0)));
-        assert!(!grid.is_in_sensor_range(&Point(0, 1)));
-    }
-}
+    // A distress beacon in the search area (see the parameter), and `size` sensors that don't
+    // reach it and stay within the search area. Four more sensors, past the corners of the
+    // search area, cover everything else in it.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let area = SEARCH_AREA_MAX.value::<i64>().max(1);
+        let distress_beacon = Point::new(rng.range(0..area + 1), rng.range(0..area + 1));
+        let mut sensors = Vec::new();
+
+        for (x, y) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+            let sensor = distress_beacon + Point::new(x * area, y * area);
+            // Next to the distress beacon, but away from the search area.
+            let beacon = sensor + Point::new(0, y * (2 * area - 1));
+            sensors.push((sensor, beacon));
+        }
 
-pub fn run(input: &str) {
-    let sensors_and_beacons = input.lines().map(parse_sensor_and_beacon).collect();
-    let grid = Grid::new(sensors_and_beacons);
+        for _ in 0..size {
+            let sensor = Point::new(rng.range(0..area + 1), rng.range(0..area + 1));
+            let edge = [sensor.x, sensor.y, area - sensor.x, area - sensor.y];
+            let radius =
+                (sensor.manhattan_distance(distress_beacon) - 1).min(*edge.iter().min().unwrap());
 
-    let detected_ranges = grid.detected_ranges();
+            if radius <= 0 || sensor == distress_beacon {
+                continue;
+            }
 
-    for y in 0..=4000000 {
-        if y % 1000 == 0 {
-            println!("Examining row {}", y);
+            let dx = rng.range(-radius..radius + 1);
+            let dy = (radius - dx.abs()) * if rng.chance(0.5) { 1 } else { -1 };
+            sensors.push((sensor, sensor + Point::new(dx, dy)));
         }
 
-        let ranges = detected_ranges.get(&y).unwrap();
-        let ranges_len = ranges.len();
+        rng.shuffle(&mut sensors);
 
-        if ranges_len > 1 {
-            println!("Found the line with a space! It's line {y}");
-            println!("It has ranges: {:?}", ranges);
-            break;
-        }
+        let lines = sensors
+            .iter()
+            .map(|(sensor, beacon)| {
+                format!(
+                    "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                    sensor.x, sensor.y, beacon.x, beacon.y
+                )
+            })
+            .collect();
+
+        Some(lines)
+    }
+
+    // Checks every position of the row, and then of the search area, against every sensor. Only
+    // for small inputs, as it takes as long as the number of positions.
+    fn reference(grid: &Self::Input) -> Option<(Answer, Answer)> {
+        const MAX_POSITIONS: i64 = 1_000_000;
+
+        let detected = |point: Point| {
+            grid.sensors_and_closest_beacons
+                .iter()
+                .any(|(sensor, beacon)| {
+                    sensor.manhattan_distance(point) <= sensor.manhattan_distance(*beacon)
+                })
+        };
+        let beacons = grid
+            .sensors_and_closest_beacons
+            .values()
+            .collect::<HashSet<&Point>>();
+
+        let (min_x, max_x) = (grid.top_left_corner.x, grid.bottom_right_corner.x);
+        let row = ROW.value::<i64>();
+
+        let part1 = match max_x - min_x < MAX_POSITIONS {
+            true => (min_x..=max_x)
+                .map(|x| Point::new(x, row))
+                .filter(|point| detected(*point) && !beacons.contains(point))
+                .count()
+                .into(),
+            false => Answer::Unsolved,
+        };
+
+        let area = SEARCH_AREA_MAX.value::<i64>();
+
+        let part2 = match (area + 1)
+            .checked_mul(area + 1)
+            .is_some_and(|positions| positions <= MAX_POSITIONS)
+        {
+            true => (0..=area)
+                .flat_map(|y| (0..=area).map(move |x| Point::new(x, y)))
+                .find(|point| !detected(*point))
+                .map_or(Answer::Unsolved, |point| {
+                    (point.x * 4000000 + point.y).into()
+                }),
+            false => Answer::Unsolved,
+        };
+
+        Some((part1, part2))
     }
 }
 
-fn parse_sensor_and_beacon(line: &str) -> (Point, Point) {
-    let (sensor, beacon) = line.split_once(":").unwrap();
-    (parse_coordinates(sensor), parse_coordinates(beacon))
+fn parse_sensor_and_beacon(line: &str) -> Result<(Point, Point), Error> {
+    let (sensor, beacon) = split_once(line, line, ":")?;
+    Ok((
+        parse_coordinates(line, sensor)?,
+        parse_coordinates(line, beacon)?,
+    ))
 }
 
-fn parse_coordinates(string: &str) -> Point {
-    let (x, y) = string.split_once(",").unwrap();
-    let (_, x) = x.split_once("=").unwrap();
-    let x = x.trim().parse::<i64>().unwrap();
+// Coordinates are 32-bit so that distances and the corners of the covered area can't overflow,
+// though the area itself can.
+fn parse_coordinates(line: &str, string: &str) -> Result<Point, Error> {
+    let (x, y) = split_once(line, string, ",")?;
+    let (_, x) = split_once(line, x, "=")?;
+    let x = parse_number::<i32>(line, x)?;
 
-    let (_, y) = y.split_once("=").unwrap();
-    let y = y.trim().parse::<i64>().unwrap();
+    let (_, y) = split_once(line, y, "=")?;
+    let y = parse_number::<i32>(line, y)?;
 
-    Point(x, y)
+    Ok(Point::new(x.into(), y.into()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_in_sensor_range() {
+        let grid = Grid::new(vec![
+            (Point::new(0, 0), Point::new(0, 1)),
+            (Point::new(10, 10), Point::new(5, 5)),
+        ]);
+        assert!(grid.is_in_sensor_range(&Point::new(7, 6)));
+        assert!(grid.is_in_sensor_range(&Point::new(5, 6)));
+        assert!(!grid.is_in_sensor_range(&Point::new(4, 4)));
+        assert!(!grid.is_in_sensor_range(&Point::new(0, 0)));
+        assert!(!grid.is_in_sensor_range(&Point::new(0, 1)));
+    }
+
+    #[test]
+    fn test_is_drawable() {
+        let grid = Grid::new(vec![(Point::new(0, 11), Point::new(-2, 10))]);
+        assert!(grid.is_drawable());
+
+        let grid = Grid::new(vec![(
+            Point::new(-2000000000, 0),
+            Point::new(-2000000000, 2000000000),
+        )]);
+        assert!(!grid.is_drawable());
+    }
+
     #[test]
     fn test_parse_coordinates() {
-        assert_eq!(parse_coordinates("x=495, y=2"), Point(495, 2));
-        assert_eq!(parse_coordinates("x=495, y=2 "), Point(495, 2));
-        assert_eq!(parse_coordinates(" x=495, y=2"), Point(495, 2));
-        assert_eq!(parse_coordinates(" x=495, y=2 "), Point(495, 2));
+        assert_eq!(
+            parse_coordinates("x=495, y=2", "x=495, y=2").unwrap(),
+            Point::new(495, 2)
+        );
+        assert_eq!(
+            parse_coordinates("x=495, y=2 ", "x=495, y=2 ").unwrap(),
+            Point::new(495, 2)
+        );
+        assert_eq!(
+            parse_coordinates(" x=495, y=2", " x=495, y=2").unwrap(),
+            Point::new(495, 2)
+        );
+        assert_eq!(
+            parse_coordinates(" x=495, y=2 ", " x=495, y=2 ").unwrap(),
+            Point::new(495, 2)
+        );
     }
 
     #[test]
     fn test_parse_sensor() {
         assert_eq!(
-            parse_sensor_and_beacon("Sensor at x=0, y=11: closest beacon is at x=-2, y=10"),
-            (Point(0, 11), Point(-2, 10),)
+            parse_sensor_and_beacon("Sensor at x=0, y=11: closest beacon is at x=-2, y=10")
+                .unwrap(),
+            (Point::new(0, 11), Point::new(-2, 10),)
         );
     }
+
+    #[test]
+    fn test_part2_outside_the_sensors() {
+        let grid = Day15::parse("Sensor at x=0, y=0: closest beacon is at x=0, y=1").unwrap();
+
+        crate::solution::override_params(vec![("search_area", 3)]);
+        assert_eq!(Day15::part2(&grid), Answer::Number(8000000));
+
+        crate::solution::override_params(vec![("search_area", 0)]);
+        assert_eq!(Day15::part2(&grid), Answer::Unsolved);
+
+        crate::solution::override_params(Vec::new());
+    }
 }
@@ -1,93 +1,78 @@
-use infinitable::Infinitable;
+use itertools::Itertools;
 use petgraph::graphmap;
-use regex::Regex;
 use std::{
+    cmp::Reverse,
     collections::{HashMap, HashSet},
     fmt::Debug,
     str::FromStr,
 };
 
-const MINUTES: u16 = 26;
+use crate::{
+    error::{parse_lines, Error},
+    parse::{list, Pattern},
+    random::Rng,
+    search::AllPairs,
+    solution::{diagnostic, Answer, Param, Solution},
+};
+
+const MINUTES_ALONE: Param = Param {
+    name: "minutes_alone",
+    default: 30,
+    min: 0,
+    max: u16::MAX as i64,
+    description: "Minutes to release pressure alone (part 1)",
+};
+
+const MINUTES: Param = Param {
+    name: "minutes",
+    default: 26,
+    min: 0,
+    max: u16::MAX as i64,
+    description: "Minutes to release pressure with the elephant (part 2)",
+};
+
+// "tunnel leads to valve" when there is only one.
+static VALVE: Pattern =
+    Pattern::new("Valve {valve} has flow rate={flow_rate}; tunnel{_} lead{_} to valve{_} {valves}");
 
 type ValveID = String;
-type ValveGraph<'a> = graphmap::UnGraphMap<&'a str, ()>;
+pub type ValveGraph<'a> = graphmap::UnGraphMap<&'a str, ()>;
 
+/// The length of the shortest path between each pair of valves.
 #[derive(Debug)]
-struct DistanceMatrix<'a>(HashMap<(&'a str, &'a str), u32>);
+pub struct DistanceMatrix<'a>(AllPairs<&'a str>);
 
 impl<'a> DistanceMatrix<'a> {
     pub fn from_graph(graph: &ValveGraph<'a>) -> DistanceMatrix<'a> {
-        // If a node is not reachable from another node, the (u, v) pair in the returned matrix
-        // is just not present.
-        let mut dist = HashMap::new();
-
-        // Initialize edges to their weight (hard-coded 1 on our case).
-        for (u, v, ()) in graph.all_edges() {
-            dist.insert((u, v), 1);
-        }
-
-        // Initialize the diagonals to 0.
-        for v in graph.nodes() {
-            dist.insert((v, v), 0);
-        }
-
-        for k in graph.nodes() {
-            for i in graph.nodes() {
-                for j in graph.nodes() {
-                    let d_ik = Self::get_(&dist, i, k);
-                    let d_kj = Self::get_(&dist, k, j);
-                    let d_ij = Self::get_(&dist, i, j);
-
-                    let sum = d_ik.and_then(|f| d_kj.map(|s| f + s));
-
-                    if Infinitable::finite_or_infinity(d_ij) > Infinitable::finite_or_infinity(sum)
-                    {
-                        dist.insert((i, j), sum.unwrap());
-                    }
-                }
-            }
-        }
-
-        Self(dist)
+        // Every tunnel takes a minute.
+        Self(AllPairs::new(graph.nodes(), |valve| {
+            graph.neighbors(valve).map(|neighbor| (neighbor, 1))
+        }))
     }
 
+    // None if a valve is not reachable from the other.
     pub fn get(&self, u: &'a str, v: &'a str) -> Option<u32> {
-        Self::get_(&self.0, u, v)
-    }
-
-    fn get_(map: &HashMap<(&str, &str), u32>, u: &str, v: &str) -> Option<u32> {
-        map.get(&(u, v)).or(map.get(&(v, u))).copied()
+        self.0.distance(&u, &v).map(|distance| distance as u32)
     }
 }
 
 #[derive(Hash, PartialEq, Eq, Clone)]
-struct Valve {
+pub struct Valve {
     id: ValveID,
     flow_rate: u32,
     connected_valves: Vec<ValveID>,
 }
 
 impl FromStr for Valve {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(
-            r"
-            Valve (?P<valve>\w{2}) has flow rate=(?P<flow_rate>\d+); tunnel(s?) lead(s?) to valve(s?) (?P<valves>.+)
-        "
-            .trim()
-        )
-        .unwrap();
-
-        let cap = re.captures(s).unwrap();
+        let valve = VALVE.parse(s)?;
 
         Ok(Self {
-            id: cap["valve"].to_string(),
-            flow_rate: cap["flow_rate"].parse::<u32>().unwrap(),
-            connected_valves: cap["valves"]
-                .split(",")
-                .map(|v| v.trim().to_string())
-                .collect::<Vec<ValveID>>(),
+            id: valve.get("valve").to_string(),
+            flow_rate: valve.number("flow_rate")?,
+            connected_valves: list(valve.get("valves"), ",", |id| Ok(id.to_string()))?,
         })
     }
 }
@@ -102,26 +87,11 @@ impl Debug for Valve {
     }
 }
 
-#[cfg(test)]
-mod valve_tests {
-    use super::*;
-
-    #[test]
-    fn test_from_str() {
-        let input = "Valve AA has flow rate=1; tunnels lead to valves BB, CC";
-        let valve = Valve::from_str(input).unwrap();
-
-        assert_eq!(valve.id, "AA");
-        assert_eq!(valve.flow_rate, 1);
-        assert_eq!(valve.connected_valves, vec!["BB", "CC"]);
-    }
-}
-
 #[derive(Clone)]
 struct State {
     open_valves: HashSet<ValveID>,
     current_valve: ValveID,
-    released_pressure: u32,
+    released_pressure: u64,
     time_left: u16,
     path: String,
 }
@@ -137,12 +107,12 @@ impl Debug for State {
 }
 
 impl State {
-    pub fn new() -> Self {
+    pub fn new(minutes: u16) -> Self {
         Self {
             open_valves: HashSet::new(),
             current_valve: "AA".to_string(),
             released_pressure: 0,
-            time_left: MINUTES,
+            time_left: minutes,
             path: String::from("AA"),
         }
     }
@@ -163,9 +133,12 @@ impl State {
             .filter(|v| flow_rates[*v] > 0)
             .filter(|v| v != &self.current_valve)
         {
-            let distance = distance_matrix
-                .get(&self.current_valve.as_str(), closed_valve.clone())
-                .unwrap() as u16;
+            // Valves in another part of the network can't be reached at all.
+            let Some(distance) = distance_matrix.get(self.current_valve.as_str(), closed_valve)
+            else {
+                continue;
+            };
+            let distance = distance as u16;
 
             // Adding one to simulate opening the valve.
             if distance + 1 >= self.time_left {
@@ -178,7 +151,7 @@ impl State {
             next_state.time_left -= distance + 1;
 
             // Then, we calculate the increased pressure with the current open valves.
-            next_state.increase_released_pressure(distance + 1, &flow_rates);
+            next_state.increase_released_pressure(distance + 1, flow_rates);
 
             // Then we update the current valve and "open it" (insert it int he current open
             // valves).
@@ -201,7 +174,7 @@ impl State {
             next_state
                 .path
                 .push_str(format!("->wait({})", self.time_left).as_str());
-            next_state.increase_released_pressure(self.time_left, &flow_rates);
+            next_state.increase_released_pressure(self.time_left, flow_rates);
             next_state.time_left = 0;
             next_states.push(next_state);
         }
@@ -213,10 +186,10 @@ impl State {
         let increment = self
             .open_valves
             .iter()
-            .map(|v| flow_rates[v.as_str()])
-            .sum::<u32>();
+            .map(|v| u64::from(flow_rates[v.as_str()]))
+            .sum::<u64>();
 
-        self.released_pressure += increment * time as u32;
+        self.released_pressure += increment * u64::from(time);
 
         self.path
             .push_str(format!("(+{} times {})", increment, time).as_str());
@@ -225,49 +198,318 @@ impl State {
 
 // Main program.
 
-pub fn run(input: &str) {
-    // let input = r#"
-    // Valve AA has flow rate=0; tunnels lead to valves BB
-    // Valve BB has flow rate=13; tunnels lead to valves AA
-    // "#
-    // .trim();
-
-    let valves = input
-        .lines()
-        .map(|line| line.parse::<Valve>().unwrap())
-        .collect::<Vec<Valve>>();
-
-    let mut explored_states = 0;
-    let graph = graph_from_valves(&valves);
-    let distance_matrix = DistanceMatrix::from_graph(&graph);
-    let flow_rates: HashMap<String, u32> =
-        HashMap::from_iter(valves.iter().map(|v| (v.id.clone(), v.flow_rate)));
-
-    let best_human_state = run_simulation(
-        State::new(),
-        &distance_matrix,
-        &graph,
-        &flow_rates,
-        &mut explored_states,
-    );
-
-    let mut edited_flow_rates = flow_rates.clone();
-    for valve in best_human_state.open_valves {
-        edited_flow_rates.insert(valve, 0);
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Vec<Valve>;
+
+    const PARAMS: &'static [Param] = &[MINUTES_ALONE, MINUTES];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let valves = parse_lines(input, Valve::from_str)?;
+
+        if !valves.iter().any(|valve| valve.id == "AA") {
+            return Err(Error::new("expected a valve AA to start from"));
+        }
+
+        let mut ids = HashSet::new();
+        let mut with_flow = 0;
+
+        for (line, valve) in input.lines().zip(&valves) {
+            // The fields are parsed again to report the error where they are.
+            let error = |field, message: String| {
+                let fields = VALVE.parse(line)?;
+                Err(Error::at(line, fields.get(field), message).within(input, line))
+            };
+
+            if !ids.insert(valve.id.as_str()) {
+                return error("valve", format!("valve {} is already defined", valve.id));
+            }
+
+            // The sets of open valves are bitmasks of the ones with a flow rate.
+            with_flow += usize::from(valve.flow_rate > 0);
+
+            if with_flow > 64 {
+                return error(
+                    "flow_rate",
+                    "expected at most 64 valves with a flow rate".to_string(),
+                );
+            }
+
+            for id in &valve.connected_valves {
+                if !valves.iter().any(|valve| valve.id == *id) {
+                    // The tunnels are at the end of the line.
+                    let tunnel = &line[line.rfind(id.as_str()).unwrap()..][..id.len()];
+                    return Err(Error::at(input, tunnel, format!("no valve {}", id)));
+                }
+            }
+        }
+
+        Ok(valves)
+    }
+
+    fn part1(valves: &Self::Input) -> Answer {
+        let mut explored_states = 0;
+        let graph = graph_from_valves(valves);
+        let distance_matrix = DistanceMatrix::from_graph(&graph);
+        let flow_rates: HashMap<String, u32> =
+            HashMap::from_iter(valves.iter().map(|v| (v.id.clone(), v.flow_rate)));
+
+        let best_state = run_simulation(
+            State::new(MINUTES_ALONE.value()),
+            &distance_matrix,
+            &graph,
+            &flow_rates,
+            &mut explored_states,
+        );
+
+        diagnostic!("Explored {explored_states} states");
+
+        best_state.released_pressure.into()
+    }
+
+    // We and the elephant open different valves, so the best we can do together is the best of
+    // the pairs of disjoint sets of valves that each of us can open alone in the time.
+    fn part2(valves: &Self::Input) -> Answer {
+        let mut explored_states = 0;
+        let graph = graph_from_valves(valves);
+        let distance_matrix = DistanceMatrix::from_graph(&graph);
+        let flow_rates: HashMap<String, u32> =
+            HashMap::from_iter(valves.iter().map(|v| (v.id.clone(), v.flow_rate)));
+        // Only valves with a flow rate are ever opened.
+        let bits = valves
+            .iter()
+            .filter(|valve| valve.flow_rate > 0)
+            .enumerate()
+            .map(|(index, valve)| (valve.id.clone(), 1 << index))
+            .collect::<HashMap<ValveID, u64>>();
+
+        let mut best_by_open_valves = HashMap::new();
+        record_open_valves(
+            State::new(MINUTES.value()),
+            &distance_matrix,
+            &graph,
+            &flow_rates,
+            &bits,
+            &mut best_by_open_valves,
+            &mut explored_states,
+        );
+
+        diagnostic!(
+            "Explored {explored_states} states, opening {} different sets of valves",
+            best_by_open_valves.len()
+        );
+
+        // Sorted by pressure, the pairs can stop as soon as they can't beat the best one.
+        let mut best_by_open_valves = best_by_open_valves.into_iter().collect::<Vec<(u64, u64)>>();
+        best_by_open_valves.sort_by_key(|(_, pressure)| Reverse(*pressure));
+
+        let mut best = 0;
+
+        for (index, (human_valves, human_pressure)) in best_by_open_valves.iter().enumerate() {
+            if human_pressure * 2 < best {
+                break;
+            }
+
+            for (elephant_valves, elephant_pressure) in &best_by_open_valves[index..] {
+                if human_pressure + elephant_pressure <= best {
+                    break;
+                }
+
+                if human_valves & elephant_valves == 0 {
+                    best = human_pressure + elephant_pressure;
+                }
+            }
+        }
+
+        best.into()
+    }
+
+    // `size` valves (at least 2), up to 15 of which have a flow rate, all connected to each
+    // other through tunnels.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut ids = vec!["AA".to_string()];
+
+        while ids.len() < size.max(2) {
+            let id = (0..2)
+                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                .collect::<String>();
+
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+
+        let mut tunnels = vec![Vec::new(); ids.len()];
+        let mut connect = |from: usize, to: usize| {
+            if from != to && !tunnels[from].contains(&to) {
+                tunnels[from].push(to);
+                tunnels[to].push(from);
+            }
+        };
+
+        for valve in 1..ids.len() {
+            connect(valve, rng.below(valve));
+        }
+
+        for _ in 0..ids.len() / 3 {
+            connect(rng.below(ids.len()), rng.below(ids.len()));
+        }
+
+        let mut with_flow = (1..ids.len()).collect::<Vec<usize>>();
+        rng.shuffle(&mut with_flow);
+        with_flow.truncate(15.min(ids.len() * 2 / 3));
+
+        let mut lines = (0..ids.len())
+            .map(|valve| {
+                let flow_rate = match with_flow.contains(&valve) {
+                    true => rng.range(1..26),
+                    false => 0,
+                };
+                let valves = tunnels[valve]
+                    .iter()
+                    .map(|other| ids[*other].as_str())
+                    .collect::<Vec<&str>>();
+                let tunnels = match valves.len() {
+                    1 => "tunnel leads to valve",
+                    _ => "tunnels lead to valves",
+                };
+
+                format!(
+                    "Valve {} has flow rate={}; {} {}\n",
+                    ids[valve],
+                    flow_rate,
+                    tunnels,
+                    valves.join(", ")
+                )
+            })
+            .collect::<Vec<String>>();
+
+        rng.shuffle(&mut lines);
+        Some(lines.concat())
     }
 
-    let best_elephant_state = run_simulation(
-        State::new(),
-        &distance_matrix,
-        &graph,
-        &edited_flow_rates,
-        &mut explored_states,
-    );
-
-    println!(
-        "Max pressure found after exploring {explored_states} states: {}",
-        best_human_state.released_pressure + best_elephant_state.released_pressure,
-    );
+    // Tries every move (opening the valve, taking a tunnel or staying) of every minute, keeping
+    // the most pressure released from each state, with the elephant moving at the same time in
+    // part 2. Only for small inputs, as the states grow exponentially with the valves that have a
+    // flow rate.
+    fn reference(valves: &Self::Input) -> Option<(Answer, Answer)> {
+        const MAX_STATES: u64 = 10_000_000;
+
+        let network = ValveNetwork::new(valves)?;
+        let start = valves.iter().position(|valve| valve.id == "AA")?;
+        let (minutes_alone, minutes) = (MINUTES_ALONE.value::<u32>(), MINUTES.value::<u32>());
+
+        let with_flow = network.flow_rates.iter().filter(|rate| **rate > 0).count();
+        let states = |minutes: u32, positions: u64| {
+            positions.saturating_mul(minutes as u64) << with_flow.min(63)
+        };
+        let positions = valves.len() as u64;
+
+        let part1 = match states(minutes_alone, positions) <= MAX_STATES {
+            true => network
+                .most_pressure(&[start], 0, minutes_alone, &mut HashMap::new())
+                .into(),
+            false => Answer::Unsolved,
+        };
+
+        let part2 = match states(minutes, positions * positions) <= MAX_STATES {
+            true => network
+                .most_pressure(&[start, start], 0, minutes, &mut HashMap::new())
+                .into(),
+            false => Answer::Unsolved,
+        };
+
+        Some((part1, part2))
+    }
+}
+
+// The valves by index, for the reference solver.
+struct ValveNetwork {
+    tunnels: Vec<Vec<usize>>,
+    flow_rates: Vec<u32>,
+}
+
+impl ValveNetwork {
+    // None if there are too many valves for a bitmask of the open ones.
+    fn new(valves: &[Valve]) -> Option<Self> {
+        if valves.len() > 64 {
+            return None;
+        }
+
+        let index = |id: &String| valves.iter().position(|valve| valve.id == *id);
+
+        Some(Self {
+            tunnels: valves
+                .iter()
+                .map(|valve| valve.connected_valves.iter().filter_map(index).collect())
+                .collect(),
+            flow_rates: valves.iter().map(|valve| valve.flow_rate).collect(),
+        })
+    }
+
+    // The most pressure that can be released from now on by the actors (us, and the elephant) at
+    // the given valves, with the valves of the `open` bitmask already open.
+    fn most_pressure(
+        &self,
+        positions: &[usize],
+        open: u64,
+        minutes: u32,
+        memo: &mut HashMap<(Vec<usize>, u64, u32), u64>,
+    ) -> u64 {
+        if minutes == 0 {
+            return 0;
+        }
+
+        // The actors are interchangeable.
+        let mut key = positions.to_vec();
+        key.sort();
+
+        if let Some(&pressure) = memo.get(&(key.clone(), open, minutes)) {
+            return pressure;
+        }
+
+        // The moves of each actor: where it is next, and the valve it opens, if any.
+        let moves = positions
+            .iter()
+            .map(|&valve| {
+                let mut moves = vec![(valve, None)];
+                moves.extend(self.tunnels[valve].iter().map(|&next| (next, None)));
+
+                if self.flow_rates[valve] > 0 && open & (1 << valve) == 0 {
+                    moves.push((valve, Some(valve)));
+                }
+
+                moves
+            })
+            .multi_cartesian_product();
+
+        let mut best = 0;
+
+        for moves in moves {
+            let opened = moves
+                .iter()
+                .filter_map(|(_, opened)| *opened)
+                .collect::<Vec<usize>>();
+
+            if opened.len() == 2 && opened[0] == opened[1] {
+                continue;
+            }
+
+            let pressure = opened
+                .iter()
+                .map(|&valve| u64::from(self.flow_rates[valve]) * u64::from(minutes - 1))
+                .sum::<u64>();
+            let open = opened.iter().fold(open, |open, valve| open | 1 << valve);
+            let next_positions = moves.iter().map(|(next, _)| *next).collect::<Vec<usize>>();
+
+            best =
+                best.max(pressure + self.most_pressure(&next_positions, open, minutes - 1, memo));
+        }
+
+        memo.insert((key, open, minutes), best);
+        best
+    }
 }
 
 fn run_simulation(
@@ -287,7 +529,49 @@ fn run_simulation(
         .unwrap_or(state)
 }
 
-fn graph_from_valves(valves: &Vec<Valve>) -> ValveGraph {
+// Records, for each set of valves opened on the way (as a bitmask of their `bits`), the most
+// pressure released by the end of the time when no more valves are opened after them.
+fn record_open_valves(
+    state: State,
+    distance_matrix: &DistanceMatrix,
+    graph: &ValveGraph,
+    flow_rates: &HashMap<String, u32>,
+    bits: &HashMap<ValveID, u64>,
+    best_by_open_valves: &mut HashMap<u64, u64>,
+    explored_states: &mut u32,
+) {
+    *explored_states += 1;
+
+    let open_valves = state
+        .open_valves
+        .iter()
+        .fold(0, |open, valve| open | bits[valve]);
+    let flow_rate = state
+        .open_valves
+        .iter()
+        .map(|valve| u64::from(flow_rates[valve]))
+        .sum::<u64>();
+    let pressure = state.released_pressure + flow_rate * u64::from(state.time_left);
+
+    let best = best_by_open_valves.entry(open_valves).or_insert(0);
+    *best = (*best).max(pressure);
+
+    for next_state in state.next_states(graph, distance_matrix, flow_rates) {
+        if next_state.time_left > 0 {
+            record_open_valves(
+                next_state,
+                distance_matrix,
+                graph,
+                flow_rates,
+                bits,
+                best_by_open_valves,
+                explored_states,
+            );
+        }
+    }
+}
+
+pub fn graph_from_valves(valves: &[Valve]) -> ValveGraph<'_> {
     let mut graph: ValveGraph = graphmap::UnGraphMap::new();
 
     for valve in valves.iter() {
@@ -302,3 +586,77 @@ fn graph_from_valves(valves: &Vec<Valve>) -> ValveGraph {
 
     graph
 }
+
+#[cfg(test)]
+mod valve_tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        let input = "Valve AA has flow rate=1; tunnels lead to valves BB, CC";
+        let valve = Valve::from_str(input).unwrap();
+
+        assert_eq!(valve.id, "AA");
+        assert_eq!(valve.flow_rate, 1);
+        assert_eq!(valve.connected_valves, vec!["BB", "CC"]);
+
+        let input = "Valve DT has flow rate=24; tunnel leads to valve RW";
+        assert_eq!(Valve::from_str(input).unwrap().connected_valves, vec!["RW"]);
+
+        let input = "Valve DT has flow rate=24; tunnel goes to valve RW";
+        assert_eq!(
+            Valve::from_str(input).unwrap_err().to_string(),
+            "line 1 col 35: expected `lead`"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = "Valve AA has flow rate=0; tunnels lead to valves BB, CC\n\
+                     Valve BB has flow rate=1; tunnel leads to valve AA";
+        assert_eq!(
+            Day16::parse(input).unwrap_err().to_string(),
+            "line 1 col 54: no valve CC"
+        );
+
+        let input = "Valve BB has flow rate=1; tunnel leads to valve BB";
+        assert_eq!(
+            Day16::parse(input).unwrap_err().to_string(),
+            "expected a valve AA to start from"
+        );
+
+        let input = "Valve AA has flow rate=0; tunnel leads to valve BB\n\
+                     Valve BB has flow rate=1; tunnel leads to valve AA\n\
+                     Valve AA has flow rate=2; tunnel leads to valve BB";
+        assert_eq!(
+            Day16::parse(input).unwrap_err().to_string(),
+            "line 3 col 7: valve AA is already defined"
+        );
+
+        let ids = (0..65)
+            .map(|index| format!("V{}", index))
+            .collect::<Vec<String>>();
+        let input = ids
+            .iter()
+            .map(|id| format!("Valve {} has flow rate=1; tunnel leads to valve AA\n", id))
+            .collect::<String>();
+        let input = format!(
+            "Valve AA has flow rate=0; tunnels lead to valves {}\n{}",
+            ids.join(", "),
+            input
+        );
+        assert_eq!(
+            Day16::parse(&input).unwrap_err().to_string(),
+            "line 66 col 25: expected at most 64 valves with a flow rate"
+        );
+    }
+
+    #[test]
+    fn test_large_flow_rates() {
+        let input = "Valve AA has flow rate=0; tunnel leads to valve BB\n\
+                     Valve BB has flow rate=4000000000; tunnel leads to valve AA";
+        let valves = Day16::parse(input).unwrap();
+
+        assert_eq!(Day16::part1(&valves), Answer::Number(4000000000 * 28));
+    }
+}
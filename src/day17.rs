@@ -1,6 +1,55 @@
+use std::collections::HashSet;
+
 use Rock::*;
 
-type Position = (i128, i128);
+use crate::{
+    cycle::Cycle,
+    error::Error,
+    geometry::{Direction4, Point2},
+    grid::{Grid, Position},
+    random::Rng,
+    solution::{diagnostic, Answer, Param, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+const ROCKS: Param = Param {
+    name: "rocks",
+    default: 2022,
+    min: 0,
+    // Dropping rocks slows down as rows pile up in the chamber, see `Chamber::compress`.
+    max: 20_000,
+    description: "Rocks to drop (part 1)",
+};
+
+const TOTAL_ROCKS: Param = Param {
+    name: "total_rocks",
+    default: 1_000_000_000_000,
+    min: 0,
+    // So that a tower of rocks up to 4 rows tall fits.
+    max: i64::MAX / 4,
+    description: "Rocks to drop (part 2)",
+};
+
+// The order the rocks fall in, over and over.
+const ROCK_ORDER: [Rock; 5] = [MinusSign, PlusSign, ReverseL, VerticalLine, Square];
+
+// How deep below the top of the tower the surface of its state goes, see `Chamber::surface`.
+const SURFACE_DEPTH: i64 = 50;
+
+// The most rocks part 2 drops to find a cycle. Even a single jet takes a few rocks before the
+// surface settles, so this doesn't depend on the length of the pattern.
+const MAX_CYCLE_ROCKS: usize = 50_000;
+
+// Rows grow upwards in the chamber, so rocks fall towards smaller `y`s.
+const FALL: Position = Point2::new(0, -1);
+
+const PALETTE: Palette = &[
+    ('#', Rgb(150, 150, 160)),
+    ('@', Rgb(240, 130, 40)),
+    ('|', Rgb(90, 90, 100)),
+    ('-', Rgb(90, 90, 100)),
+    ('+', Rgb(90, 90, 100)),
+];
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Rock {
@@ -12,7 +61,7 @@ enum Rock {
 }
 
 impl std::str::FromStr for Rock {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
@@ -28,58 +77,80 @@ impl std::str::FromStr for Rock {
         } else if s == "##\n##" {
             Ok(Square)
         } else {
-            Err(())
+            Err(Error::at(s, s, "unknown rock shape"))
         }
     }
 }
 
 impl Rock {
-    pub fn to_positions(&self) -> Vec<Position> {
-        match self {
-            MinusSign => vec![(0, 0), (0, 1), (0, 2), (0, 3)],
-            VerticalLine => vec![(0, 0), (1, 0), (2, 0), (3, 0)],
-            PlusSign => vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)],
-            ReverseL => vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)],
-            Square => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
-        }
+    // The positions of the rock's parts, from its bottom left corner.
+    pub fn to_positions(self) -> Vec<Position> {
+        let positions: &[(i64, i64)] = match self {
+            MinusSign => &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            VerticalLine => &[(0, 0), (0, 1), (0, 2), (0, 3)],
+            PlusSign => &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+            ReverseL => &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            Square => &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        };
+
+        positions
+            .iter()
+            .map(|position| Point2::from(*position))
+            .collect()
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
+// The direction a jet of hot gas pushes rocks in.
+fn jet(c: char) -> Option<Direction4> {
+    match c {
+        '<' => Some(Direction4::West),
+        '>' => Some(Direction4::East),
+        _ => None,
+    }
 }
 
-impl Direction {
-    pub fn from_char(c: char) -> Self {
-        match c {
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            _ => panic!("Unknown direction: {}", c),
-        }
+// The jets of the pattern, over and over, keeping track of where in the pattern they are.
+struct Jets<'a> {
+    pattern: &'a [Direction4],
+    index: usize,
+}
+
+impl<'a> Jets<'a> {
+    fn new(pattern: &'a [Direction4]) -> Self {
+        Jets { pattern, index: 0 }
     }
 }
 
+impl Iterator for Jets<'_> {
+    type Item = Direction4;
+
+    fn next(&mut self) -> Option<Direction4> {
+        let jet = self.pattern[self.index];
+        self.index = (self.index + 1) % self.pattern.len();
+        Some(jet)
+    }
+}
+
+// Row 0 is the bottom of the chamber (or of what's left of it after compressing).
 struct Chamber {
-    rows: Vec<Vec<char>>,
-    tallest_row: i128,
+    rows: Grid<char>,
+    tallest_row: i64,
     row_offset: usize,
 }
 
 impl Chamber {
     pub fn new() -> Self {
         Self {
-            rows: vec![],
+            rows: Grid::new(0, 7, '.'),
             tallest_row: -1,
             row_offset: 0,
         }
     }
 
-    pub fn add_rock(&mut self, rock: Rock, jet_pattern: &mut impl Iterator<Item = Direction>) {
+    pub fn add_rock(&mut self, rock: Rock, jet_pattern: &mut impl Iterator<Item = Direction4>) {
         let positions = rock.to_positions();
 
-        let height = positions.iter().map(|(row, _)| row).max().unwrap() + 1;
+        let height = positions.iter().map(|position| position.y).max().unwrap() + 1;
 
         // Add rows if necessary.
         for _ in 0..height + 3 {
@@ -88,50 +159,35 @@ impl Chamber {
 
         let mut positions = positions
             .iter()
-            .map(|(row, column)| (*row as i128 + self.tallest_row + 4, column + 2))
+            .map(|position| *position + Point2::new(2, self.tallest_row + 4))
             .collect::<Vec<_>>();
 
         loop {
             let dir = jet_pattern.next().unwrap();
 
             // Pushing.
-            let mut new_positions = match dir {
-                Direction::Left => {
-                    let new_positions =
-                        positions.iter().map(|(row, col)| (*row, col - 1)).collect();
-
-                    if self.can_blow(&new_positions) {
-                        new_positions
-                    } else {
-                        // Don't blow.
-                        positions.clone()
-                    }
-                }
-                Direction::Right => {
-                    let new_positions =
-                        positions.iter().map(|(row, col)| (*row, col + 1)).collect();
-
-                    if self.can_blow(&new_positions) {
-                        new_positions
-                    } else {
-                        positions.clone()
-                    }
-                }
+            let pushed_positions: Vec<Position> = positions
+                .iter()
+                .map(|position| *position + dir.step())
+                .collect();
+
+            let mut new_positions = if self.can_blow(&pushed_positions) {
+                pushed_positions
+            } else {
+                // Don't blow.
+                positions.clone()
             };
 
+            visualize::emit(|| self.frame(&new_positions));
+
             // Falling.
             // If the rock would overlap on the bottom by falling, it sets instead.
             if self.should_set(&new_positions) {
-                for (row, column) in new_positions.iter() {
-                    self.rows[*row as usize][*column as usize] = '#';
+                for position in new_positions.iter() {
+                    self.rows[*position] = '#';
                 }
 
-                self.rows = self
-                    .rows
-                    .iter()
-                    .filter(|row| row.iter().any(|c| *c == '#'))
-                    .cloned()
-                    .collect();
+                self.rows.retain_rows(|row| row.contains(&'#'));
 
                 self.compress();
                 self.update_tallest_row();
@@ -139,14 +195,14 @@ impl Chamber {
                 return;
             } else {
                 // Clean the current positions.
-                for (row, column) in new_positions.iter() {
-                    self.rows[*row as usize][*column as usize] = '.';
+                for position in new_positions.iter() {
+                    self.rows[*position] = '.';
                 }
 
                 // Fall by one row.
                 new_positions = new_positions
                     .iter()
-                    .map(|(row, col)| (row - 1, *col))
+                    .map(|position| *position + FALL)
                     .collect::<Vec<Position>>();
             }
 
@@ -155,20 +211,16 @@ impl Chamber {
     }
 
     fn update_tallest_row(&mut self) {
-        self.tallest_row = self
-            .rows
-            .iter()
-            .enumerate()
-            .filter(|(_, row)| row.iter().any(|c| *c == '#'))
-            .map(|(index, _)| index)
-            .max()
-            .map(|row| row as i128)
+        self.tallest_row = (0..self.height())
+            .rev()
+            .find(|row| self.rows.row_slice(*row).contains(&'#'))
+            .map(|row| row as i64)
             .unwrap_or(-1);
     }
 
-    fn can_blow(&self, positions: &Vec<Position>) -> bool {
-        for (row, column) in positions.iter() {
-            if *column < 0 || *column > 6 || self.rows[*row as usize][*column as usize] == '#' {
+    fn can_blow(&self, positions: &[Position]) -> bool {
+        for position in positions.iter() {
+            if position.x < 0 || position.x > 6 || self.rows[*position] == '#' {
                 return false;
             }
         }
@@ -176,11 +228,11 @@ impl Chamber {
         true
     }
 
-    fn should_set(&self, positions: &Vec<Position>) -> bool {
-        for (row, column) in positions.iter() {
-            let row = row - 1;
+    fn should_set(&self, positions: &[Position]) -> bool {
+        for position in positions.iter() {
+            let below = *position + FALL;
 
-            if row < 0 || self.rows[row as usize][*column as usize] == '#' {
+            if below.y < 0 || self.rows[below] == '#' {
                 return true;
             }
         }
@@ -188,31 +240,107 @@ impl Chamber {
         false
     }
 
+    // The empty cells that the next rocks can reach from above the tower, moving sideways or
+    // down, as their columns and depths below the top, sorted. Unlike the height of each column,
+    // this includes the gaps under overhangs, so towers with the same surface grow the same way.
+    // Only the top rows count, as a column that's never covered would make the surface deeper
+    // with every rock, and the state would never repeat.
+    fn surface(&self) -> Vec<(i64, i64)> {
+        let top = self.tallest_row + 1;
+        let is_empty = |cell: Position| {
+            (0..7).contains(&cell.x)
+                && (0.max(top - SURFACE_DEPTH)..=top).contains(&cell.y)
+                && (cell.y == top || self.rows[cell] == '.')
+        };
+
+        let mut reached = (0..7)
+            .map(|column| Point2::new(column, top))
+            .collect::<HashSet<Position>>();
+        let mut cells = reached.iter().copied().collect::<Vec<Position>>();
+
+        while let Some(cell) = cells.pop() {
+            for next in [Direction4::West.step(), Direction4::East.step(), FALL] {
+                let next = cell + next;
+
+                if is_empty(next) && reached.insert(next) {
+                    cells.push(next);
+                }
+            }
+        }
+
+        let mut surface = reached
+            .into_iter()
+            .map(|cell| (cell.x, top - cell.y))
+            .collect::<Vec<(i64, i64)>>();
+        surface.sort();
+        surface
+    }
+
     fn add_row(&mut self) {
-        self.rows.push(vec!['.'; 7]);
+        self.rows.push_row(vec!['.'; 7]);
+    }
+
+    fn height(&self) -> usize {
+        self.rows.size().0
     }
 
     fn compress(&mut self) {
-        let cutoff = self
-            .rows
-            .iter()
-            .position(|row| row.iter().all(|c| *c == '#'));
+        let cutoff =
+            (0..self.height()).find(|row| self.rows.row_slice(*row).iter().all(|c| *c == '#'));
 
         if let Some(cutoff_row) = cutoff {
             self.row_offset += cutoff_row;
-            self.rows.drain(0..cutoff_row);
+            self.rows.remove_rows(0..cutoff_row);
         }
     }
 
-    pub fn tower_height(&self) -> u32 {
-        self.rows.len() as u32 + self.row_offset as u32
+    pub fn tower_height(&self) -> u64 {
+        (self.height() + self.row_offset) as u64
+    }
+
+    // The chamber with a falling rock, upside down since rows grow downwards in frames. The rows
+    // that were compressed away are left out.
+    fn frame(&self, rock: &[Position]) -> Frame {
+        let caption = format!(
+            "Tower height: {}",
+            self.tallest_row + 1 + self.row_offset as i64
+        );
+        let top = rock.iter().map(|position| position.y).max().unwrap();
+        let to_frame =
+            |position: Position| Point2::new(position.x, -(position.y + self.row_offset as i64));
+
+        let mut frame = Frame::new(caption, PALETTE).with_focus(to_frame(Point2::new(3, top)));
+
+        for (position, cell) in self.rows.iter() {
+            if *cell == '#' {
+                frame.set(to_frame(position), '#');
+            }
+        }
+
+        for position in rock {
+            frame.set(to_frame(*position), '@');
+        }
+
+        for row in 0..=top {
+            frame.set(to_frame(Point2::new(-1, row)), '|');
+            frame.set(to_frame(Point2::new(7, row)), '|');
+        }
+
+        if self.row_offset == 0 {
+            for x in -1..=7 {
+                let cell = if x == -1 || x == 7 { '+' } else { '-' };
+                frame.set(to_frame(Point2::new(x, -1)), cell);
+            }
+        }
+
+        frame
     }
 }
 
 impl std::fmt::Display for Chamber {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         // Print two dummy rows on top of what's already there.
-        for row in self.rows.iter().rev() {
+        for row in (0..self.height()).rev().map(|row| self.rows.row_slice(row)) {
             write!(f, "|")?;
 
             for c in row.iter() {
@@ -229,23 +357,144 @@ impl std::fmt::Display for Chamber {
     }
 }
 
-pub fn run(input: &str) {
-    let rocks = [MinusSign, PlusSign, ReverseL, VerticalLine, Square]
-        .iter()
-        .cloned()
-        .cycle();
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Vec<Direction4>;
+
+    const PARAMS: &'static [Param] = &[ROCKS, TOTAL_ROCKS];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let jet_pattern = input.trim();
+
+        if jet_pattern.is_empty() {
+            return Err(Error::new("expected at least one jet"));
+        }
+
+        jet_pattern
+            .char_indices()
+            .map(|(index, c)| {
+                jet(c).ok_or_else(|| {
+                    Error::at(
+                        input,
+                        &jet_pattern[index..],
+                        format!("expected `<` or `>`, got `{}`", c),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn part1(jet_pattern: &Self::Input) -> Answer {
+        let rocks = ROCK_ORDER.iter().cloned().cycle();
+        let mut jets = Jets::new(jet_pattern);
+
+        let mut chamber = Chamber::new();
+
+        for rock in rocks.take(ROCKS.value()) {
+            chamber.add_rock(rock, &mut jets);
+        }
+
+        diagnostic!("Finished with {} rows in memory", chamber.height());
+
+        chamber.tower_height().into()
+    }
+
+    // Simulating a trillion rocks one by one is not feasible, but the next rock, the next jet and
+    // the top of the tower eventually repeat, and so does the growth of the tower from there.
+    fn part2(jet_pattern: &Self::Input) -> Answer {
+        let mut jets = Jets::new(jet_pattern);
+        let mut chamber = Chamber::new();
 
-    let mut jet_pattern = input.trim().chars().map(Direction::from_char).cycle();
+        let initial = ((0, 0, chamber.surface()), 0);
 
-    let mut chamber = Chamber::new();
+        let cycle = Cycle::find(initial, MAX_CYCLE_ROCKS, |rocks| {
+            chamber.add_rock(ROCK_ORDER[(rocks - 1) % ROCK_ORDER.len()], &mut jets);
 
-    for rock in rocks.take(2022) {
-        chamber.add_rock(rock, &mut jet_pattern);
+            let state = (rocks % ROCK_ORDER.len(), jets.index, chamber.surface());
+            (state, chamber.tower_height() as i64)
+        });
+
+        let Some(cycle) = cycle else {
+            diagnostic!("The tower doesn't repeat itself after {MAX_CYCLE_ROCKS} rocks");
+            return Answer::Unsolved;
+        };
+
+        diagnostic!(
+            "The tower grows by {} every {} rocks after {} rocks",
+            cycle.delta,
+            cycle.length,
+            cycle.start
+        );
+
+        let Some(height) = cycle.metric(TOTAL_ROCKS.value()) else {
+            diagnostic!("The height of the tower doesn't fit in 64 bits");
+            return Answer::Unsolved;
+        };
+
+        height.into()
+    }
+
+    // A jet pattern of 100 jets per `size`.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let jets = (0..100 * size.max(1))
+            .map(|_| *rng.choose(&['<', '>']))
+            .collect::<String>();
+
+        Some(jets + "\n")
     }
 
-    println!(
-        "The tower is {} units tall (finished with {} rows in memory)",
-        chamber.tower_height(),
-        chamber.rows.len()
-    );
+    // Drops every rock, one at a time, onto the set of all the parts of the rocks that fell
+    // before, without forgetting rows or looking for cycles. Part 2 is only checked when there
+    // aren't many rocks to drop.
+    fn reference(jet_pattern: &Self::Input) -> Option<(Answer, Answer)> {
+        const MAX_ROCKS: usize = 100_000;
+
+        let tower_height = |rocks: usize| {
+            let mut fallen = HashSet::new();
+            let mut height = 0;
+            let mut jets = jet_pattern.iter().cycle();
+
+            for rock in ROCK_ORDER.iter().cycle().take(rocks) {
+                let parts = rock.to_positions();
+                let fits = |position: Position| {
+                    parts.iter().all(|part| {
+                        let part = position + *part;
+                        (0..7).contains(&part.x) && part.y >= 0 && !fallen.contains(&part)
+                    })
+                };
+
+                let mut position = Point2::new(2, height + 3);
+
+                loop {
+                    let pushed = position + jets.next().unwrap().step();
+
+                    if fits(pushed) {
+                        position = pushed;
+                    }
+
+                    if !fits(position + FALL) {
+                        break;
+                    }
+
+                    position += FALL;
+                }
+
+                for part in &parts {
+                    fallen.insert(position + *part);
+                    height = height.max(position.y + part.y + 1);
+                }
+            }
+
+            Answer::from(height)
+        };
+
+        let total_rocks = TOTAL_ROCKS.value::<usize>();
+        let part2 = match total_rocks <= MAX_ROCKS {
+            true => tower_height(total_rocks),
+            false => Answer::Unsolved,
+        };
+
+        Some((tower_height(ROCKS.value()), part2))
+    }
 }
@@ -1,97 +1,153 @@
 use std::collections::HashSet;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Cube(i32, i32, i32);
-
-impl Cube {
-    pub fn from_string(string: &str) -> Self {
-        let parts = string
-            .split(",")
-            .map(|s| s.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-
-        Self(parts[0], parts[1], parts[2])
-    }
-
-    fn neighbors(&self) -> [Self; 6] {
-        [
-            Self(self.0 + 1, self.1, self.2),
-            Self(self.0 - 1, self.1, self.2),
-            Self(self.0, self.1 + 1, self.2),
-            Self(self.0, self.1 - 1, self.2),
-            Self(self.0, self.1, self.2 + 1),
-            Self(self.0, self.1, self.2 - 1),
-        ]
+use crate::{
+    error::{parse_lines, parse_number, Error},
+    geometry::Point3,
+    random::Rng,
+    search,
+    solution::{Answer, Solution},
+};
+
+pub type Cube = Point3<i32>;
+
+pub fn parse_cube(string: &str) -> Result<Cube, Error> {
+    let parts = string
+        .split(",")
+        .map(|s| parse_number::<i32>(string, s))
+        .collect::<Result<Vec<i32>, Error>>()?;
+
+    match parts[..] {
+        [x, y, z] => Ok(Point3::new(x, y, z)),
+        _ => Err(Error::at(string, string, "expected `<x>,<y>,<z>`")),
     }
+}
 
-    fn exposed_sides(&self, other_cubes: &HashSet<Self>) -> u16 {
-        let adjacent_count = HashSet::from(self.neighbors())
-            .intersection(other_cubes)
-            .count();
+fn exposed_sides(cube: &Cube, other_cubes: &HashSet<Cube>) -> u16 {
+    let adjacent_count = HashSet::from(cube.neighbors6())
+        .intersection(other_cubes)
+        .count();
 
-        6 - (adjacent_count as u16)
-    }
+    6 - (adjacent_count as u16)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Day18;
 
-    #[test]
-    fn test_from_string() {
-        let cube = Cube::from_string("1,2,3");
-        assert_eq!(cube.0, 1);
-        assert_eq!(cube.1, 2);
-        assert_eq!(cube.2, 3);
+impl Solution for Day18 {
+    type Input = HashSet<Cube>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(HashSet::from_iter(parse_lines(input, parse_cube)?))
     }
 
-    #[test]
-    fn test_exposed_sides() {
-        let base_cube = Cube::from_string("5,5,5");
+    fn part1(cubes: &Self::Input) -> Answer {
+        total_exposed_sides(cubes).into()
+    }
 
-        assert_eq!(base_cube.exposed_sides(&HashSet::new()), 6);
+    // Only the sides that face the outside of the droplet count, so we subtract the sides of the
+    // air pockets trapped inside.
+    fn part2(cubes: &Self::Input) -> Answer {
+        let total_sides = total_exposed_sides(cubes);
+
+        // The air around the droplet, which the bounds of `invert` leave a layer of around it.
+        let inverted = invert(cubes);
+        let outer = search::bfs([Point3::new(-1, -1, -1)], |cube| {
+            cube.neighbors6()
+                .into_iter()
+                .filter(|neighbor| inverted.contains(neighbor))
+        })
+        .reached()
+        .map(|(cube, _)| *cube)
+        .collect::<HashSet<Cube>>();
 
-        assert_eq!(
-            base_cube.exposed_sides(&HashSet::from([Cube::from_string("5,5,4")])),
-            5
-        );
-        assert_eq!(
-            base_cube.exposed_sides(&HashSet::from([
-                Cube::from_string("5,5,4"),
-                Cube::from_string("5,5,6")
-            ])),
-            4
-        );
+        let internal = HashSet::from_iter(inverted.difference(&outer).cloned());
+        (total_sides - total_exposed_sides(&internal)).into()
     }
-}
 
-pub fn run(input: &str) {
-    let cubes: HashSet<Cube> = HashSet::from_iter(input.trim().lines().map(Cube::from_string));
+    // A droplet about `size` cubes across: a ball with a tenth of its cubes missing, which
+    // leaves air pockets inside.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let size = size.max(1) as i32;
+        let center = Point3::new(size / 2, size / 2, size / 2);
+        let radius = size * size / 4;
+        let mut input = String::new();
+
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    let offset = Point3::new(x, y, z) - center;
+                    let inside = offset.x.pow(2) + offset.y.pow(2) + offset.z.pow(2) <= radius;
+
+                    if inside && rng.chance(0.9) {
+                        input += &format!("{},{},{}\n", x, y, z);
+                    }
+                }
+            }
+        }
 
-    let total_sides = total_exposed_sides(&cubes);
+        if input.is_empty() {
+            input += &format!("{},{},{}\n", center.x, center.y, center.z);
+        }
 
-    println!("Total exposed sides: {}", total_sides);
+        Some(input)
+    }
 
-    let inverted = invert(&cubes);
-    let mut outer = HashSet::new();
-    collect(Cube(-1, -1, -1), &inverted.clone(), &mut outer);
+    // Counts the sides next to air one by one, and for part 2, searches for a way out of the
+    // droplet's bounding box from the air next to each of them.
+    fn reference(cubes: &Self::Input) -> Option<(Answer, Answer)> {
+        let min = cubes
+            .iter()
+            .fold(Cube::new(i32::MAX, i32::MAX, i32::MAX), |min, cube| {
+                Cube::new(min.x.min(cube.x), min.y.min(cube.y), min.z.min(cube.z))
+            });
+        let max = cubes
+            .iter()
+            .fold(Cube::new(i32::MIN, i32::MIN, i32::MIN), |max, cube| {
+                Cube::new(max.x.max(cube.x), max.y.max(cube.y), max.z.max(cube.z))
+            });
+        let outside = |cube: &Cube| {
+            cube.x < min.x
+                || cube.y < min.y
+                || cube.z < min.z
+                || cube.x > max.x
+                || cube.y > max.y
+                || cube.z > max.z
+        };
+
+        let air_sides = cubes
+            .iter()
+            .flat_map(|cube| cube.neighbors6())
+            .filter(|neighbor| !cubes.contains(neighbor))
+            .collect::<Vec<Cube>>();
+
+        let exterior_sides = air_sides
+            .iter()
+            .filter(|air| {
+                search::bfs([**air], |cube| {
+                    // Nothing's left to search once out of the box.
+                    let escaped = outside(cube);
+
+                    cube.neighbors6()
+                        .into_iter()
+                        .filter(move |neighbor| !escaped && !cubes.contains(neighbor))
+                })
+                .reached()
+                .any(|(cube, _)| outside(cube))
+            })
+            .count();
 
-    let internal = HashSet::from_iter(inverted.difference(&outer).cloned());
-    println!(
-        "Internal-facing exposed sides: {}",
-        total_sides - total_exposed_sides(&internal)
-    );
+        Some((air_sides.len().into(), exterior_sides.into()))
+    }
 }
 
 fn invert(cubes: &HashSet<Cube>) -> HashSet<Cube> {
-    let (min_x, max_x, min_y, max_y, min_z, max_z) = bounds(&cubes);
+    let (min_x, max_x, min_y, max_y, min_z, max_z) = bounds(cubes);
     let mut inverted = HashSet::new();
 
     for x in min_x - 1..=max_x + 1 {
         for y in min_y - 1..=max_y + 1 {
             for z in min_z - 1..=max_z + 1 {
-                if !cubes.contains(&Cube(x, y, z)) {
-                    inverted.insert(Cube(x, y, z));
+                if !cubes.contains(&Point3::new(x, y, z)) {
+                    inverted.insert(Point3::new(x, y, z));
                 }
             }
         }
@@ -100,15 +156,6 @@ fn invert(cubes: &HashSet<Cube>) -> HashSet<Cube> {
     inverted
 }
 
-fn collect(pos: Cube, cubes: &HashSet<Cube>, visited: &mut HashSet<Cube>) {
-    for neighbor in pos.neighbors() {
-        if cubes.contains(&neighbor) && !visited.contains(&neighbor) {
-            visited.insert(neighbor.clone());
-            collect(neighbor, cubes, visited);
-        }
-    }
-}
-
 fn bounds(cubes: &HashSet<Cube>) -> (i32, i32, i32, i32, i32, i32) {
     let mut min_x = 0;
     let mut max_x = 0;
@@ -118,12 +165,12 @@ fn bounds(cubes: &HashSet<Cube>) -> (i32, i32, i32, i32, i32, i32) {
     let mut max_z = 0;
 
     for cube in cubes.iter() {
-        min_x = cube.0.min(min_x);
-        max_x = cube.0.max(max_x);
-        min_y = cube.1.min(min_y);
-        max_y = cube.1.max(max_y);
-        min_z = cube.2.min(min_z);
-        max_z = cube.2.max(max_z);
+        min_x = cube.x.min(min_x);
+        max_x = cube.x.max(max_x);
+        min_y = cube.y.min(min_y);
+        max_y = cube.y.max(max_y);
+        min_z = cube.z.min(min_z);
+        max_z = cube.z.max(max_z);
     }
 
     (min_x, max_x, min_y, max_y, min_z, max_z)
@@ -133,10 +180,42 @@ fn total_exposed_sides(cubes: &HashSet<Cube>) -> u32 {
     cubes
         .iter()
         .map(|cube| {
-            let set_with_cube = HashSet::from([cube.clone()]);
+            let set_with_cube = HashSet::from([*cube]);
             let difference: HashSet<Cube> =
                 HashSet::from_iter(cubes.difference(&set_with_cube).cloned());
-            cube.exposed_sides(&difference) as u32
+            exposed_sides(cube, &difference) as u32
         })
         .sum::<u32>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_string() {
+        let cube = parse_cube("1,2,3").unwrap();
+        assert_eq!(cube.x, 1);
+        assert_eq!(cube.y, 2);
+        assert_eq!(cube.z, 3);
+    }
+
+    #[test]
+    fn test_exposed_sides() {
+        let base_cube = parse_cube("5,5,5").unwrap();
+
+        assert_eq!(exposed_sides(&base_cube, &HashSet::new()), 6);
+
+        assert_eq!(
+            exposed_sides(&base_cube, &HashSet::from([parse_cube("5,5,4").unwrap()])),
+            5
+        );
+        assert_eq!(
+            exposed_sides(
+                &base_cube,
+                &HashSet::from([parse_cube("5,5,4").unwrap(), parse_cube("5,5,6").unwrap()])
+            ),
+            4
+        );
+    }
+}
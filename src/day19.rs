@@ -1,8 +1,47 @@
-use std::{fmt::Debug, str::FromStr, time::Instant};
-
-use regex::Regex;
-
-const TOTAL_MINUTES: u16 = 32;
+use std::{collections::HashMap, fmt::Debug, str::FromStr, time::Instant};
+
+use crate::{
+    error::{parse_lines, Error},
+    parse::Pattern,
+    random::Rng,
+    solution::{diagnostic, Answer, Param, Progress, Solution},
+};
+
+const MINUTES: Param = Param {
+    name: "minutes",
+    default: 24,
+    min: 0,
+    // The search grows exponentially with the minutes.
+    max: 32,
+    description: "Minutes to open geodes for each blueprint (part 1)",
+};
+
+const TOTAL_MINUTES: Param = Param {
+    name: "total_minutes",
+    default: 32,
+    min: 0,
+    max: 32,
+    description: "Minutes to open geodes for the first blueprints (part 2)",
+};
+
+const BLUEPRINTS: Param = Param {
+    name: "blueprints",
+    default: 3,
+    min: 0,
+    max: u16::MAX as i64,
+    description: "Blueprints that are left after the elephants ate the rest (part 2)",
+};
+
+// Some inputs leave out the final period.
+static BLUEPRINT: Pattern = Pattern::new(
+    "
+    Blueprint {_}:
+      Each ore robot costs {ore_robot_ore} ore.
+      Each clay robot costs {clay_robot_ore} ore.
+      Each obsidian robot costs {obsidian_robot_ore} ore and {obsidian_robot_clay} clay.
+      Each geode robot costs {geode_robot_ore} ore and {geode_robot_obsidian} obsidian{_}
+    ",
+);
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct Ore(u16);
@@ -12,7 +51,7 @@ struct Clay(u16);
 struct Obsidian(u16);
 
 #[derive(Debug)]
-struct Blueprint {
+pub struct Blueprint {
     ore_robot_cost: Ore,
     clay_robot_cost: Ore,
     obsidian_robot_cost: (Ore, Clay),
@@ -21,7 +60,7 @@ struct Blueprint {
 
 impl Blueprint {
     fn max_ore_cost(&self) -> Ore {
-        vec![
+        *[
             self.ore_robot_cost,
             self.clay_robot_cost,
             self.obsidian_robot_cost.0,
@@ -30,66 +69,34 @@ impl Blueprint {
         .iter()
         .max()
         .unwrap()
-        .clone()
     }
 
     fn max_clay_cost(&self) -> Clay {
-        self.obsidian_robot_cost.1.clone()
+        self.obsidian_robot_cost.1
     }
 
     fn max_obsidian_cost(&self) -> Obsidian {
-        self.geode_robot_cost.1.clone()
+        self.geode_robot_cost.1
     }
 }
 
 impl FromStr for Blueprint {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let ore_robot_cost = Ore(Regex::new(r"Each ore robot costs (\d+) ore")
-            .unwrap()
-            .captures_iter(string)
-            .next()
-            .unwrap()[1]
-            .parse::<u16>()
-            .unwrap());
-
-        let clay_robot_cost = Ore(Regex::new(r"Each clay robot costs (\d+) ore")
-            .unwrap()
-            .captures_iter(string)
-            .next()
-            .unwrap()[1]
-            .parse::<u16>()
-            .unwrap());
-
-        let obsidian_robot_costs =
-            Regex::new(r"Each obsidian robot costs (\d+) ore and (\d+) clay")
-                .unwrap()
-                .captures_iter(string)
-                .next()
-                .unwrap();
-
-        let obsidian_robot_cost = (
-            Ore(obsidian_robot_costs[1].parse::<u16>().unwrap()),
-            Clay(obsidian_robot_costs[2].parse::<u16>().unwrap()),
-        );
-
-        let geode_robot_costs = Regex::new(r"Each geode robot costs (\d+) ore and (\d+) obsidian")
-            .unwrap()
-            .captures_iter(string)
-            .next()
-            .unwrap();
-
-        let geode_robot_cost = (
-            Ore(geode_robot_costs[1].parse::<u16>().unwrap()),
-            Obsidian(geode_robot_costs[2].parse::<u16>().unwrap()),
-        );
+        let blueprint = BLUEPRINT.parse(string)?;
 
         Ok(Self {
-            ore_robot_cost,
-            clay_robot_cost,
-            obsidian_robot_cost,
-            geode_robot_cost,
+            ore_robot_cost: Ore(blueprint.number("ore_robot_ore")?),
+            clay_robot_cost: Ore(blueprint.number("clay_robot_ore")?),
+            obsidian_robot_cost: (
+                Ore(blueprint.number("obsidian_robot_ore")?),
+                Clay(blueprint.number("obsidian_robot_clay")?),
+            ),
+            geode_robot_cost: (
+                Ore(blueprint.number("geode_robot_ore")?),
+                Obsidian(blueprint.number("geode_robot_obsidian")?),
+            ),
         })
     }
 }
@@ -128,7 +135,7 @@ impl State {
     }
 
     fn possible_next_states(&self, blueprint: &Blueprint, time_left: u16) -> Vec<State> {
-        let possible_next_states_with_new_robots = vec![
+        let possible_next_states_with_new_robots = [
             self.build_ore_robot(blueprint, time_left),
             self.build_clay_robot(blueprint, time_left),
             self.build_obsidian_robot(blueprint, time_left),
@@ -137,7 +144,7 @@ impl State {
 
         let mut next_states = possible_next_states_with_new_robots
             .iter()
-            .filter_map(|state| state.clone())
+            .filter_map(|state| *state)
             .collect::<Vec<Self>>();
 
         // Only have an "idle" state (where we only produce and don't build any robots)
@@ -146,14 +153,14 @@ impl State {
             || self.clay < self.max_clay_needed
             || self.obsidian < self.max_obsidian_needed
         {
-            next_states.push(self.clone());
+            next_states.push(*self);
         }
 
-        for mut state in &mut next_states {
+        for state in &mut next_states {
             state.ore = Ore(state.ore.0 + self.ore_robots);
             state.clay = Clay(state.clay.0 + self.clay_robots);
             state.obsidian = Obsidian(state.obsidian.0 + self.obsidian_robots);
-            state.cracked_geodes = state.cracked_geodes + self.geode_robots;
+            state.cracked_geodes += self.geode_robots;
         }
 
         next_states
@@ -166,7 +173,7 @@ impl State {
             Some(Self {
                 ore_robots: self.ore_robots + 1,
                 ore: Ore(self.ore.0 - blueprint.ore_robot_cost.0),
-                ..self.clone()
+                ..*self
             })
         } else {
             None
@@ -180,7 +187,7 @@ impl State {
             Some(Self {
                 clay_robots: self.clay_robots + 1,
                 ore: Ore(self.ore.0 - blueprint.clay_robot_cost.0),
-                ..self.clone()
+                ..*self
             })
         } else {
             None
@@ -199,7 +206,7 @@ impl State {
                 obsidian_robots: self.obsidian_robots + 1,
                 ore: Ore(self.ore.0 - blueprint.obsidian_robot_cost.0 .0),
                 clay: Clay(self.clay.0 - blueprint.obsidian_robot_cost.1 .0),
-                ..self.clone()
+                ..*self
             })
         } else {
             None
@@ -214,7 +221,7 @@ impl State {
                 geode_robots: self.geode_robots + 1,
                 ore: Ore(self.ore.0 - ore_cost.0),
                 obsidian: Obsidian(self.obsidian.0 - obsidian_cost.0),
-                ..self.clone()
+                ..*self
             })
         } else {
             None
@@ -227,41 +234,190 @@ impl State {
     }
 }
 
-pub fn run(input: &str) {
-    let blueprints = input
-        .lines()
-        .take(3)
-        .map(|line| line.parse::<Blueprint>().unwrap())
-        .collect::<Vec<Blueprint>>();
+pub struct Day19;
 
-    let mut multiplied_geodes = 0;
+impl Solution for Day19 {
+    type Input = Vec<Blueprint>;
 
-    for (index, blueprint) in blueprints.iter().enumerate() {
-        let blueprint_index = index + 1;
+    const PARAMS: &'static [Param] = &[MINUTES, TOTAL_MINUTES, BLUEPRINTS];
 
-        let start_time = Instant::now();
-        let (max_open_geodes, explored_simulations) = simulate_all(blueprint);
-        let elapsed = start_time.elapsed();
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, Blueprint::from_str)
+    }
 
-        println!("\n== Blueprint {blueprint_index} ==");
-        println!("Explored {} unique simulations, simulating {} minutes (took {:.2?}). Max open geodes: {}",
-            explored_simulations, TOTAL_MINUTES, elapsed, max_open_geodes
-        );
+    // The sum of the quality levels (ID times max open geodes) of all the blueprints.
+    fn part1(blueprints: &Self::Input) -> Answer {
+        let mut quality_levels = 0;
+        let mut progress = Progress::new("Simulating blueprints", blueprints.len());
+
+        for (index, blueprint) in blueprints.iter().enumerate() {
+            progress.set(index);
+            let blueprint_index = index + 1;
+            let max_open_geodes = simulate_blueprint(blueprint_index, blueprint, MINUTES.value());
+
+            quality_levels += blueprint_index as u32 * max_open_geodes;
+        }
+
+        quality_levels.into()
+    }
+
+    // The product of the max open geodes of the first few blueprints, with more time.
+    fn part2(blueprints: &Self::Input) -> Answer {
+        let mut multiplied_geodes = 1;
+        let blueprints = &blueprints[..blueprints.len().min(BLUEPRINTS.value())];
+        let mut progress = Progress::new("Simulating blueprints", blueprints.len());
+
+        for (index, blueprint) in blueprints.iter().enumerate() {
+            progress.set(index);
+            multiplied_geodes *= simulate_blueprint(index + 1, blueprint, TOTAL_MINUTES.value());
+        }
+
+        multiplied_geodes.into()
+    }
+
+    // `size` blueprints, with costs in the ranges of the puzzle's.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let blueprints = (1..=size.max(1))
+            .map(|id| {
+                format!(
+                    "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.\n",
+                    id,
+                    rng.range(2..5),
+                    rng.range(2..5),
+                    rng.range(2..5),
+                    rng.range(4..21),
+                    rng.range(2..5),
+                    rng.range(4..21)
+                )
+            })
+            .collect();
+
+        Some(blueprints)
+    }
+
+    // Tries every choice of every minute (building any robot that's affordable, or none), keeping
+    // the most geodes from each state. Only for a few minutes, as the states grow exponentially
+    // with them.
+    fn reference(blueprints: &Self::Input) -> Option<(Answer, Answer)> {
+        const MAX_MINUTES: u16 = 20;
+
+        let most_geodes = |blueprint: &Blueprint, minutes: u16| {
+            most_geodes(
+                blueprint,
+                [1, 0, 0, 0],
+                [0; 3],
+                minutes,
+                &mut HashMap::new(),
+            ) as u32
+        };
+
+        let minutes = MINUTES.value();
+        let part1 = match minutes <= MAX_MINUTES {
+            true => (blueprints.iter().enumerate())
+                .map(|(index, blueprint)| (index as u32 + 1) * most_geodes(blueprint, minutes))
+                .sum::<u32>()
+                .into(),
+            false => Answer::Unsolved,
+        };
+
+        let total_minutes = TOTAL_MINUTES.value();
+        let part2 = match total_minutes <= MAX_MINUTES {
+            true => (blueprints.iter().take(BLUEPRINTS.value()))
+                .map(|blueprint| most_geodes(blueprint, total_minutes))
+                .product::<u32>()
+                .into(),
+            false => Answer::Unsolved,
+        };
+
+        Some((part1, part2))
+    }
+}
+
+// The most geodes that can be cracked in the minutes left, for the reference solver, with the
+// robots (collecting ore, clay, obsidian and cracking geodes) and the resources (ore, clay and
+// obsidian) at hand.
+fn most_geodes(
+    blueprint: &Blueprint,
+    robots: [u16; 4],
+    resources: [u16; 3],
+    minutes: u16,
+    memo: &mut HashMap<([u16; 4], [u16; 3], u16), u16>,
+) -> u16 {
+    if minutes == 0 {
+        return 0;
+    }
+
+    if let Some(&geodes) = memo.get(&(robots, resources, minutes)) {
+        return geodes;
+    }
+
+    let costs = [
+        [blueprint.ore_robot_cost.0, 0, 0],
+        [blueprint.clay_robot_cost.0, 0, 0],
+        [
+            blueprint.obsidian_robot_cost.0 .0,
+            blueprint.obsidian_robot_cost.1 .0,
+            0,
+        ],
+        [
+            blueprint.geode_robot_cost.0 .0,
+            0,
+            blueprint.geode_robot_cost.1 .0,
+        ],
+    ];
+
+    // Building no robot, or one of each kind.
+    let mut best = 0;
+
+    for robot in [None, Some(0), Some(1), Some(2), Some(3)] {
+        let cost = robot.map_or([0; 3], |robot| costs[robot]);
+
+        if (0..3).any(|resource| resources[resource] < cost[resource]) {
+            continue;
+        }
+
+        let next_resources =
+            [0, 1, 2].map(|resource| resources[resource] - cost[resource] + robots[resource]);
+        let mut next_robots = robots;
+
+        if let Some(robot) = robot {
+            next_robots[robot] += 1;
+        }
 
-        multiplied_geodes = multiplied_geodes * max_open_geodes;
+        let geodes =
+            robots[3] + most_geodes(blueprint, next_robots, next_resources, minutes - 1, memo);
+        best = best.max(geodes);
     }
 
-    println!("\n\nMultiplied geodes: {}", multiplied_geodes);
+    memo.insert((robots, resources, minutes), best);
+    best
 }
 
-fn simulate_all(blueprint: &Blueprint) -> (u32, u64) {
+fn simulate_blueprint(blueprint_index: usize, blueprint: &Blueprint, minutes: u16) -> u32 {
+    let start_time = Instant::now();
+    let (max_open_geodes, explored_simulations) = simulate_all(blueprint, minutes);
+    let elapsed = start_time.elapsed();
+
+    diagnostic!("\n== Blueprint {blueprint_index} ==");
+    diagnostic!(
+        "Explored {} unique simulations, simulating {} minutes (took {:.2?}). Max open geodes: {}",
+        explored_simulations,
+        minutes,
+        elapsed,
+        max_open_geodes
+    );
+
+    max_open_geodes
+}
+
+fn simulate_all(blueprint: &Blueprint, minutes: u16) -> (u32, u64) {
     let mut explored_simulations = 0;
     let mut max_geodes_seen = 0;
 
     let max_open_geodes = simulate_all_(
         State::from_blueprint(blueprint),
         blueprint,
-        TOTAL_MINUTES,
+        minutes,
         &mut max_geodes_seen,
         &mut explored_simulations,
     );
@@ -282,8 +438,13 @@ fn simulate_all_(
         return current_state.cracked_geodes as u32;
     }
 
-    if current_state.cracked_geodes as u32 > *max_geodes_seen {
-        *max_geodes_seen = current_state.cracked_geodes as u32;
+    // The geode robots we already have keep cracking geodes until the end no matter what we do
+    // next, so this simulation will yield at least this many open geodes.
+    let guaranteed_cracked_geodes =
+        current_state.cracked_geodes as u32 + (current_state.geode_robots * time_left) as u32;
+
+    if guaranteed_cracked_geodes > *max_geodes_seen {
+        *max_geodes_seen = guaranteed_cracked_geodes;
     }
 
     if current_state.utopistic_cracked_geodes(time_left) < *max_geodes_seen {
@@ -297,7 +458,7 @@ fn simulate_all_(
         .iter()
         .map(|next_state| {
             simulate_all_(
-                next_state.clone(),
+                *next_state,
                 blueprint,
                 time_left - 1,
                 max_geodes_seen,
@@ -307,3 +468,25 @@ fn simulate_all_(
         .max()
         .unwrap()
 }
+
+#[cfg(test)]
+mod blueprint_tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.";
+        let blueprint = Blueprint::from_str(input).unwrap();
+
+        assert_eq!(blueprint.ore_robot_cost, Ore(4));
+        assert_eq!(blueprint.clay_robot_cost, Ore(2));
+        assert_eq!(blueprint.obsidian_robot_cost, (Ore(3), Clay(14)));
+        assert_eq!(blueprint.geode_robot_cost, (Ore(2), Obsidian(7)));
+
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore.";
+        assert_eq!(
+            Blueprint::from_str(input).unwrap_err().to_string(),
+            "line 1 col 66: expected `ore. Each obsidian robot costs`"
+        );
+    }
+}
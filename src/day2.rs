@@ -1,5 +1,11 @@
+use crate::{
+    error::{parse_lines, Error},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
 #[derive(Copy, Clone, PartialEq)]
-enum Choice {
+pub enum Choice {
     Rock,
     Paper,
     Scissors,
@@ -12,35 +18,99 @@ enum RoundEnd {
     Draw,
 }
 
-pub fn run(input: &str) {
-    let lines = input.lines();
+pub struct Day2;
+
+impl Solution for Day2 {
+    // The opponent's choice and the second column of the strategy guide, for every round.
+    type Input = Vec<(Choice, char)>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| {
+            let split: Vec<&str> = line.split_whitespace().collect();
+
+            let (opponent_choice, second_column) = match split[..] {
+                [opponent_choice, second_column] => (opponent_choice, second_column),
+                _ => return Err(Error::at(line, line, "expected two columns")),
+            };
+
+            let opponent_choice = match opponent_choice {
+                "A" => Choice::Rock,
+                "B" => Choice::Paper,
+                "C" => Choice::Scissors,
+                _ => {
+                    return Err(Error::at(
+                        line,
+                        opponent_choice,
+                        format!("invalid choice `{}`", opponent_choice),
+                    ))
+                }
+            };
+
+            match second_column {
+                "X" | "Y" | "Z" => Ok((opponent_choice, second_column.chars().next().unwrap())),
+                _ => Err(Error::at(
+                    line,
+                    second_column,
+                    format!("expected X, Y or Z, got `{}`", second_column),
+                )),
+            }
+        })
+    }
+
+    // The second column is what we should play.
+    fn part1(rounds: &Self::Input) -> Answer {
+        let mut total = 0;
 
-    let mut total = 0;
+        for (opponent_choice, column) in rounds {
+            let my_choice = match column {
+                'X' => Choice::Rock,
+                'Y' => Choice::Paper,
+                'Z' => Choice::Scissors,
+                _ => panic!("Invalid choice"),
+            };
 
-    for line in lines {
-        let split: Vec<&str> = line.split_whitespace().collect();
+            total +=
+                calculate_choice_value(my_choice) + calculate_score(*opponent_choice, my_choice);
+        }
 
-        let opponent_choice = match split[0] {
-            "A" => Choice::Rock,
-            "B" => Choice::Paper,
-            "C" => Choice::Scissors,
-            _ => panic!("Invalid choice"),
-        };
+        total.into()
+    }
 
-        let round_end = match split[1] {
-            "X" => RoundEnd::Lose,
-            "Y" => RoundEnd::Draw,
-            "Z" => RoundEnd::Win,
-            _ => panic!("Invalid round end"),
-        };
+    // The second column is how the round needs to end.
+    fn part2(rounds: &Self::Input) -> Answer {
+        let mut total = 0;
 
-        let my_choice = choose_based_on_end(opponent_choice, round_end);
+        for (opponent_choice, column) in rounds {
+            let round_end = match column {
+                'X' => RoundEnd::Lose,
+                'Y' => RoundEnd::Draw,
+                'Z' => RoundEnd::Win,
+                _ => panic!("Invalid round end"),
+            };
 
-        total =
-            total + calculate_choice_value(my_choice) + calculate_score(opponent_choice, my_choice);
+            let my_choice = choose_based_on_end(*opponent_choice, round_end);
+
+            total +=
+                calculate_choice_value(my_choice) + calculate_score(*opponent_choice, my_choice);
+        }
+
+        total.into()
     }
 
-    println!("{:?}", total);
+    // `size` rounds.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let rounds = (0..size.max(1))
+            .map(|_| {
+                format!(
+                    "{} {}\n",
+                    rng.choose(&["A", "B", "C"]),
+                    rng.choose(&["X", "Y", "Z"])
+                )
+            })
+            .collect();
+
+        Some(rounds)
+    }
 }
 
 fn choose_based_on_end(opponent_choice: Choice, round_end: RoundEnd) -> Choice {
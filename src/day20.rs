@@ -2,7 +2,19 @@ use std::fmt::Display;
 
 use itertools::Itertools;
 
-const DECRYPTION_KEY: i64 = 811589153;
+use crate::{
+    error::{parse_lines, parse_number, Error},
+    random::Rng,
+    solution::{Answer, Param, Progress, Solution},
+};
+
+const DECRYPTION_KEY: Param = Param {
+    name: "decryption_key",
+    default: 811589153,
+    min: i32::MIN as i64,
+    max: i32::MAX as i64,
+    description: "Number to multiply each number by before mixing (part 2)",
+};
 
 #[derive(Clone)]
 struct CircularList(Vec<(i64, usize)>);
@@ -24,15 +36,16 @@ impl CircularList {
         let len = self.0.len() as i64;
         let (number, target_id) = element;
 
+        // A single number has nowhere to go.
+        if len < 2 {
+            return;
+        }
+
         // First, find the number's current position.
         let current_index = self.0.iter().position(|(_, id)| *id == target_id).unwrap();
 
         // Compute the new index.
-        let mut new_index = (current_index as i64 + number) % (len - 1);
-
-        if new_index < 0 {
-            new_index = (len - 1) + new_index;
-        }
+        let new_index = (current_index as i64 + number.rem_euclid(len - 1)) % (len - 1);
 
         // Remove the element from its current position.
         let element = self.0.remove(current_index);
@@ -46,19 +59,113 @@ impl CircularList {
     }
 }
 
-pub fn run(input: &str) {
-    let original_numbers = input
-        .lines()
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<i64>;
+
+    const PARAMS: &'static [Param] = &[DECRYPTION_KEY];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let numbers = parse_lines(input, |number| parse_number(number, number))?;
+
+        // The grove coordinates are counted from the 0.
+        if !numbers.contains(&0) {
+            return Err(Error::new("expected a 0 among the numbers"));
+        }
+
+        Ok(numbers)
+    }
+
+    fn part1(numbers: &Self::Input) -> Answer {
+        grove_coordinates_sum(numbers, 1, 1).map_or(Answer::Unsolved, Answer::from)
+    }
+
+    fn part2(numbers: &Self::Input) -> Answer {
+        grove_coordinates_sum(numbers, DECRYPTION_KEY.value(), 10)
+            .map_or(Answer::Unsolved, Answer::from)
+    }
+
+    // `size` numbers (at least 2), exactly one of which is 0.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut numbers = (1..size.max(2))
+            .map(|_| match rng.range(-10000..10000) {
+                0 => 10000,
+                number => number,
+            })
+            .collect::<Vec<i64>>();
+
+        numbers.insert(rng.below(numbers.len() + 1), 0);
+
+        Some(
+            numbers
+                .iter()
+                .map(|number| format!("{}\n", number))
+                .collect(),
+        )
+    }
+
+    // Moves each number by swapping it with its next neighbour, one step at a time. Moving by the
+    // length minus one is a full turn around the other numbers, so that's all the steps it takes.
+    fn reference(numbers: &Self::Input) -> Option<(Answer, Answer)> {
+        const MAX_NUMBERS: usize = 1000;
+
+        if numbers.len() < 2 || numbers.len() > MAX_NUMBERS {
+            return Some((Answer::Unsolved, Answer::Unsolved));
+        }
+
+        let mix = |decryption_key: i64, rounds: u32| {
+            let Some(numbers) = numbers
+                .iter()
+                .map(|number| number.checked_mul(decryption_key))
+                .collect::<Option<Vec<i64>>>()
+            else {
+                return Answer::Unsolved;
+            };
+            let mut list = numbers
+                .into_iter()
+                .enumerate()
+                .collect::<Vec<(usize, i64)>>();
+            let len = list.len();
+
+            for _ in 0..rounds {
+                for id in 0..len {
+                    let mut index = list.iter().position(|(other, _)| *other == id).unwrap();
+
+                    for _ in 0..list[index].1.rem_euclid(len as i64 - 1) {
+                        list.swap(index, (index + 1) % len);
+                        index = (index + 1) % len;
+                    }
+                }
+            }
+
+            let zero = list.iter().position(|(_, number)| *number == 0).unwrap();
+            let coordinates = [1000, 2000, 3000].map(|offset| list[(zero + offset) % len].1);
+            coordinates
+                .iter()
+                .try_fold(0_i64, |sum, coordinate| sum.checked_add(*coordinate))
+                .map_or(Answer::Unsolved, Answer::from)
+        };
+
+        Some((mix(1, 1), mix(DECRYPTION_KEY.value(), 10)))
+    }
+}
+
+// None if a number times the key, or the sum, doesn't fit in an i64.
+fn grove_coordinates_sum(numbers: &[i64], decryption_key: i64, rounds: u32) -> Option<i64> {
+    let original_numbers = numbers
+        .iter()
         .enumerate()
-        .map(|(id, number)| (number.parse::<i64>().unwrap(), id))
-        .map(|(number, id)| (number * DECRYPTION_KEY, id))
-        .collect::<CircularList>();
+        .map(|(id, number)| Some((number.checked_mul(decryption_key)?, id)))
+        .collect::<Option<CircularList>>()?;
 
     let mut numbers = original_numbers.clone();
+    let mut progress = Progress::new("Mixing", rounds as usize * numbers.0.len());
 
-    for _ in 1..=10 {
-        for element in original_numbers.0.clone() {
-            numbers.move_element(element);
+    for round in 0..rounds as usize {
+        for (index, element) in original_numbers.0.iter().enumerate() {
+            progress.set(round * numbers.0.len() + index);
+            numbers.move_element(*element);
         }
     }
 
@@ -66,11 +173,70 @@ pub fn run(input: &str) {
     let n2 = numbers.get_element_from_zero(2000);
     let n3 = numbers.get_element_from_zero(3000);
 
-    println!(
-        "1000th is {}, 2000th is {}, 3000th is {}, sum is {}",
-        n1,
-        n2,
-        n3,
-        n1 + n2 + n3
-    );
+    n1.checked_add(n2)?.checked_add(n3)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn mixed(numbers: &[i64]) -> CircularList {
+        let original = numbers
+            .iter()
+            .enumerate()
+            .map(|(id, number)| (*number, id))
+            .collect::<CircularList>();
+        let mut list = original.clone();
+
+        for element in original.0 {
+            list.move_element(element);
+        }
+
+        list
+    }
+
+    #[test]
+    fn test_mix() {
+        assert_eq!(
+            mixed(&[1, 2, -3, 3, -2, 0, 4]).to_string(),
+            "-2, 1, 2, -3, 4, 0, 3"
+        );
+        assert_eq!(mixed(&[5]).to_string(), "5");
+    }
+
+    #[test]
+    fn test_parse_without_zero() {
+        assert_eq!(
+            Day20::parse("1\n2\n-3").unwrap_err().to_string(),
+            "expected a 0 among the numbers"
+        );
+    }
+
+    #[test]
+    fn test_overflowing_numbers() {
+        let numbers = Day20::parse("1\n9223372036854775807\n0").unwrap();
+        assert_eq!(Day20::part1(&numbers), Answer::Unsolved);
+        assert_eq!(Day20::part2(&numbers), Answer::Unsolved);
+
+        assert_eq!(
+            mixed(&[0, i64::MAX, i64::MIN]).to_string(),
+            "-9223372036854775808, 9223372036854775807, 0"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn mixing_keeps_the_numbers(numbers in prop::collection::vec(-1000_i64..1000, 1..30)) {
+            let list = mixed(&numbers);
+
+            let mut elements = list.0.clone();
+            elements.sort_by_key(|(_, id)| *id);
+            prop_assert_eq!(
+                elements,
+                numbers.iter().copied().zip(0..).collect::<Vec<(i64, usize)>>()
+            );
+        }
+    }
 }
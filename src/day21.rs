@@ -1,11 +1,17 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{Display, Formatter},
     str::FromStr,
 };
 
+use crate::{
+    error::{parse_lines, split_once, Error},
+    random::Rng,
+    solution::{diagnostic, Answer, Solution},
+};
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Operation {
+pub enum Operation {
     Add,
     Sub,
     Mul,
@@ -23,12 +29,13 @@ impl Operation {
         }
     }
 
-    fn apply(&self, a: isize, b: isize) -> isize {
+    // None if it overflows or divides by zero.
+    fn apply(&self, a: isize, b: isize) -> Option<isize> {
         match self {
-            Self::Add => a + b,
-            Self::Sub => a - b,
-            Self::Mul => a * b,
-            Self::Div => a / b,
+            Self::Add => a.checked_add(b),
+            Self::Sub => a.checked_sub(b),
+            Self::Mul => a.checked_mul(b),
+            Self::Div => a.checked_div(b),
         }
     }
 
@@ -42,46 +49,51 @@ impl Operation {
     }
 }
 
-#[cfg(test)]
-mod operation_test {
-    use super::*;
-
-    #[test]
-    fn test_parse() {
-        assert_eq!(Operation::parse("+"), Some(Operation::Add));
-        assert_eq!(Operation::parse("-"), Some(Operation::Sub));
-        assert_eq!(Operation::parse("*"), Some(Operation::Mul));
-        assert_eq!(Operation::parse("/"), Some(Operation::Div));
-        assert_eq!(Operation::parse("a"), None);
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Sub => write!(f, "-"),
+            Self::Mul => write!(f, "*"),
+            Self::Div => write!(f, "/"),
+        }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
-enum Monkey {
+pub enum Monkey {
     YellingMonkey(String, isize),
     MathMonkey(String, Operation, String, String),
 }
 
 impl FromStr for Monkey {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let (name, rest) = string.split_once(":").unwrap();
+        let (name, rest) = split_once(string, string, ":")?;
         let rest = rest.trim();
 
         match rest.parse::<isize>() {
             Ok(number) => Ok(Self::YellingMonkey(name.to_string(), number)),
-            _ => {
-                let (monkey1, rest) = rest.split_at(4);
-                let (operation, monkey2) = rest.trim().split_once(" ").unwrap();
-
-                Ok(Self::MathMonkey(
+            _ => match rest.split_whitespace().collect::<Vec<&str>>()[..] {
+                [monkey1, operation, monkey2] => Ok(Self::MathMonkey(
                     name.to_string(),
-                    Operation::parse(operation).unwrap(),
+                    Operation::parse(operation).ok_or_else(|| {
+                        Error::at(
+                            string,
+                            operation,
+                            format!("unknown operation `{}`", operation),
+                        )
+                    })?,
                     monkey1.to_string(),
                     monkey2.to_string(),
-                ))
-            }
+                )),
+                _ => Err(Error::at(
+                    string,
+                    rest,
+                    "expected a number or `<monkey> <operation> <monkey>`",
+                )),
+            },
         }
     }
 }
@@ -108,46 +120,50 @@ impl ASTNode {
             Monkey::YellingMonkey(name, _) if *name == "humn" => Self::Human,
             Monkey::YellingMonkey(_, number) => Self::Number(*number),
             Monkey::MathMonkey(_, operation, left, right) => Self::Operation(
-                operation.clone(),
+                *operation,
                 Box::new(Self::from_monkey(monkeys[left].name().as_str(), monkeys)),
                 Box::new(Self::from_monkey(monkeys[right].name().as_str(), monkeys)),
             ),
         }
     }
 
-    fn simplify(&self) -> Self {
+    // None if an operation between numbers overflows or divides by zero.
+    fn simplify(&self) -> Option<Self> {
         match self {
-            Self::Human => Self::Human,
-            Self::Number(number) => Self::Number(*number),
+            Self::Human => Some(Self::Human),
+            Self::Number(number) => Some(Self::Number(*number)),
             Self::Operation(operation, left, right) => {
-                let left = left.simplify();
-                let right = right.simplify();
+                let left = left.simplify()?;
+                let right = right.simplify()?;
 
                 match (left, right) {
                     (Self::Number(left), Self::Number(right)) => {
-                        Self::Number(operation.apply(left, right))
+                        Some(Self::Number(operation.apply(left, right)?))
                     }
                     (left, right) => {
-                        Self::Operation(operation.clone(), Box::new(left), Box::new(right))
+                        Some(Self::Operation(*operation, Box::new(left), Box::new(right)))
                     }
                 }
             }
         }
     }
 
-    // left is always an expression with "humn" in it, right is always a number.
-    fn simplify_equation(left: &Self, right: &Self) -> (Self, Self) {
+    // left is always an expression with "humn" in it, right is always a number. None if it can't
+    // be reduced to "humn" = number, like when "humn" divides a number, or when undoing an
+    // operation overflows or divides by zero.
+    fn simplify_equation(left: &Self, right: &Self) -> Option<(Self, Self)> {
         match (left, right) {
             // We solved it!
-            (Self::Human, Self::Number(_)) => (left.clone(), right.clone()),
-
-            (Self::Number(_), _) => panic!("left is a number, but should be humn"),
+            (Self::Human, Self::Number(_)) => Some((left.clone(), right.clone())),
 
             // If there's an operation on the left, we simplify it.
             (Self::Operation(op, x, y), Self::Number(right_number)) => {
                 // The equation is number • y = right_number, so we can simplify it as
                 // y = right_number ¬ number, where ¬ is the opposite of •.
                 match (x.as_ref(), y.as_ref()) {
+                    // Integer division by "humn" can't be undone.
+                    (Self::Number(_), _) if *op == Operation::Div => None,
+
                     (Self::Number(number), _) => {
                         let (simplified_left, op) = if *op == Operation::Sub {
                             (
@@ -163,28 +179,29 @@ impl ASTNode {
                         };
 
                         let simplified_right =
-                            Self::Number(op.inverse().apply(*right_number, *number));
+                            Self::Number(op.inverse().apply(*right_number, *number)?);
 
                         Self::simplify_equation(&simplified_left, &simplified_right)
                     }
 
+                    (_, Self::Number(0)) if *op == Operation::Div => None,
+
                     // The equation is x • number = right_number, so we can simplify it as
                     // x = right_number ¬ number, where ¬ is the opposite of •.
                     (_, Self::Number(number)) => {
                         let simplified_left = x.clone();
                         let simplified_right =
-                            Self::Number(op.inverse().apply(*right_number, *number));
+                            Self::Number(op.inverse().apply(*right_number, *number)?);
 
                         Self::simplify_equation(&simplified_left, &simplified_right)
                     }
 
-                    _ => {
-                        panic!("found an operation on the left where neither operand is a number")
-                    }
+                    // "humn" is on both sides of the operation.
+                    _ => None,
                 }
             }
 
-            _ => todo!(),
+            _ => None,
         }
     }
 }
@@ -194,40 +211,517 @@ impl Display for ASTNode {
         match self {
             Self::Human => write!(f, "humn"),
             Self::Number(number) => write!(f, "{}", number),
-            Self::Operation(operation, left, right) => write!(
-                f,
-                "({} {} {})",
-                *left,
-                match operation {
-                    Operation::Add => "+",
-                    Operation::Sub => "-",
-                    Operation::Mul => "*",
-                    Operation::Div => "/",
-                },
-                *right
-            ),
+            Self::Operation(operation, left, right) => {
+                write!(f, "({} {} {})", *left, operation, *right)
+            }
+        }
+    }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = HashMap<String, Monkey>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let lines = parse_lines(input, Monkey::from_str)?;
+        let monkeys = lines
+            .iter()
+            .map(|monkey| (monkey.name(), monkey.clone()))
+            .collect::<HashMap<String, Monkey>>();
+
+        let mut jobs = HashMap::new();
+
+        // Part 2 needs "root" to compare two monkeys, and "humn" to yell a number it can change.
+        for (line, monkey) in input.lines().zip(&lines) {
+            let (_, job) = line.split_once(':').unwrap();
+            jobs.insert(monkey.name(), job.trim());
+
+            match monkey {
+                Monkey::MathMonkey(name, ..) if name == "humn" => {
+                    return Err(Error::at(
+                        input,
+                        job.trim(),
+                        "expected `humn` to yell a number",
+                    ));
+                }
+                Monkey::YellingMonkey(name, _) if name == "root" => {
+                    return Err(Error::at(
+                        input,
+                        job.trim(),
+                        "expected `root` to be an operation",
+                    ));
+                }
+                Monkey::MathMonkey(_, _, left, right) => {
+                    for operand in [left, right] {
+                        if !monkeys.contains_key(operand) {
+                            let operand =
+                                job.split_whitespace().find(|word| word == operand).unwrap();
+                            return Err(Error::at(
+                                input,
+                                operand,
+                                format!("no monkey {}", operand),
+                            ));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for name in ["root", "humn"] {
+            if !monkeys.contains_key(name) {
+                return Err(Error::new(format!("expected a `{}` monkey", name)));
+            }
         }
+
+        // Part 2 can only undo the operations between "root" and "humn" if there's one path
+        // between them.
+        let mut counts = HashMap::new();
+
+        for monkey in &lines {
+            human_count(&monkey.name(), &monkeys, &mut counts).map_err(|monkey| {
+                Error::at(
+                    input,
+                    jobs[monkey],
+                    format!("monkey {} depends on itself", monkey),
+                )
+            })?;
+        }
+
+        if counts["root"] != Some(1) {
+            return Err(Error::at(
+                input,
+                jobs["root"],
+                "expected `humn` to be in the equation of `root` once",
+            ));
+        }
+
+        Ok(monkeys)
+    }
+
+    fn part1(monkeys: &Self::Input) -> Answer {
+        let Some(number) = yelled_number("root", monkeys) else {
+            diagnostic!("A monkey's operation overflows or divides by zero");
+            return Answer::Unsolved;
+        };
+
+        number.into()
+    }
+
+    // "root" checks that its two operands are equal, and we need to find what "humn" yells.
+    fn part2(monkeys: &Self::Input) -> Answer {
+        let Monkey::MathMonkey(_, _, left, right) = &monkeys["root"] else {
+            return Answer::Unsolved;
+        };
+        let (left_ast, right_ast) = (
+            ASTNode::from_monkey(left, monkeys),
+            ASTNode::from_monkey(right, monkeys),
+        );
+
+        // The side with "humn" in it always goes on the left.
+        let simplified = match (left_ast.simplify(), right_ast.simplify()) {
+            (Some(left @ ASTNode::Number(_)), Some(right)) => (right, left),
+            (Some(left), Some(right)) => (left, right),
+            _ => {
+                diagnostic!("A monkey's operation overflows or divides by zero");
+                return Answer::Unsolved;
+            }
+        };
+        diagnostic!("Simplified: {} = {}", simplified.0, simplified.1);
+
+        let Some(reduced) = ASTNode::simplify_equation(&simplified.0, &simplified.1) else {
+            diagnostic!("The equation can't be reduced to humn = <number>");
+            return Answer::Unsolved;
+        };
+        diagnostic!("Reduced: {} = {}", reduced.0, reduced.1);
+
+        match reduced.1 {
+            ASTNode::Number(number) => number.into(),
+            _ => Answer::Unsolved,
+        }
+    }
+
+    // `size` operations between "humn" and "root", with other monkeys yelling the numbers they
+    // need. The equation is built from the number to yell, only with operations part 2 knows how
+    // to undo, and divisions that leave no remainder.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        const LIMIT: isize = 1_000_000_000_000;
+
+        let mut names = HashSet::from(["root".to_string(), "humn".to_string()]);
+        let mut lines = Vec::new();
+        // What the monkeys on the path yell, with the number "humn" has to yell and the one it
+        // actually yells.
+        let (mut equal, mut yelled) = (rng.range(1..1000) as isize, rng.range(1..1000) as isize);
+        let mut monkey = "humn".to_string();
+
+        lines.push(format!("humn: {}", yelled));
+
+        for _ in 0..size {
+            let number = rng.range(2..10) as isize;
+            let (operation, human_first) = match rng.below(4) {
+                0 => (Operation::Add, rng.chance(0.5)),
+                1 => (Operation::Sub, rng.chance(0.5)),
+                2 if equal.abs() * number < LIMIT && yelled.abs() * number < LIMIT => {
+                    (Operation::Mul, rng.chance(0.5))
+                }
+                3 if equal % number == 0 && yelled % number == 0 => (Operation::Div, true),
+                _ => (Operation::Add, true),
+            };
+
+            let other = generate_monkey(rng, number, 2, &mut names, &mut lines);
+            let name = unique_name(rng, &mut names);
+
+            (equal, yelled) = match human_first {
+                true => (
+                    operation.apply(equal, number)?,
+                    operation.apply(yelled, number)?,
+                ),
+                false => (
+                    operation.apply(number, equal)?,
+                    operation.apply(number, yelled)?,
+                ),
+            };
+
+            lines.push(match human_first {
+                true => format!("{}: {} {} {}", name, monkey, operation, other),
+                false => format!("{}: {} {} {}", name, other, operation, monkey),
+            });
+            monkey = name;
+        }
+
+        let other = generate_monkey(rng, equal, 3, &mut names, &mut lines);
+        lines.push(match rng.chance(0.5) {
+            true => format!("root: {} + {}", monkey, other),
+            false => format!("root: {} + {}", other, monkey),
+        });
+
+        rng.shuffle(&mut lines);
+        Some(lines.iter().map(|line| format!("{}\n", line)).collect())
+    }
+
+    // Works out what each monkey yells as a fraction, and as a linear function of what "humn"
+    // yells for part 2, which gives the number that makes both sides of "root" equal. Parts with
+    // answers that aren't whole numbers (or that overflow) are unsolved.
+    fn reference(monkeys: &Self::Input) -> Option<(Answer, Answer)> {
+        let whole = |number: Option<Fraction>| match number {
+            Some((numerator, 1)) => Answer::from(numerator),
+            _ => Answer::Unsolved,
+        };
+
+        let part1 = whole(yelled_fraction("root", monkeys));
+
+        let part2 = match &monkeys["root"] {
+            Monkey::MathMonkey(_, _, left, right) => whole(equalizing_human(left, right, monkeys)),
+            _ => Answer::Unsolved,
+        };
+
+        Some((part1, part2))
     }
 }
 
-pub fn run(input: &str) {
-    let monkeys = input
-        .lines()
-        .map(|line| line.parse::<Monkey>().unwrap())
-        .map(|monkey| (monkey.name(), monkey.clone()))
-        .collect::<HashMap<String, Monkey>>();
+// A number as a numerator and a positive denominator, with no common divisor, for the reference
+// solver. The operations return None if they overflow.
+type Fraction = (i128, i128);
+
+fn fraction(numerator: i128, denominator: i128) -> Option<Fraction> {
+    let mut gcd = (numerator.abs(), denominator.abs());
+
+    while gcd.1 != 0 {
+        gcd = (gcd.1, gcd.0 % gcd.1);
+    }
+
+    let divisor = gcd.0.max(1) * denominator.signum();
+    (denominator != 0).then_some((numerator / divisor, denominator / divisor))
+}
+
+fn add(a: Fraction, b: Fraction) -> Option<Fraction> {
+    fraction(
+        a.0.checked_mul(b.1)?.checked_add(b.0.checked_mul(a.1)?)?,
+        a.1.checked_mul(b.1)?,
+    )
+}
+
+fn negate(a: Fraction) -> Fraction {
+    (-a.0, a.1)
+}
+
+fn multiply(a: Fraction, b: Fraction) -> Option<Fraction> {
+    fraction(a.0.checked_mul(b.0)?, a.1.checked_mul(b.1)?)
+}
 
-    let (left_ast, right_ast) = match &monkeys["root"] {
-        Monkey::MathMonkey(_, _, left, right) => (
-            ASTNode::from_monkey(&left, &monkeys),
-            ASTNode::from_monkey(&right, &monkeys),
+fn invert(a: Fraction) -> Option<Fraction> {
+    fraction(a.1, a.0)
+}
+
+fn yelled_fraction(monkey: &str, monkeys: &HashMap<String, Monkey>) -> Option<Fraction> {
+    let [coefficient, constant] = linear(monkey, monkeys)?;
+    let human = match &monkeys["humn"] {
+        Monkey::YellingMonkey(_, number) => (*number as i128, 1),
+        Monkey::MathMonkey(..) => return None,
+    };
+
+    add(multiply(coefficient, human)?, constant)
+}
+
+// What "humn" has to yell for both monkeys to yell the same number.
+fn equalizing_human(
+    left: &str,
+    right: &str,
+    monkeys: &HashMap<String, Monkey>,
+) -> Option<Fraction> {
+    let ([a, b], [c, d]) = (linear(left, monkeys)?, linear(right, monkeys)?);
+
+    // a * humn + b = c * humn + d
+    multiply(add(d, negate(b))?, invert(add(a, negate(c))?)?)
+}
+
+// What a monkey yells as `a * humn + b`, or None if it isn't linear (or overflows).
+fn linear(monkey: &str, monkeys: &HashMap<String, Monkey>) -> Option<[Fraction; 2]> {
+    match monkeys.get(monkey)? {
+        Monkey::YellingMonkey(name, _) if name == "humn" => Some([(1, 1), (0, 1)]),
+        Monkey::YellingMonkey(_, number) => Some([(0, 1), (*number as i128, 1)]),
+        Monkey::MathMonkey(_, operation, left, right) => {
+            let ([a, b], [c, d]) = (linear(left, monkeys)?, linear(right, monkeys)?);
+
+            match operation {
+                Operation::Add => Some([add(a, c)?, add(b, d)?]),
+                Operation::Sub => Some([add(a, negate(c))?, add(b, negate(d))?]),
+                Operation::Mul if a.0 == 0 => Some([multiply(b, c)?, multiply(b, d)?]),
+                Operation::Mul if c.0 == 0 => Some([multiply(a, d)?, multiply(b, d)?]),
+                Operation::Div if c.0 == 0 => {
+                    let divisor = invert(d)?;
+                    Some([multiply(a, divisor)?, multiply(b, divisor)?])
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+// Adds a monkey yelling the number, directly or from up to `depth` levels of operations, and
+// returns its name.
+fn generate_monkey(
+    rng: &mut Rng,
+    number: isize,
+    depth: usize,
+    names: &mut HashSet<String>,
+    lines: &mut Vec<String>,
+) -> String {
+    let name = unique_name(rng, names);
+    let operand = rng.range(1..20) as isize;
+
+    let operation = match rng.below(4) {
+        _ if depth == 0 => None,
+        0 => Some((Operation::Add, number - operand, operand)),
+        1 => Some((Operation::Sub, number + operand, operand)),
+        2 if number % operand == 0 => Some((Operation::Mul, number / operand, operand)),
+        3 if (number * operand).abs() < 1_000_000_000_000 => {
+            Some((Operation::Div, number * operand, operand))
+        }
+        _ => None,
+    };
+
+    let line = match operation {
+        Some((operation, left, right)) => {
+            let left = generate_monkey(rng, left, depth - 1, names, lines);
+            let right = generate_monkey(rng, right, depth - 1, names, lines);
+            format!("{}: {} {} {}", name, left, operation, right)
+        }
+        None => format!("{}: {}", name, number),
+    };
+
+    lines.push(line);
+    name
+}
+
+fn unique_name(rng: &mut Rng, names: &mut HashSet<String>) -> String {
+    loop {
+        let name = rng.word(4);
+
+        if names.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+// None if an operation overflows or divides by zero.
+fn yelled_number(monkey: &str, monkeys: &HashMap<String, Monkey>) -> Option<isize> {
+    match &monkeys[monkey] {
+        Monkey::YellingMonkey(_, number) => Some(*number),
+        Monkey::MathMonkey(_, operation, left, right) => operation.apply(
+            yelled_number(left, monkeys)?,
+            yelled_number(right, monkeys)?,
         ),
-        _ => panic!("root is not a math monkey"),
+    }
+}
+
+// How many times "humn" is in the equation of a monkey, or the name of a monkey that depends on
+// itself. `counts` keeps the monkeys counted so far, with None for the ones being counted.
+fn human_count<'a>(
+    monkey: &str,
+    monkeys: &'a HashMap<String, Monkey>,
+    counts: &mut HashMap<&'a str, Option<usize>>,
+) -> Result<usize, &'a str> {
+    let (name, monkey) = monkeys.get_key_value(monkey).unwrap();
+
+    match counts.get(name.as_str()) {
+        Some(Some(count)) => return Ok(*count),
+        Some(None) => return Err(name),
+        None => counts.insert(name, None),
     };
 
-    let simplified = (left_ast.simplify(), right_ast.simplify());
-    println!("Simplified: {} = {}", simplified.0, simplified.1);
+    let count = match monkey {
+        Monkey::YellingMonkey(..) => usize::from(name == "humn"),
+        Monkey::MathMonkey(_, _, left, right) => {
+            human_count(left, monkeys, counts)?.saturating_add(human_count(right, monkeys, counts)?)
+        }
+    };
+
+    counts.insert(name, Some(count));
+    Ok(count)
+}
 
-    let reduced = ASTNode::simplify_equation(&simplified.0, &simplified.1);
-    println!("Reduced: {} = {}", reduced.0, reduced.1);
+#[cfg(test)]
+mod operation_test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Operation::parse("+"), Some(Operation::Add));
+        assert_eq!(Operation::parse("-"), Some(Operation::Sub));
+        assert_eq!(Operation::parse("*"), Some(Operation::Mul));
+        assert_eq!(Operation::parse("/"), Some(Operation::Div));
+        assert_eq!(Operation::parse("a"), None);
+    }
+}
+
+#[cfg(test)]
+mod ast_node_test {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    // The value of the expression when the human yells `human`, or None if it divides by zero or
+    // overflows anywhere.
+    fn evaluate(node: &ASTNode, human: isize) -> Option<isize> {
+        match node {
+            ASTNode::Human => Some(human),
+            ASTNode::Number(number) => Some(*number),
+            ASTNode::Operation(operation, left, right) => {
+                let (left, right) = (evaluate(left, human)?, evaluate(right, human)?);
+
+                match operation {
+                    Operation::Add => left.checked_add(right),
+                    Operation::Sub => left.checked_sub(right),
+                    Operation::Mul => left.checked_mul(right),
+                    Operation::Div => left.checked_div(right),
+                }
+            }
+        }
+    }
+
+    fn has_constant_operation(node: &ASTNode) -> bool {
+        match node {
+            ASTNode::Operation(_, left, right) => {
+                matches!(**left, ASTNode::Number(_)) && matches!(**right, ASTNode::Number(_))
+                    || has_constant_operation(left)
+                    || has_constant_operation(right)
+            }
+            _ => false,
+        }
+    }
+
+    fn operations() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            Just(Operation::Add),
+            Just(Operation::Sub),
+            Just(Operation::Mul),
+            Just(Operation::Div),
+        ]
+    }
+
+    fn expressions() -> impl Strategy<Value = ASTNode> {
+        let leaf = prop_oneof![
+            1 => Just(ASTNode::Human),
+            4 => (-20_isize..20).prop_map(ASTNode::Number),
+        ];
+
+        leaf.prop_recursive(5, 32, 2, |node| {
+            (operations(), node.clone(), node).prop_map(|(operation, left, right)| {
+                ASTNode::Operation(operation, Box::new(left), Box::new(right))
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn simplify_keeps_the_value(expression in expressions(), human in -50_isize..50) {
+            let value = evaluate(&expression, human);
+            prop_assume!(value.is_some());
+
+            let simplified = expression.simplify().unwrap();
+
+            prop_assert_eq!(evaluate(&simplified, human), value);
+            prop_assert!(!has_constant_operation(&simplified), "{}", simplified);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| Day21::parse(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("root: 5\nhumn: 3"),
+            "line 1 col 7: expected `root` to be an operation"
+        );
+        assert_eq!(
+            error("root: abcd + ab\nhumn: 3\nabcd: 2"),
+            "line 1 col 14: no monkey ab"
+        );
+        assert_eq!(
+            error("root: humn + abcd\nhumn: abcd * abcd\nabcd: 2"),
+            "line 2 col 7: expected `humn` to yell a number"
+        );
+        assert_eq!(
+            error("root: abcd + abcd\nabcd: 2"),
+            "expected a `humn` monkey"
+        );
+        assert_eq!(
+            error("root: abcd + humn\nabcd: efgh * humn\nefgh: abcd - humn\nhumn: 3"),
+            "line 2 col 7: monkey abcd depends on itself"
+        );
+        assert_eq!(
+            error("root: humn + humn\nhumn: 3"),
+            "line 1 col 7: expected `humn` to be in the equation of `root` once"
+        );
+        assert_eq!(
+            error("root: abcd + abcd\nabcd: humn * efgh\nefgh: 2\nhumn: 3"),
+            "line 1 col 7: expected `humn` to be in the equation of `root` once"
+        );
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let monkeys =
+            Day21::parse("root: abcd + efgh\nabcd: humn / efgh\nefgh: 0\nhumn: 3").unwrap();
+        assert_eq!(Day21::part1(&monkeys), Answer::Unsolved);
+        assert_eq!(Day21::part2(&monkeys), Answer::Unsolved);
+
+        let monkeys = Day21::parse(
+            "root: abcd + efgh\nabcd: humn * efgh\nefgh: 9223372036854775807\nhumn: 3",
+        )
+        .unwrap();
+        assert_eq!(Day21::part1(&monkeys), Answer::Unsolved);
+
+        let monkeys =
+            Day21::parse("root: abcd + efgh\nabcd: efgh / humn\nefgh: 6\nhumn: 3").unwrap();
+        assert_eq!(Day21::part1(&monkeys), Answer::Number(8));
+        assert_eq!(Day21::part2(&monkeys), Answer::Unsolved);
+    }
 }
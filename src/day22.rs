@@ -1,68 +1,46 @@
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::num::ParseIntError;
 use std::str::FromStr;
 
 use ansi_term::Style;
 
-type Position = (usize, usize);
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn turn_left(&self) -> Self {
-        match self {
-            Direction::North => Direction::West,
-            Direction::East => Direction::North,
-            Direction::South => Direction::East,
-            Direction::West => Direction::South,
-        }
-    }
-
-    fn turn_right(&self) -> Self {
-        match self {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        }
-    }
-
-    fn to_password(&self) -> usize {
-        match self {
-            Direction::East => 0,
-            Direction::South => 1,
-            Direction::West => 2,
-            Direction::North => 3,
-        }
-    }
-}
-
-impl Display for Direction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Direction::North => write!(f, "^"),
-            Direction::East => write!(f, ">"),
-            Direction::South => write!(f, "v"),
-            Direction::West => write!(f, "<"),
-        }
+use crate::{
+    error::{split_once, Error},
+    geometry::Direction4,
+    grid::{Grid, Position},
+    random::Rng,
+    solution::{trace, Answer, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+const PALETTE: Palette = &[
+    ('.', Rgb(60, 60, 70)),
+    ('#', Rgb(170, 170, 180)),
+    ('^', Rgb(90, 200, 90)),
+    ('>', Rgb(90, 200, 90)),
+    ('v', Rgb(90, 200, 90)),
+    ('<', Rgb(90, 200, 90)),
+    ('@', Rgb(250, 230, 80)),
+];
+
+// The value of the facing in the password.
+fn facing_password(direction: Direction4) -> usize {
+    match direction {
+        Direction4::East => 0,
+        Direction4::South => 1,
+        Direction4::West => 2,
+        Direction4::North => 3,
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum Instruction {
+pub enum Instruction {
     TurnLeft,
     TurnRight,
     Move(usize),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 enum Cell {
     Empty,
     Space,
@@ -70,83 +48,48 @@ enum Cell {
 }
 
 impl Cell {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Option<Self> {
         match c {
-            '.' => Cell::Space,
-            '#' => Cell::Wall,
-            ' ' => Cell::Empty,
-            _ => unreachable!(),
+            '.' => Some(Cell::Space),
+            '#' => Some(Cell::Wall),
+            ' ' => Some(Cell::Empty),
+            _ => None,
         }
     }
 
     fn is_some(&self) -> bool {
-        match self {
-            Cell::Empty => false,
-            _ => true,
-        }
+        !matches!(self, Cell::Empty)
     }
 }
 
-#[cfg(test)]
-mod cell_tests {
-    use super::*;
-
-    #[test]
-    fn from_char() {
-        assert_eq!(Cell::from_char('.'), Cell::Space);
-        assert_eq!(Cell::from_char('#'), Cell::Wall);
-        assert_eq!(Cell::from_char(' '), Cell::Empty);
-    }
-
-    #[test]
-    fn is_some() {
-        assert_eq!(Cell::Space.is_some(), true);
-        assert_eq!(Cell::Wall.is_some(), true);
-        assert_eq!(Cell::Empty.is_some(), false);
-    }
-}
-
-struct Board {
-    map: Vec<Vec<Cell>>,
+#[derive(Clone)]
+pub struct Board {
+    map: Grid<Cell>,
     current_position: Position,
-    current_direction: Direction,
-    visited_positions: HashMap<Position, Direction>,
+    current_direction: Direction4,
+    visited_positions: HashMap<Position, Direction4>,
 }
 
 impl FromStr for Board {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, ()> {
-        let line_count = s.lines().count();
-        let col_count = s.lines().map(str::len).max().unwrap();
-
-        let mut current_position = None;
-        let mut map = Vec::with_capacity(line_count);
-
-        for row_index in 0..line_count {
-            let line = s.lines().nth(row_index).unwrap();
-            let mut row = Vec::with_capacity(col_count);
+    type Err = Error;
 
-            for col_index in 0..col_count {
-                let cell = line
-                    .chars()
-                    .nth(col_index)
-                    .map_or(Cell::Empty, Cell::from_char);
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let map = Grid::parse(s, "`.`, `#` or a space", Cell::from_char)?;
 
-                if cell == Cell::Space && current_position.is_none() {
-                    current_position = Some((row_index, col_index));
-                }
-
-                row.push(cell);
-            }
-
-            map.push(row);
+        if map.is_empty() {
+            return Err(Error::new("expected a map"));
         }
 
+        let current_position = map
+            .iter()
+            .find(|(_, cell)| **cell == Cell::Space)
+            .map(|(position, _)| position)
+            .ok_or_else(|| Error::new("the map has no open tile to start from"))?;
+
         Ok(Self {
             map,
-            current_position: current_position.unwrap(),
-            current_direction: Direction::East,
+            current_position,
+            current_direction: Direction4::East,
             visited_positions: HashMap::new(),
         })
     }
@@ -154,30 +97,25 @@ impl FromStr for Board {
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{} by {} board:", self.map.len(), self.map[0].len())?;
-
-        for (row_index, row) in self.map.iter().enumerate() {
-            for (cell_index, cell) in row.iter().enumerate() {
-                match cell {
-                    _ if self.current_position == (row_index, cell_index) => {
-                        let style = Style::new().bold().on(ansi_term::Color::Green);
-                        write!(f, "{}", style.paint(format!("{}", self.current_direction)))?
-                    }
-                    _ if self
-                        .visited_positions
-                        .contains_key(&(row_index, cell_index)) =>
-                    {
-                        write!(f, "{}", self.visited_positions[&(row_index, cell_index)])?
-                    }
-                    Cell::Space => write!(f, ".")?,
-                    Cell::Wall => write!(f, "#")?,
-                    Cell::Empty => write!(f, " ")?,
-                }
+        let (rows, columns) = self.map.size();
+        writeln!(f, "{} by {} board:", rows, columns)?;
+
+        let drawing = self.map.render(|position, cell| match cell {
+            _ if self.current_position == position => {
+                let style = Style::new().bold().on(ansi_term::Color::Green);
+                style
+                    .paint(format!("{}", self.current_direction))
+                    .to_string()
             }
-            writeln!(f)?;
-        }
+            _ if self.visited_positions.contains_key(&position) => {
+                self.visited_positions[&position].to_string()
+            }
+            Some(Cell::Space) => ".".to_string(),
+            Some(Cell::Wall) => "#".to_string(),
+            Some(Cell::Empty) | None => " ".to_string(),
+        });
 
-        Ok(())
+        writeln!(f, "{}", drawing)
     }
 }
 
@@ -196,118 +134,165 @@ impl Board {
         }
     }
 
+    // Moves one tile forward, wrapping around the map past the empty cells around it, unless a
+    // wall is in the way.
     fn move_forward(&mut self) {
-        let (row_index, col_index) = self.current_position;
-
-        let new_position = match self.current_direction {
-            Direction::East => {
-                let row = &self.map[row_index];
-
-                match self.map[row_index].get(col_index + 1) {
-                    Some(Cell::Space) => (row_index, col_index + 1),
-                    Some(Cell::Wall) => self.current_position.clone(),
-                    Some(Cell::Empty) | None => {
-                        let new_col_index = row.iter().position(|cell| cell.is_some()).unwrap();
-
-                        match self.map[row_index][new_col_index] {
-                            Cell::Space => (row_index, new_col_index),
-                            Cell::Wall => self.current_position.clone(),
-                            _ => unreachable!(),
-                        }
-                    }
-                }
-            }
-            Direction::West => {
-                let row = &self.map[row_index];
-
-                match col_index
-                    .checked_sub(1)
-                    .and_then(|i| self.map[row_index].get(i))
-                {
-                    Some(Cell::Space) => (row_index, col_index - 1),
-                    Some(Cell::Wall) => self.current_position.clone(),
-                    Some(Cell::Empty) | None => {
-                        let new_col_index = row.iter().rposition(|cell| cell.is_some()).unwrap();
-
-                        match self.map[row_index][new_col_index] {
-                            Cell::Space => (row_index, new_col_index),
-                            Cell::Wall => self.current_position.clone(),
-                            _ => unreachable!(),
-                        }
-                    }
-                }
+        let step = self.current_direction.step();
+        let mut new_position = self.map.wrapping_step(self.current_position, step);
+
+        while !self.map[new_position].is_some() {
+            new_position = self.map.wrapping_step(new_position, step);
+        }
+
+        if self.map[new_position] == Cell::Space {
+            self.visited_positions
+                .insert(self.current_position, self.current_direction);
+            self.current_position = new_position;
+        }
+    }
+
+    // The map with the path so far, and the current position.
+    fn frame(&self, caption: String) -> Frame {
+        let mut frame = Frame::new(caption, PALETTE).with_focus(self.current_position);
+
+        frame.draw(&self.map, |position, cell| match cell {
+            _ if self.current_position == position => Some('@'),
+            _ if self.visited_positions.contains_key(&position) => {
+                self.visited_positions[&position].to_string().chars().next()
             }
-            Direction::North => match row_index
-                .checked_sub(1)
-                .and_then(|i| self.map.get(i))
-                .map(|row| &row[col_index])
-            {
-                Some(Cell::Space) => (row_index - 1, col_index),
-                Some(Cell::Wall) => self.current_position.clone(),
-                Some(Cell::Empty) | None => {
-                    let new_row_index = self
-                        .map
-                        .iter()
-                        .rposition(|row| row[col_index].is_some())
-                        .unwrap();
-
-                    match self.map[new_row_index][col_index] {
-                        Cell::Space => (new_row_index, col_index),
-                        Cell::Wall => self.current_position.clone(),
-                        _ => unreachable!(),
-                    }
-                }
-            },
-            Direction::South => match self.map.get(row_index + 1).map(|row| &row[col_index]) {
-                Some(Cell::Space) => (row_index + 1, col_index),
-                Some(Cell::Wall) => self.current_position.clone(),
-                Some(Cell::Empty) | None => {
-                    let new_row_index = self
-                        .map
-                        .iter()
-                        .position(|row| row[col_index].is_some())
-                        .unwrap();
-
-                    match self.map[new_row_index][col_index] {
-                        Cell::Space => (new_row_index, col_index),
-                        Cell::Wall => self.current_position.clone(),
-                        _ => unreachable!(),
-                    }
-                }
-            },
-        };
-
-        self.current_position = new_position;
+            Cell::Space => Some('.'),
+            Cell::Wall => Some('#'),
+            Cell::Empty => None,
+        });
+
+        frame
     }
 
     fn password(&self) -> usize {
-        1000 * (self.current_position.0 + 1)
-            + 4 * (self.current_position.1 + 1)
-            + self.current_direction.to_password()
+        let (row, column) = (self.current_position.y, self.current_position.x);
+
+        1000 * (row as usize + 1)
+            + 4 * (column as usize + 1)
+            + facing_password(self.current_direction)
     }
 }
 
-pub fn run(input: &str) {
-    let (map_string, instruction_string) = input.split_once("\n\n").unwrap();
+pub struct Day22;
 
-    let mut board = map_string.parse::<Board>().unwrap();
-    let instructions = parse_instructions(instruction_string.trim()).unwrap();
+impl Solution for Day22 {
+    type Input = (Board, Vec<Instruction>);
 
-    println!("Board: {}", board);
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let (map_string, instruction_string) = split_once(input, input, "\n\n")?;
+        let instruction_string = instruction_string.trim();
 
-    assert_eq!(board.map.len(), 200);
-    for row in &board.map {
-        assert_eq!(row.len(), 150);
+        let board = map_string.parse::<Board>()?;
+        let instructions = parse_instructions(instruction_string)
+            .map_err(|error| error.within(input, instruction_string))?;
+
+        Ok((board, instructions))
     }
 
-    for instruction in &instructions {
-        board.apply_instruction(instruction);
+    fn part1((board, instructions): &Self::Input) -> Answer {
+        let mut board = board.clone();
+
+        trace!("Board: {}", board);
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            board.apply_instruction(instruction);
+
+            visualize::emit(|| {
+                board.frame(format!(
+                    "Instruction {} of {}: {:?}",
+                    index + 1,
+                    instructions.len(),
+                    instruction
+                ))
+            });
+        }
+
+        board.password().into()
+    }
+
+    // Folding the map into a cube is not implemented.
+    fn part2(_input: &Self::Input) -> Answer {
+        Answer::Unsolved
     }
 
-    println!("Final password: {}", board.password());
+    // A map folding into a cube with faces of `size` tiles (at least 2), laid out like one of the
+    // 11 nets of a cube, turned or flipped, and `size` times 4 instructions.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let size = size.max(2);
+        let mut net = rng
+            .choose(&CUBE_NETS)
+            .iter()
+            .map(|row| row.chars().map(|face| face == '#').collect::<Vec<bool>>())
+            .collect::<Vec<Vec<bool>>>();
+
+        if rng.chance(0.5) {
+            net = (0..net[0].len())
+                .map(|column| net.iter().map(|row| row[column]).collect())
+                .collect();
+        }
+
+        if rng.chance(0.5) {
+            net.reverse();
+        }
+
+        if rng.chance(0.5) {
+            net.iter_mut().for_each(|row| row.reverse());
+        }
+
+        let mut input = String::new();
+
+        for faces in &net {
+            for _ in 0..size {
+                let row = faces
+                    .iter()
+                    .flat_map(|face| (0..size).map(move |_| *face))
+                    .map(|face| match face {
+                        false => ' ',
+                        true if rng.chance(0.15) => '#',
+                        true => '.',
+                    })
+                    .collect::<String>();
+
+                input += row.trim_end();
+                input.push('\n');
+            }
+        }
+
+        input.push('\n');
+
+        for index in 0..size * 4 {
+            if index > 0 {
+                input.push(*rng.choose(&['L', 'R']));
+            }
+
+            input += &rng.range(1..3 * size as i64).to_string();
+        }
+
+        input.push('\n');
+        Some(input)
+    }
 }
 
-fn parse_instructions(s: &str) -> Result<Vec<Instruction>, ParseIntError> {
+// The 11 ways to unfold a cube, with a `#` for each face.
+const CUBE_NETS: [&[&str]; 11] = [
+    &["#...", "####", "#..."],
+    &["#...", "####", ".#.."],
+    &["#...", "####", "..#."],
+    &["#...", "####", "...#"],
+    &[".#..", "####", ".#.."],
+    &[".#..", "####", "..#."],
+    &["##..", ".###", ".#.."],
+    &["##..", ".###", "..#."],
+    &["##..", ".###", "...#"],
+    &["##..", ".##.", "..##"],
+    &["###..", "..###"],
+];
+
+fn parse_instructions(s: &str) -> Result<Vec<Instruction>, Error> {
     let mut left = s;
     let mut instructions = Vec::new();
 
@@ -323,7 +308,7 @@ fn parse_instructions(s: &str) -> Result<Vec<Instruction>, ParseIntError> {
             }
             None => break,
             _ => {
-                let (int, rest) = parse_next_int(left)?;
+                let (int, rest) = parse_next_int(s, left)?;
                 instructions.push(Instruction::Move(int as usize));
                 left = rest;
             }
@@ -333,14 +318,37 @@ fn parse_instructions(s: &str) -> Result<Vec<Instruction>, ParseIntError> {
     Ok(instructions)
 }
 
-fn parse_next_int(s: &str) -> Result<(i32, &str), ParseIntError> {
+// Errors are relative to `instructions`, which `s` is the rest of.
+fn parse_next_int<'a>(instructions: &str, s: &'a str) -> Result<(i32, &'a str), Error> {
     let (int_str, rest) = s.split_at(s.find(|c: char| !c.is_numeric()).unwrap_or(s.len()));
-    let int = int_str.parse::<i32>()?;
+    let int = int_str
+        .parse::<i32>()
+        .map_err(|_| Error::at(instructions, s, "expected a number, `L` or `R`"))?;
 
     // Return the integer and the slice to the rest of the string
     Ok((int, rest))
 }
 
+#[cfg(test)]
+mod cell_tests {
+    use super::*;
+
+    #[test]
+    fn from_char() {
+        assert_eq!(Cell::from_char('.'), Some(Cell::Space));
+        assert_eq!(Cell::from_char('#'), Some(Cell::Wall));
+        assert_eq!(Cell::from_char(' '), Some(Cell::Empty));
+        assert_eq!(Cell::from_char('x'), None);
+    }
+
+    #[test]
+    fn is_some() {
+        assert!(Cell::Space.is_some());
+        assert!(Cell::Wall.is_some());
+        assert!(!Cell::Empty.is_some());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,7 +380,7 @@ mod tests {
     #[test]
     fn test_parse_next_int() {
         let input = "123,456";
-        let (int, rest) = parse_next_int(input).unwrap();
+        let (int, rest) = parse_next_int(input, input).unwrap();
         assert_eq!(int, 123);
         assert_eq!(rest, ",456");
     }
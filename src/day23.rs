@@ -1,40 +1,34 @@
 use std::{collections::HashMap, fmt::Display, str::FromStr};
-use Direction::*;
-
-type Position = (isize, isize);
-
-#[derive(Clone, PartialEq, Eq, Debug, Hash, Copy)]
-enum Direction {
-    North,
-    NorthEast,
-    East,
-    SouthEast,
-    South,
-    SouthWest,
-    West,
-    NorthWest,
-}
 
-struct Elves {
-    elves: Vec<Position>,
-    directions_to_consider: [Direction; 4],
-    round_proposals: HashMap<Position, Direction>,
+use crate::{
+    error::Error,
+    geometry::{Direction4, Direction4::*, Direction8, Point2},
+    grid::{Grid, Position},
+    random::Rng,
+    solution::{trace, Answer, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+const ROUNDS: u32 = 10;
+
+const PALETTE: Palette = &[('#', Rgb(80, 200, 120))];
+
+#[derive(Clone)]
+pub struct Elves {
+    elves: Grid<char>,
+    directions_to_consider: [Direction4; 4],
+    round_proposals: HashMap<Position, Direction4>,
 }
 
 impl FromStr for Elves {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut elves = Vec::new();
-
-        for (x, line) in s.lines().enumerate() {
-            for (y, char) in line.trim().chars().enumerate() {
-                match char {
-                    '#' => elves.push((x as isize, y as isize)),
-                    '.' => continue,
-                    _ => return Err(()),
-                };
-            }
+        let tiles = Grid::parse(s, "`#` or `.`", |c| matches!(c, '#' | '.').then_some(c))?;
+        let mut elves = Grid::sparse();
+
+        for (position, _) in tiles.iter().filter(|(_, tile)| **tile == '#') {
+            elves.set(position, '#');
         }
 
         Ok(Self {
@@ -45,65 +39,58 @@ impl FromStr for Elves {
     }
 }
 
+// Draws the smallest rectangle with all the elves.
 impl Display for Elves {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let min_x = self.elves.iter().min_by_key(|(x, _)| *x).unwrap().0;
-        let min_y = self.elves.iter().min_by_key(|(_, y)| *y).unwrap().1;
-        let max_x = self.elves.iter().max_by_key(|(x, _)| *x).unwrap().0;
-        let max_y = self.elves.iter().max_by_key(|(_, y)| *y).unwrap().1;
-
-        let mut last_row = min_x;
-
-        for x in min_x..=max_x {
-            if x != last_row {
-                writeln!(f)?;
-                last_row = x;
-            }
-
-            for y in min_y..=max_y {
-                let pos = (x, y);
-
-                if self.elves.contains(&pos) {
-                    write!(f, "#")?;
-                } else {
-                    write!(f, ".")?;
-                }
-            }
-        }
-
-        Ok(())
+        write!(f, "{}", self.elves)
     }
 }
 
 impl Elves {
+    // The elves, following the middle of the area they spread over.
+    fn frame(&self, caption: String) -> Frame {
+        let (min, max) = self.elves.bounds().unwrap_or_default();
+        let middle = Point2::new((min.x + max.x) / 2, (min.y + max.y) / 2);
+
+        let mut frame = Frame::new(caption, PALETTE).with_focus(middle);
+        frame.draw(&self.elves, |_, elf| Some(*elf));
+        frame
+    }
+
     pub fn rotate_directions_to_consider(&mut self) {
-        let first = self.directions_to_consider[0].clone();
+        let first = self.directions_to_consider[0];
 
         for i in 0..self.directions_to_consider.len() - 1 {
-            self.directions_to_consider[i] = self.directions_to_consider[i + 1].clone();
+            self.directions_to_consider[i] = self.directions_to_consider[i + 1];
         }
 
-        self.directions_to_consider[self.directions_to_consider.len() - 1] = first.clone();
+        self.directions_to_consider[self.directions_to_consider.len() - 1] = first;
     }
 
-    pub fn perform_first_half_of_round(&mut self) {
-        for elf in &self.elves {
-            let neighbors = Self::adjacent_elves(elf);
+    // Returns the number of elves that moved.
+    pub fn perform_round(&mut self) -> u32 {
+        self.perform_first_half_of_round();
+        let moved_elves = self.perform_second_half_of_round();
+        self.rotate_directions_to_consider();
+        moved_elves
+    }
 
+    pub fn perform_first_half_of_round(&mut self) {
+        for elf in self.elves.positions() {
             // If no other Elves are in one of the neighbor positions, the Elf does not do
             // anything during this round.
-            if neighbors.iter().all(|(_, pos)| !self.elves.contains(&pos)) {
+            if self.elves.neighbors8(elf).next().is_none() {
                 continue;
             }
 
             let proposed_direction = self
                 .directions_to_consider
                 .iter()
-                .find(|dir| self.is_viable_direction(&neighbors, **dir));
+                .find(|dir| self.is_viable_direction(elf, **dir));
 
             match proposed_direction {
                 Some(dir) => {
-                    self.round_proposals.insert(*elf, *dir);
+                    self.round_proposals.insert(elf, *dir);
                 }
 
                 None => {
@@ -117,18 +104,7 @@ impl Elves {
         let mut proposed_positions: HashMap<Position, Vec<Position>> = HashMap::new();
 
         for (elf, direction) in &self.round_proposals {
-            let (x, y) = elf.clone();
-
-            let new_pos = match direction {
-                North => (x - 1, y),
-                NorthEast => (x - 1, y + 1),
-                East => (x, y + 1),
-                SouthEast => (x + 1, y + 1),
-                South => (x + 1, y),
-                SouthWest => (x + 1, y - 1),
-                West => (x, y - 1),
-                NorthWest => (x - 1, y - 1),
-            };
+            let new_pos = *elf + direction.step();
 
             match proposed_positions.get_mut(&new_pos) {
                 Some(elves) => {
@@ -148,9 +124,8 @@ impl Elves {
                 continue;
             }
 
-            let elf = elves[0];
-            let elf_index = self.elves.iter().position(|e| *e == elf).unwrap();
-            self.elves[elf_index] = *new_pos;
+            self.elves.remove(elves[0]);
+            self.elves.set(*new_pos, '#');
             moved_elves += 1;
         }
 
@@ -159,77 +134,87 @@ impl Elves {
     }
 
     pub fn empty_tiles(&self) -> u32 {
-        let min_x = self.elves.iter().min_by_key(|(x, _)| *x).unwrap().0;
-        let min_y = self.elves.iter().min_by_key(|(_, y)| *y).unwrap().1;
-        let max_x = self.elves.iter().max_by_key(|(x, _)| *x).unwrap().0;
-        let max_y = self.elves.iter().max_by_key(|(_, y)| *y).unwrap().1;
+        let (rows, columns) = self.elves.size();
 
-        let mut total = 0;
+        (rows * columns - self.elves.len()) as u32
+    }
 
-        for x in min_x..=max_x {
-            for y in min_y..=max_y {
-                let pos = (x, y);
+    // An elf can move in a direction when there is no other elf there, nor diagonally on either
+    // side of it.
+    fn is_viable_direction(&self, elf: Position, direction: Direction4) -> bool {
+        let direction = Direction8::from(direction);
 
-                if !self.elves.contains(&pos) {
-                    total += 1;
-                }
-            }
+        [direction, direction.turn_left(), direction.turn_right()]
+            .iter()
+            .all(|direction| !self.elves.contains(elf + direction.step()))
+    }
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Elves;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse::<Elves>()
+    }
+
+    fn part1(elves: &Self::Input) -> Answer {
+        let mut elves = elves.clone();
+
+        for round in 1..=ROUNDS {
+            elves.perform_round();
+            visualize::emit(|| elves.frame(format!("Part 1: round {}", round)));
         }
 
-        total
+        elves.empty_tiles().into()
     }
 
-    fn adjacent_elves(elf: &Position) -> HashMap<Direction, Position> {
-        let (x, y) = elf.clone();
-
-        HashMap::from([
-            (North, (x - 1, y)),
-            (NorthEast, (x - 1, y + 1)),
-            (East, (x, y + 1)),
-            (SouthEast, (x + 1, y + 1)),
-            (South, (x + 1, y)),
-            (SouthWest, (x + 1, y - 1)),
-            (West, (x, y - 1)),
-            (NorthWest, (x - 1, y - 1)),
-        ])
+    // The number of the first round where no elf moves.
+    fn part2(elves: &Self::Input) -> Answer {
+        let mut elves = elves.clone();
+
+        for round in 1_u32.. {
+            trace!("Round {round}...");
+
+            let moved_elves = elves.perform_round();
+            visualize::emit(|| {
+                elves.frame(format!(
+                    "Part 2: round {}, {} elves moved",
+                    round, moved_elves
+                ))
+            });
+
+            if moved_elves == 0 {
+                return round.into();
+            }
+        }
+
+        unreachable!()
     }
 
-    fn is_viable_direction(
-        &self,
-        neighbors: &HashMap<Direction, Position>,
-        direction: Direction,
-    ) -> bool {
-        let three_neighbors = match direction {
-            North => [
-                neighbors[&North],
-                neighbors[&NorthEast],
-                neighbors[&NorthWest],
-            ],
-            South => [
-                neighbors[&South],
-                neighbors[&SouthEast],
-                neighbors[&SouthWest],
-            ],
-            West => [
-                neighbors[&West],
-                neighbors[&NorthWest],
-                neighbors[&SouthWest],
-            ],
-            East => [
-                neighbors[&East],
-                neighbors[&NorthEast],
-                neighbors[&SouthEast],
-            ],
-            _ => panic!("Invalid direction: {:?}", direction),
-        };
-
-        three_neighbors.iter().all(|pos| !self.elves.contains(pos))
+    // A grove of `size` by `size` tiles, with elves on about half of them.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let size = size.max(1);
+        let mut input = String::new();
+
+        for row in 0..size {
+            for column in 0..size {
+                let elf = rng.chance(0.5) || (row, column) == (0, 0);
+                input.push(if elf { '#' } else { '.' });
+            }
+
+            input.push('\n');
+        }
+
+        Some(input)
     }
 }
 
 #[cfg(test)]
 mod elves_tests {
     use super::*;
+    use crate::geometry::Point2;
 
     #[test]
     fn test_rotate_directions_to_consider() {
@@ -246,7 +231,12 @@ mod elves_tests {
     fn test_from_str() {
         let input = ".#.\n..#\n#..";
         let elves = Elves::from_str(input.trim()).unwrap();
-        assert_eq!(elves.elves, vec![(0, 1), (1, 2), (2, 0)]);
+        let mut positions = elves.elves.positions().collect::<Vec<Position>>();
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![Point2::new(0, 2), Point2::new(1, 0), Point2::new(2, 1)]
+        );
 
         assert_eq!(format!("{}", elves), input);
     }
@@ -254,18 +244,3 @@ mod elves_tests {
     #[test]
     fn test_display() {}
 }
-
-pub fn run(input: &str) {
-    let mut elves = input.parse::<Elves>().unwrap();
-
-    for round in 1.. {
-        println!("Round {round}...");
-        elves.perform_first_half_of_round();
-        if elves.perform_second_half_of_round() == 0 {
-            break;
-        }
-        elves.rotate_directions_to_consider();
-    }
-
-    println!("The number of empty tiles is {}", elves.empty_tiles());
-}
@@ -1 +1,52 @@
-pub fn run(_input: &str) {}
+use crate::{
+    error::Error,
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = ();
+
+    fn parse(_input: &str) -> Result<Self::Input, Error> {
+        Ok(())
+    }
+
+    fn part1(_input: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+
+    fn part2(_input: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+
+    // A valley of `size` rows by 3 times `size` columns (at least 2 rows), with blizzards on a
+    // third of its tiles, none of them going up or down in the columns of the entrance and exit.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let (rows, columns) = (size.max(2), 3 * size.max(2));
+        let mut input = format!("#.{}\n", "#".repeat(columns));
+
+        for _ in 0..rows {
+            input.push('#');
+
+            for column in 0..columns {
+                let blizzards: &[char] = match column {
+                    0 => &['<', '>'],
+                    column if column == columns - 1 => &['<', '>'],
+                    _ => &['<', '>', '^', 'v'],
+                };
+
+                input.push(match rng.chance(0.33) {
+                    true => *rng.choose(blizzards),
+                    false => '.',
+                });
+            }
+
+            input += "#\n";
+        }
+
+        input += &format!("{}.#\n", "#".repeat(columns));
+        Some(input)
+    }
+}
@@ -1,18 +1,28 @@
 use std::str::FromStr;
 
-struct SNAFU {
+use crate::{
+    error::{parse_lines, Error},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct SNAFU {
     digits: Vec<char>,
 }
 
 impl SNAFU {
-    fn to_int(&self) -> i128 {
-        let mut total = 0;
-
-        for (index, digit) in self.digits.iter().enumerate() {
-            total += Self::digit_value(*digit) as i128 * 5_i128.pow(index as u32);
-        }
+    pub fn to_int(&self) -> i128 {
+        Self::checked_int(&self.digits).expect("SNAFU numbers fit in an i128")
+    }
 
-        total
+    // The value of the digits (least significant first), or None if it doesn't fit in an i128.
+    fn checked_int(digits: &[char]) -> Option<i128> {
+        digits.iter().rev().try_fold(0_i128, |total, digit| {
+            total
+                .checked_mul(5)?
+                .checked_add(Self::digit_value(*digit) as i128)
+        })
     }
 
     pub fn digit_value(digit: char) -> i16 {
@@ -45,7 +55,7 @@ impl TryFrom<i128> for SNAFU {
         let mut digits = Vec::new();
 
         loop {
-            let c = match acc % 5 {
+            let c = match acc.rem_euclid(5) {
                 0 => '0',
                 1 => '1',
                 2 => '2',
@@ -56,7 +66,10 @@ impl TryFrom<i128> for SNAFU {
 
             digits.push(c);
 
-            acc = (acc - Self::digit_value(c) as i128) / 5;
+            acc = acc
+                .checked_sub(Self::digit_value(c) as i128)
+                .ok_or("out of range of SNAFU numbers")?
+                / 5;
 
             if acc == 0 {
                 break;
@@ -68,24 +81,76 @@ impl TryFrom<i128> for SNAFU {
 }
 
 impl FromStr for SNAFU {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut digits = Vec::new();
+        let trimmed = s.trim();
 
-        for char in s.trim().chars().rev() {
+        for (index, char) in trimmed.char_indices().rev() {
             match char {
                 '2' | '1' | '0' | '-' | '=' => digits.push(char),
-                _ => return Err(()),
+                _ => {
+                    return Err(Error::at(
+                        s,
+                        &trimmed[index..],
+                        format!("invalid digit `{}`, expected 0, 1, 2, - or =", char),
+                    ))
+                }
             };
         }
 
+        if Self::checked_int(&digits).is_none() {
+            return Err(Error::at(s, trimmed, "SNAFU number out of range"));
+        }
+
         Ok(Self { digits })
     }
 }
 
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<SNAFU>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, SNAFU::from_str)
+    }
+
+    fn part1(snafus: &Self::Input) -> Answer {
+        let sum_in_snafu: SNAFU = snafus
+            .iter()
+            .map(SNAFU::to_int)
+            .sum::<i128>()
+            .try_into()
+            .unwrap();
+
+        sum_in_snafu.to_string().into()
+    }
+
+    // Day 25 has no second puzzle.
+    fn part2(_snafus: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+
+    // `size` SNAFU numbers, of up to 20 digits.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let numbers = (0..size.max(1))
+            .map(|_| {
+                let digits = rng.range(1..20) as u32;
+                let number = rng.range(1..5_i64.pow(digits)) as i128;
+                format!("{}\n", SNAFU::try_from(number).unwrap())
+            })
+            .collect();
+
+        Some(numbers)
+    }
+}
+
 #[cfg(test)]
 mod snafu_tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     const TABLE: [(&str, i128); 27] = [
@@ -141,15 +206,42 @@ mod snafu_tests {
             assert_eq!(snafu.to_string(), *snafu_str);
         }
     }
-}
 
-pub fn run(input: &str) {
-    let sum_in_snafu: SNAFU = input
-        .lines()
-        .map(|line| line.parse::<SNAFU>().unwrap().to_int())
-        .sum::<i128>()
-        .try_into()
-        .unwrap();
+    #[test]
+    fn test_negative_and_out_of_range() {
+        assert_eq!(SNAFU::try_from(-3).unwrap().to_string(), "-2");
+        assert_eq!(SNAFU::try_from(i128::MAX).unwrap().to_int(), i128::MAX);
+        assert_eq!(
+            SNAFU::try_from(i128::MIN + 1).unwrap().to_int(),
+            i128::MIN + 1
+        );
+        assert!(SNAFU::try_from(i128::MIN).is_err());
+
+        assert_eq!("-2".parse::<SNAFU>().unwrap().to_int(), -3);
+        assert_eq!("0".repeat(100).parse::<SNAFU>().unwrap().to_int(), 0);
+        assert!("2".repeat(56).parse::<SNAFU>().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn numbers_round_trip(number in (i128::MIN + 1)..=i128::MAX) {
+            let snafu = SNAFU::try_from(number).unwrap();
 
-    println!("Sum of all SNAFUs (in SNAFU): {}", sum_in_snafu);
+            prop_assert_eq!(snafu.to_int(), number);
+            prop_assert_eq!(snafu.to_string().parse::<SNAFU>().unwrap().to_int(), number);
+        }
+
+        #[test]
+        fn small_numbers_round_trip(number in -100_000_i128..100_000) {
+            prop_assert_eq!(SNAFU::try_from(number).unwrap().to_int(), number);
+        }
+
+        // Without leading zeros, every string of digits is the only way to write its number.
+        #[test]
+        fn digits_round_trip(digits in "[12=-][012=-]{0,25}|0") {
+            let number = digits.parse::<SNAFU>().unwrap().to_int();
+
+            prop_assert_eq!(SNAFU::try_from(number).unwrap().to_string(), digits);
+        }
+    }
 }
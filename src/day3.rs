@@ -1,30 +1,150 @@
 use std::collections::HashSet;
 
-pub fn run(input: &str) {
-    let lines = input.lines().collect::<Vec<&str>>();
-    let chunks = lines.chunks(3).collect::<Vec<&[&str]>>();
+use crate::{
+    error::{parse_lines, Error},
+    random::Rng,
+    solution::{Answer, Solution},
+};
 
-    let mut total: i32 = 0;
+pub struct Day3;
 
-    for chunk in chunks {
-        let (line1, line2, line3) = (chunk[0], chunk[1], chunk[2]);
+impl Solution for Day3 {
+    type Input = Vec<String>;
 
-        let set1: HashSet<char> = HashSet::from_iter(line1.chars());
-        let set2: HashSet<char> = HashSet::from_iter(line2.chars());
-        let set3: HashSet<char> = HashSet::from_iter(line3.chars());
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let rucksacks = parse_lines(input, |line| {
+            match line.find(|c: char| !c.is_ascii_alphabetic()) {
+                Some(index) => Err(Error::at(
+                    line,
+                    &line[index..],
+                    "expected an item (a letter)",
+                )),
+                None if common_item(line).is_none() => Err(Error::at(
+                    line,
+                    line,
+                    "expected an item in both compartments",
+                )),
+                None => Ok(line.to_string()),
+            }
+        })?;
 
-        let common: char = set1
-            .intersection(&set2)
-            .map(|c| *c)
-            .collect::<HashSet<char>>()
-            .intersection(&set3)
-            .map(|c| *c)
-            .collect::<Vec<char>>()[0];
+        // The rucksacks are the lines of the input, so a group starts at a line of it.
+        for (group, first_line) in rucksacks.chunks(3).zip(input.lines().step_by(3)) {
+            if group.len() < 3 {
+                return Err(Error::at(
+                    input,
+                    first_line,
+                    "expected a group of three elves",
+                ));
+            }
 
-        total = total + priority(common);
+            if badge(group).is_none() {
+                return Err(Error::at(
+                    input,
+                    first_line,
+                    "expected a badge for the group",
+                ));
+            }
+        }
+
+        Ok(rucksacks)
+    }
+
+    // Each rucksack has two compartments, one per half of the line.
+    fn part1(rucksacks: &Self::Input) -> Answer {
+        rucksacks
+            .iter()
+            .map(|rucksack| priority(common_item(rucksack).unwrap()))
+            .sum::<i32>()
+            .into()
+    }
+
+    // Groups of three elves share a badge.
+    fn part2(rucksacks: &Self::Input) -> Answer {
+        rucksacks
+            .chunks(3)
+            .map(|group| priority(badge(group).unwrap()))
+            .sum::<i32>()
+            .into()
+    }
+
+    // `size` groups of three elves. Each rucksack has exactly one item in both compartments, and
+    // each group exactly one badge.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let items = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+        let mut input = String::new();
+
+        for _ in 0..size.max(1) {
+            let badge = *rng.choose(&items);
+            let mut group: Vec<HashSet<char>> = Vec::new();
+
+            for elf in 0..3 {
+                // The last elf has none of the other items that the first two share.
+                let shared = match elf {
+                    2 => &group[0] & &group[1],
+                    _ => HashSet::new(),
+                };
+
+                let mut others = items
+                    .iter()
+                    .copied()
+                    .filter(|item| *item != badge && !shared.contains(item))
+                    .collect::<Vec<char>>();
+                rng.shuffle(&mut others);
+
+                let half = rng.range(2..12) as usize;
+                let common = if rng.chance(0.2) { badge } else { others[0] };
+                let mut compartment1 = others[1..half].to_vec();
+                let mut compartment2 = others[half..2 * half - 1].to_vec();
+
+                compartment1.push(common);
+                compartment2.push(common);
+
+                if common != badge {
+                    match rng.chance(0.5) {
+                        true => compartment1[0] = badge,
+                        false => compartment2[0] = badge,
+                    }
+                }
+
+                rng.shuffle(&mut compartment1);
+                rng.shuffle(&mut compartment2);
+
+                let rucksack = compartment1.into_iter().chain(compartment2);
+                group.push(rucksack.clone().collect());
+                input.extend(rucksack);
+                input.push('\n');
+            }
+        }
+
+        Some(input)
     }
+}
+
+// The item in both halves of the rucksack, if any.
+fn common_item(rucksack: &str) -> Option<char> {
+    let (compartment1, compartment2) = rucksack.split_at(rucksack.len() / 2);
+
+    let set1: HashSet<char> = HashSet::from_iter(compartment1.chars());
+    let set2: HashSet<char> = HashSet::from_iter(compartment2.chars());
+
+    set1.intersection(&set2).next().copied()
+}
 
-    println!("{:?}", total);
+// The item in the three rucksacks of the group, if any.
+fn badge(group: &[String]) -> Option<char> {
+    let (line1, line2, line3) = (&group[0], &group[1], &group[2]);
+
+    let set1: HashSet<char> = HashSet::from_iter(line1.chars());
+    let set2: HashSet<char> = HashSet::from_iter(line2.chars());
+    let set3: HashSet<char> = HashSet::from_iter(line3.chars());
+
+    set1.intersection(&set2)
+        .copied()
+        .collect::<HashSet<char>>()
+        .intersection(&set3)
+        .next()
+        .copied()
 }
 
 fn priority(c: char) -> i32 {
@@ -36,3 +156,26 @@ fn priority(c: char) -> i32 {
         ascii_value - ('A' as i32) + 27
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| Day3::parse(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("abca\nab"),
+            "line 2 col 1: expected an item in both compartments"
+        );
+        assert_eq!(
+            error("aa\nbb\ncc"),
+            "line 1 col 1: expected a badge for the group"
+        );
+        assert_eq!(
+            error("aa\naa\naa\nbb"),
+            "line 4 col 1: expected a group of three elves"
+        );
+    }
+}
@@ -1,57 +1,152 @@
-use std::ops::Range;
+use std::{collections::HashSet, ops::Range};
 
-pub fn run(input: &str) {
-    let mut count: u32 = 0;
+use crate::{
+    error::{parse_lines, parse_number, split_once, Error},
+    interval::IntervalSet,
+    random::Rng,
+    solution::{Answer, Solution},
+};
 
-    for line in input.lines() {
-        let (left, right) = line.split_once(",").unwrap();
+pub struct Day4;
 
-        let left_range = parse_into_range(left);
-        let right_range = parse_into_range(right);
+impl Solution for Day4 {
+    type Input = Vec<(Range<i64>, Range<i64>)>;
 
-        if is_overlapping(&left_range, &right_range) {
-            count += 1;
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| {
+            let (left, right) = split_once(line, line, ",")?;
+            Ok((
+                parse_into_range(line, left)?,
+                parse_into_range(line, right)?,
+            ))
+        })
+    }
+
+    fn part1(pairs: &Self::Input) -> Answer {
+        pairs
+            .iter()
+            .filter(|(left, right)| is_containing(left, right))
+            .count()
+            .into()
+    }
+
+    fn part2(pairs: &Self::Input) -> Answer {
+        pairs
+            .iter()
+            .filter(|(left, right)| is_overlapping(left, right))
+            .count()
+            .into()
+    }
+
+    // `size` pairs of elves.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut input = String::new();
+
+        for _ in 0..size.max(1) {
+            let [start1, start2] = [rng.range(1..99), rng.range(1..99)];
+            let (end1, end2) = (rng.range(start1..100), rng.range(start2..100));
+            input += &format!("{}-{},{}-{}\n", start1, end1, start2, end2);
         }
+
+        Some(input)
     }
 
-    println!("Day 4: {}", count);
+    // Compares the sets of sections, one section at a time.
+    fn reference(pairs: &Self::Input) -> Option<(Answer, Answer)> {
+        let sections = |range: &Range<i64>| range.clone().collect::<HashSet<i64>>();
+        let (mut containing, mut overlapping) = (0, 0);
+
+        for (left, right) in pairs {
+            let (left, right) = (sections(left), sections(right));
+
+            if left.is_subset(&right) || right.is_subset(&left) {
+                containing += 1;
+            }
+
+            if !left.is_disjoint(&right) {
+                overlapping += 1;
+            }
+        }
+
+        Some((containing.into(), overlapping.into()))
+    }
 }
 
-fn parse_into_range(string: &str) -> Range<u32> {
-    let (left, right) = string.split_once("-").unwrap();
-    let start = left.parse::<u32>().unwrap();
-    let end = right.parse::<u32>().unwrap();
-    start..end
+// The sections of an assignment like "2-4", as a half-open range.
+fn parse_into_range(line: &str, string: &str) -> Result<Range<i64>, Error> {
+    let (left, right) = split_once(line, string, "-")?;
+    let start = parse_number::<u32>(line, left)?;
+    let end = parse_number::<u32>(line, right)?;
+    Ok(i64::from(start)..i64::from(end) + 1)
 }
 
 // Returns true if left and right overlap.
-fn is_overlapping(left: &Range<u32>, right: &Range<u32>) -> bool {
-    let (min, max) = if left.start < right.start {
-        (left, right)
-    } else {
-        (right, left)
-    };
-
-    min.end >= max.start
+fn is_overlapping(left: &Range<i64>, right: &Range<i64>) -> bool {
+    !IntervalSet::from(left.clone()).is_disjoint(&IntervalSet::from(right.clone()))
+}
+
+// Returns true if one of left and right fully contains the other.
+fn is_containing(left: &Range<i64>, right: &Range<i64>) -> bool {
+    let (left, right) = (
+        IntervalSet::from(left.clone()),
+        IntervalSet::from(right.clone()),
+    );
+    left.is_superset(&right) || right.is_superset(&left)
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
     fn test_parse_into_range() {
-        assert_eq!(parse_into_range("1-2"), 1..2);
-        assert_eq!(parse_into_range("1-3"), 1..3);
-        assert_eq!(parse_into_range("2-3"), 2..3);
+        assert_eq!(parse_into_range("1-2", "1-2").unwrap(), 1..3);
+        assert_eq!(parse_into_range("1-3", "1-3").unwrap(), 1..4);
+        assert_eq!(parse_into_range("2-3", "2-3").unwrap(), 2..4);
     }
 
     #[test]
     fn test_is_overlapping() {
-        assert!(is_overlapping(&(1..3), &(2..4)));
-        assert!(is_overlapping(&(1..3), &(3..4)));
-        assert!(is_overlapping(&(3..4), &(1..3)));
-        assert!(!is_overlapping(&(1..3), &(4..6)));
-        assert!(!is_overlapping(&(4..6), &(1..3)));
+        assert!(is_overlapping(&(1..4), &(2..5)));
+        assert!(is_overlapping(&(1..4), &(3..5)));
+        assert!(is_overlapping(&(3..5), &(1..4)));
+        assert!(!is_overlapping(&(1..4), &(4..7)));
+        assert!(!is_overlapping(&(4..7), &(1..4)));
+    }
+
+    #[test]
+    fn test_is_containing() {
+        assert!(is_containing(&(2..9), &(3..8)));
+        assert!(is_containing(&(6..7), &(4..7)));
+        assert!(is_containing(&(1..4), &(1..4)));
+        assert!(!is_containing(&(1..4), &(2..5)));
+        assert!(!is_containing(&(5..8), &(7..10)));
+    }
+
+    // Section assignments like the input's, which are never empty.
+    fn sections() -> impl Strategy<Value = Range<i64>> {
+        (1_i64..30, 0_i64..10).prop_map(|(start, len)| start..start + len + 1)
+    }
+
+    proptest! {
+        #[test]
+        fn overlapping_is_symmetric(left in sections(), right in sections()) {
+            prop_assert_eq!(is_overlapping(&left, &right), is_overlapping(&right, &left));
+            prop_assert_eq!(is_containing(&left, &right), is_containing(&right, &left));
+        }
+
+        #[test]
+        fn overlapping_means_a_shared_section(left in sections(), right in sections()) {
+            let shared = left.clone().any(|section| right.contains(&section));
+
+            prop_assert_eq!(is_overlapping(&left, &right), shared);
+            prop_assert!(is_overlapping(&left, &left));
+
+            if is_containing(&left, &right) {
+                prop_assert!(is_overlapping(&left, &right));
+            }
+        }
     }
 }
@@ -1,51 +1,127 @@
+use crate::{
+    error::{parse_lines, split_once, Error},
+    parse::{columns, Pattern},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
+static CRATE: Pattern = Pattern::new("[{crate}]");
+static MOVE: Pattern = Pattern::new("move {crates} from {from} to {to}");
+
 #[derive(Debug, PartialEq)]
-struct Move {
+pub struct Move {
     start_stack: u16,
     end_stack: u16,
     crates_to_move: u16,
 }
 
-#[derive(Debug)]
-struct Stack {
+#[derive(Debug, Clone)]
+pub struct Stack {
     // Crates are ordered from bottm to top (that is, the top crate is the last crate in the vector)
     crates: Vec<char>,
 }
 
-pub fn run(input: &str) {
-    let mut world = Vec::new();
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = (Vec<Stack>, Vec<Move>);
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let (drawing, procedure) = split_once(input, input, "\n\n")?;
+
+        let world = parse_stacks(drawing).map_err(|error| error.within(input, drawing))?;
+        let moves = parse_lines(procedure, parse_move)
+            .and_then(|moves| check_moves(&world, procedure, &moves).map(|_| moves))
+            .map_err(|error| error.within(input, procedure))?;
+
+        Ok((world, moves))
+    }
+
+    // The CrateMover 9000 moves one crate at a time.
+    fn part1((world, moves): &Self::Input) -> Answer {
+        let mut world = world.clone();
+
+        for move_ in moves {
+            move_crates(&mut world, move_);
+        }
+
+        top_crates(&world)
+    }
+
+    // The CrateMover 9001 moves all the crates at once.
+    fn part2((world, moves): &Self::Input) -> Answer {
+        let mut world = world.clone();
+
+        for move_ in moves {
+            move_crates_9001(&mut world, move_);
+        }
 
-    for column_index in 0..count_stacks(input) {
-        let stack = parse_stack(input, column_index as usize);
-        world.push(stack);
+        top_crates(&world)
     }
 
-    assert_eq!(world.len(), 9);
-    assert_eq!(world[0].crates, vec!['W', 'D', 'G', 'B', 'H', 'R', 'V']);
-
-    let moves: Vec<Move> = input
-        .lines()
-        .filter(|line| line.starts_with("move"))
-        .map(parse_move)
-        .collect();
-
-    assert_eq!(
-        moves[0],
-        Move {
-            start_stack: 2,
-            end_stack: 7,
-            crates_to_move: 2
+    // `size` stacks (from 2 to 9), and 5 moves per stack. No stack is ever emptied.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut stacks = (0..size.clamp(2, 9))
+            .map(|_| {
+                (0..rng.range(1..9))
+                    .map(|_| (b'A' + rng.below(26) as u8) as char)
+                    .collect::<Vec<char>>()
+            })
+            .collect::<Vec<Vec<char>>>();
+
+        let height = stacks.iter().map(Vec::len).max().unwrap();
+        let mut input = String::new();
+
+        for level in (0..height).rev() {
+            let line = stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(crate_) => format!("[{}]", crate_),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            input += line.trim_end();
+            input.push('\n');
+        }
+
+        let numbers = (1..=stacks.len())
+            .map(|number| format!(" {} ", number))
+            .collect::<Vec<String>>();
+        input += &format!("{}\n\n", numbers.join(" "));
+
+        for _ in 0..stacks.len() * 5 {
+            let from = (0..stacks.len())
+                .filter(|stack| stacks[*stack].len() > 1)
+                .collect::<Vec<usize>>();
+
+            if from.is_empty() {
+                break;
+            }
+
+            let from = *rng.choose(&from);
+            let to = (from + rng.range(1..stacks.len() as i64) as usize) % stacks.len();
+            let count = rng.range(1..stacks[from].len() as i64) as usize;
+
+            let rest = stacks[from].len() - count;
+            let crates = stacks[from].split_off(rest);
+            stacks[to].extend(crates);
+
+            input += &format!("move {} from {} to {}\n", count, from + 1, to + 1);
         }
-    );
 
-    for move_ in moves {
-        move_crates_9001(&mut world, move_);
+        Some(input)
     }
+}
 
-    let top_chars_iter = world.iter().map(|stack| stack.crates.last().unwrap());
-    println!("{}", String::from_iter(top_chars_iter));
+// Unsolved if a stack is empty.
+fn top_crates(world: &[Stack]) -> Answer {
+    let top_chars_iter = world.iter().map(|stack| stack.crates.last());
+    Option::<String>::from_iter(top_chars_iter).map_or(Answer::Unsolved, Answer::from)
 }
 
-fn move_crates_9001(world: &mut Vec<Stack>, move_: Move) {
+fn move_crates_9001(world: &mut [Stack], move_: &Move) {
     let start_stack = &mut world[(move_.start_stack - 1) as usize];
     let to_move = pop_many(&mut start_stack.crates, move_.crates_to_move);
     let end_stack = &mut world[(move_.end_stack - 1) as usize];
@@ -60,7 +136,7 @@ fn pop_many<T>(vec: &mut Vec<T>, count: u16) -> Vec<T> {
     popped
 }
 
-fn _move_crates(world: &mut Vec<Stack>, move_: Move) {
+fn move_crates(world: &mut [Stack], move_: &Move) {
     for _ in 0..move_.crates_to_move {
         let start_stack = &mut world[(move_.start_stack - 1) as usize];
         let crate_to_move = start_stack.crates.pop().unwrap();
@@ -69,53 +145,152 @@ fn _move_crates(world: &mut Vec<Stack>, move_: Move) {
     }
 }
 
-fn count_stacks(input: &str) -> u16 {
-    let indexes_line = input
-        .lines()
-        .find(|line| line.trim().starts_with("1"))
-        .unwrap();
-
-    indexes_line
-        .split_ascii_whitespace()
-        .count()
-        .try_into()
-        .unwrap()
+// The drawing of the stacks has a column of 4 characters for each stack, with the crates from
+// top to bottom, and the stack numbers on the last line.
+fn parse_stacks(drawing: &str) -> Result<Vec<Stack>, Error> {
+    let (crates, numbers) = drawing
+        .rsplit_once('\n')
+        .ok_or_else(|| Error::new("expected a line with the stack numbers"))?;
+
+    let mut stacks = vec![Stack { crates: Vec::new() }; numbers.split_ascii_whitespace().count()];
+
+    for line in crates.lines().rev() {
+        for (index, column) in columns(line, 4).enumerate() {
+            // Lines are shorter than the others, or have spaces, where there are no crates.
+            if column.trim().is_empty() {
+                continue;
+            }
+
+            let crate_ = parse_crate(column).map_err(|error| error.within(drawing, column))?;
+            let stack = stacks
+                .get_mut(index)
+                .ok_or_else(|| Error::at(drawing, column.trim(), "crate outside of the stacks"))?;
+
+            stack.crates.push(crate_);
+        }
+    }
+
+    Ok(stacks)
 }
 
-fn parse_stack(input: &str, column_index: usize) -> Stack {
-    let mut crates: Vec<char> = Vec::new();
+// Checks that the moves are between stacks of the drawing, and never take more crates than a
+// stack has. `text` is the lines of the moves.
+fn check_moves(world: &[Stack], text: &str, moves: &[Move]) -> Result<(), Error> {
+    let mut heights = world
+        .iter()
+        .map(|stack| stack.crates.len())
+        .collect::<Vec<usize>>();
 
-    for line in input.lines().take_while(|line| !line.trim().is_empty()) {
-        let range = (column_index * 4)..(column_index * 4 + 2);
-        let cleaned_line = line[range].trim();
+    for (line, move_) in text.lines().zip(moves) {
+        let captures = MOVE.parse(line)?;
+        let stack = |field: &str, number: u16| match usize::from(number) {
+            index @ 1.. if index <= heights.len() => Ok(index - 1),
+            _ => Err(Error::at(
+                text,
+                captures.get(field),
+                format!("no stack {}", number),
+            )),
+        };
 
-        if cleaned_line.is_empty() {
-            continue;
-        } else if cleaned_line.starts_with("[") {
-            let char = cleaned_line
-                .trim_start_matches("[")
-                .trim_end_matches("]")
-                .chars()
-                .next()
-                .unwrap();
+        let (from, to) = (
+            stack("from", move_.start_stack)?,
+            stack("to", move_.end_stack)?,
+        );
+        let crates = usize::from(move_.crates_to_move);
 
-            crates.push(char);
+        if crates > heights[from] {
+            return Err(Error::at(
+                text,
+                captures.get("crates"),
+                format!(
+                    "stack {} only has {} crates",
+                    move_.start_stack, heights[from]
+                ),
+            ));
         }
+
+        heights[from] -= crates;
+        heights[to] += crates;
+    }
+
+    Ok(())
+}
+
+fn parse_crate(column: &str) -> Result<char, Error> {
+    let name = CRATE.parse(column)?.get("crate");
+    let mut chars = name.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(name), None) => Ok(name),
+        _ => Err(Error::at(
+            column,
+            column.trim(),
+            "expected a crate like `[A]`",
+        )),
     }
+}
+
+fn parse_move(line: &str) -> Result<Move, Error> {
+    let move_ = MOVE.parse(line)?;
 
-    crates.reverse();
-    Stack { crates }
+    Ok(Move {
+        start_stack: move_.number("from")?,
+        end_stack: move_.number("to")?,
+        crates_to_move: move_.number("crates")?,
+    })
 }
 
-fn parse_move(string: &str) -> Move {
-    let words: Vec<&str> = string.split_ascii_whitespace().collect();
-    let crates_to_move = words[1].parse::<u16>().unwrap();
-    let start_stack = words[3].parse::<u16>().unwrap();
-    let end_stack = words[5].parse::<u16>().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n";
+        let (world, moves) = Day5::parse(input).unwrap();
+
+        assert_eq!(world[0].crates, vec!['Z', 'N']);
+        assert_eq!(world[1].crates, vec!['M', 'C', 'D']);
+        assert_eq!(world[2].crates, vec!['P']);
+        assert_eq!(
+            moves,
+            vec![Move {
+                start_stack: 2,
+                end_stack: 1,
+                crates_to_move: 1
+            }]
+        );
+
+        let input = "[Z] [MM]\n 1   2\n\nmove 1 from 2 to 1";
+        assert_eq!(
+            Day5::parse(input).unwrap_err().to_string(),
+            "line 1 col 5: expected a crate like `[A]`"
+        );
+
+        let input = "[Z] [M]\n 1   2\n\nmove 1 from 2 to 1\nmove 1 to 2";
+        assert_eq!(
+            Day5::parse(input).unwrap_err().to_string(),
+            "line 5 col 8: expected `from`"
+        );
+
+        let error = |moves| {
+            Day5::parse(&format!("[Z] [M]\n 1   2\n\n{}", moves))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(error("move 1 from 0 to 1"), "line 4 col 13: no stack 0");
+        assert_eq!(error("move 1 from 1 to 3"), "line 4 col 18: no stack 3");
+        assert_eq!(
+            error("move 1 from 1 to 2\nmove 3 from 2 to 1"),
+            "line 5 col 6: stack 2 only has 2 crates"
+        );
+    }
+
+    #[test]
+    fn test_empty_stack() {
+        let stacks = Day5::parse("[Z] [M]\n 1   2\n\nmove 1 from 1 to 2").unwrap();
 
-    Move {
-        start_stack,
-        end_stack,
-        crates_to_move,
+        assert_eq!(Day5::part1(&stacks), Answer::Unsolved);
+        assert_eq!(Day5::part2(&stacks), Answer::Unsolved);
     }
 }
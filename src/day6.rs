@@ -1,24 +1,85 @@
-const MARKER_LENGTH: usize = 14;
-
-pub fn run(input: &str) {
-    let mut seen_chars = input.chars().collect::<Vec<char>>();
-    let chars = seen_chars.drain(MARKER_LENGTH..).collect::<Vec<char>>();
-    let mut counted_chars = MARKER_LENGTH;
-
-    for char in chars {
-        if all_chars_are_different(&seen_chars) {
-            println!("{:?}", counted_chars);
-            return;
-        } else {
-            seen_chars.remove(0);
-            seen_chars.push(char);
-            counted_chars += 1;
+use crate::{
+    error::Error,
+    random::Rng,
+    solution::{Answer, Param, Solution},
+};
+
+const PACKET_MARKER_LENGTH: Param = Param {
+    name: "packet_marker_length",
+    default: 4,
+    min: 1,
+    max: 1000,
+    description: "Different characters in a start-of-packet marker (part 1)",
+};
+
+const MESSAGE_MARKER_LENGTH: Param = Param {
+    name: "message_marker_length",
+    default: 14,
+    min: 1,
+    max: 1000,
+    description: "Different characters in a start-of-message marker (part 2)",
+};
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<char>;
+
+    const PARAMS: &'static [Param] = &[PACKET_MARKER_LENGTH, MESSAGE_MARKER_LENGTH];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.trim().chars().collect())
+    }
+
+    fn part1(chars: &Self::Input) -> Answer {
+        find_marker(chars, PACKET_MARKER_LENGTH.value()).map_or(Answer::Unsolved, Answer::from)
+    }
+
+    fn part2(chars: &Self::Input) -> Answer {
+        find_marker(chars, MESSAGE_MARKER_LENGTH.value()).map_or(Answer::Unsolved, Answer::from)
+    }
+
+    // About `size` characters before each marker. The characters before the markers are only
+    // three different letters, so that they have no marker of their own.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut letters = ('a'..='z').collect::<Vec<char>>();
+        rng.shuffle(&mut letters);
+
+        let filler = |rng: &mut Rng, letters: &[char]| {
+            (0..size.max(1))
+                .map(|_| *rng.choose(letters))
+                .collect::<String>()
+        };
+
+        let mut input = filler(rng, &letters[..3]);
+
+        for marker_length in [
+            PACKET_MARKER_LENGTH.value::<usize>(),
+            MESSAGE_MARKER_LENGTH.value(),
+        ] {
+            rng.shuffle(&mut letters);
+            input.extend(&letters[..marker_length.min(letters.len())]);
+            input += &filler(rng, &letters[..3]);
         }
+
+        input += &filler(rng, &letters);
+        input.push('\n');
+
+        Some(input)
     }
 }
 
-fn all_chars_are_different(chars: &Vec<char>) -> bool {
-    let mut chars = chars.clone();
+// Returns how many characters need to be processed before the first marker of the given length
+// is detected, if there is one.
+fn find_marker(chars: &[char], marker_length: usize) -> Option<usize> {
+    chars
+        .windows(marker_length)
+        .position(all_chars_are_different)
+        .map(|start| start + marker_length)
+}
+
+fn all_chars_are_different(chars: &[char]) -> bool {
+    let mut chars = chars.to_vec();
     let full_length = chars.len();
     chars.sort();
     chars.dedup();
@@ -29,7 +90,21 @@ fn all_chars_are_different(chars: &Vec<char>) -> bool {
 mod tests {
     #[test]
     fn all_chars_are_different() {
-        assert!(super::all_chars_are_different(&vec!['a', 'b', 'c']));
-        assert!(!super::all_chars_are_different(&vec!['a', 'b', 'a']));
+        assert!(super::all_chars_are_different(&['a', 'b', 'c']));
+        assert!(!super::all_chars_are_different(&['a', 'b', 'a']));
+    }
+
+    #[test]
+    fn find_marker() {
+        let chars = "mjqjpqmgbljsphdztnvjfqwrcgsmlb"
+            .chars()
+            .collect::<Vec<char>>();
+        assert_eq!(super::find_marker(&chars, 4), Some(7));
+        assert_eq!(super::find_marker(&chars, 14), Some(19));
+
+        let chars = "aabcd".chars().collect::<Vec<char>>();
+        assert_eq!(super::find_marker(&chars, 4), Some(5));
+        assert_eq!(super::find_marker(&chars, 5), None);
+        assert_eq!(super::find_marker(&chars, 6), None);
     }
 }
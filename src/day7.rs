@@ -1,30 +1,45 @@
 use core::fmt;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    error::{parse_number, Error},
+    random::Rng,
+    solution::{diagnostic, Answer, Solution},
+};
 
 const AVAILABLE_SPACE: u64 = 70000000;
 const MIN_FREE_SPACE: u64 = 30000000;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct File {
     pub name: String,
     size: u64,
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Dir {
     pub name: String,
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum NodeValue {
     File(File),
     Dir(Dir),
 }
 
-struct Node {
+pub struct Node {
     value: NodeValue,
     children: HashMap<String, Rc<RefCell<Node>>>,
-    parent: Option<Rc<RefCell<Node>>>,
+    // Weak, so that the tree is freed with its root despite the cycles between parents and
+    // children.
+    parent: Option<Weak<RefCell<Node>>>,
 }
 
 impl fmt::Debug for Node {
@@ -45,19 +60,20 @@ impl Node {
         }
     }
 
-    fn append_node<'a>(&mut self, name: String, node: Rc<RefCell<Node>>) {
+    fn append_node(&mut self, name: String, node: Rc<RefCell<Node>>) {
         self.children.insert(name.clone(), node);
     }
 
-    fn size(&self) -> u64 {
+    // None if the size doesn't fit in a u64.
+    fn size(&self) -> Option<u64> {
         match &self.value {
-            NodeValue::File(file) => file.size,
+            NodeValue::File(file) => Some(file.size),
             NodeValue::Dir(_) => {
-                let mut size = 0;
+                let mut size = 0_u64;
                 for child in self.children.values() {
-                    size += child.borrow().size();
+                    size = size.checked_add(child.borrow().size()?)?;
                 }
-                size
+                Some(size)
             }
         }
     }
@@ -76,99 +92,140 @@ enum Line {
     Dir(String),
 }
 
-pub fn run(input: &str) {
-    let root = Rc::new(RefCell::new(Node::new(Dir {
-        name: "/".to_string(),
-    })));
-
-    let mut current_dir = Rc::clone(&root);
-
-    let lines = input.lines().skip(1).map(parse_line);
-
-    for line in lines {
-        match line {
-            // For a file, we create a new file struct and add it to the current directory's
-            // children.
-            Line::FileWithSize(filename, size) => {
-                let child_node = Rc::new(RefCell::new(Node {
-                    value: NodeValue::File(File {
-                        name: filename.to_string(),
-                        size,
-                    }),
-                    children: HashMap::new(),
-                    parent: None,
-                }));
-
-                current_dir
-                    .borrow_mut()
-                    .append_node(filename, Rc::clone(&child_node));
-
-                child_node.borrow_mut().parent = Some(Rc::clone(&current_dir));
-            }
+pub struct Day7;
 
-            // For a directory, we create a new directory struct, set its parent
-            // the current directory, and add it to the current directory's
-            // children.
-            Line::Dir(dir_name) => {
-                let child_node = Rc::new(RefCell::new(Node::new(Dir {
-                    name: dir_name.to_string(),
-                })));
+impl Solution for Day7 {
+    type Input = Rc<RefCell<Node>>;
 
-                current_dir
-                    .borrow_mut()
-                    .append_node(dir_name, Rc::clone(&child_node));
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let root = Rc::new(RefCell::new(Node::new(Dir {
+            name: "/".to_string(),
+        })));
 
-                let mut mut_child = child_node.borrow_mut();
-                mut_child.parent = Some(Rc::clone(&current_dir));
-            }
+        let mut current_dir = Rc::clone(&root);
 
-            // "ls" is kind of not very useful, so we just ignore it.
-            Line::Command(Command::Ls) => continue,
+        // The session starts at the root.
+        let first_line = input.lines().next().unwrap_or(input);
 
-            // For "cd", we find the directory in the current directory's children
-            // and make it the current directory. If the directory is "/", we go
-            // back to the root. If the directory is "..", we go to the parent
-            // of the current directory.
-            Line::Command(Command::Cd(dir_name)) => match dir_name.as_str() {
-                "/" => {
-                    current_dir = Rc::clone(&root);
-                }
-                ".." => {
-                    let current_dir_clone = Rc::clone(&current_dir);
-                    current_dir = Rc::clone(current_dir_clone.borrow().parent.as_ref().unwrap());
+        if !matches!(parse_line(first_line), Ok(Line::Command(Command::Cd(dir))) if dir == "/") {
+            return Err(Error::at(input, first_line, "expected `$ cd /`"));
+        }
+
+        for line in input.lines().skip(1) {
+            match parse_line(line).map_err(|error| error.within(input, line))? {
+                // For a file, we create a new file struct and add it to the current directory's
+                // children.
+                Line::FileWithSize(filename, size) => {
+                    let child_node = Rc::new(RefCell::new(Node {
+                        value: NodeValue::File(File {
+                            name: filename.to_string(),
+                            size,
+                        }),
+                        children: HashMap::new(),
+                        parent: None,
+                    }));
+
+                    current_dir
+                        .borrow_mut()
+                        .append_node(filename, Rc::clone(&child_node));
+
+                    child_node.borrow_mut().parent = Some(Rc::downgrade(&current_dir));
                 }
-                _ => {
-                    let child_clone = Rc::clone(&current_dir.borrow().children[&dir_name]);
-                    current_dir = child_clone;
+
+                // For a directory, we create a new directory struct, set its parent
+                // the current directory, and add it to the current directory's
+                // children.
+                Line::Dir(dir_name) => {
+                    let child_node = Rc::new(RefCell::new(Node::new(Dir {
+                        name: dir_name.to_string(),
+                    })));
+
+                    current_dir
+                        .borrow_mut()
+                        .append_node(dir_name, Rc::clone(&child_node));
+
+                    let mut mut_child = child_node.borrow_mut();
+                    mut_child.parent = Some(Rc::downgrade(&current_dir));
                 }
-            },
+
+                // "ls" is kind of not very useful, so we just ignore it.
+                Line::Command(Command::Ls) => continue,
+
+                // For "cd", we find the directory in the current directory's children
+                // and make it the current directory. If the directory is "/", we go
+                // back to the root. If the directory is "..", we go to the parent
+                // of the current directory.
+                Line::Command(Command::Cd(dir_name)) => match dir_name.as_str() {
+                    "/" => {
+                        current_dir = Rc::clone(&root);
+                    }
+                    ".." => {
+                        let parent = current_dir.borrow().parent.as_ref().map(Weak::upgrade);
+                        current_dir = parent
+                            .flatten()
+                            .ok_or_else(|| Error::at(input, line, "the root has no parent"))?;
+                    }
+                    _ => {
+                        let child_clone =
+                            Rc::clone(current_dir.borrow().children.get(&dir_name).ok_or_else(
+                                || {
+                                    Error::at(
+                                        input,
+                                        line,
+                                        format!("no such directory `{dir_name}`"),
+                                    )
+                                },
+                            )?);
+                        current_dir = child_clone;
+                    }
+                },
+            }
         }
+
+        Ok(root)
+    }
+
+    // The sum of the sizes of all directories that are at most 100000 big.
+    fn part1(root: &Self::Input) -> Answer {
+        calc_size(&root.borrow()).into()
     }
 
-    let small_size = calc_size(&root.borrow());
-    println!("Total size of dirs < 100k: {:?}", small_size);
+    fn part2(root: &Self::Input) -> Answer {
+        let Some(total_size) = root.borrow().size() else {
+            diagnostic!("The total size doesn't fit in 64 bits");
+            return Answer::Unsolved;
+        };
 
-    let total_size = root.borrow().size();
+        let Some(size) = find_size_of_smallest_dir_to_delete(total_size, &root.borrow()) else {
+            diagnostic!("No directory frees enough space, with {} used", total_size);
+            return Answer::Unsolved;
+        };
 
-    println!(
-        "Total size of all dirs: {:?} (which leaves {} free)",
-        total_size,
-        AVAILABLE_SPACE - total_size
-    );
+        size.into()
+    }
 
-    println!(
-        "Size of the smallest dir to delete: {:?}",
-        find_size_of_smallest_dir_to_delete(total_size, &root.borrow())
-    );
-}
+    // A terminal session exploring `size` directories, with a few files each.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        // The subdirectories of each directory, the root being the first one.
+        let mut subdirectories = vec![Vec::new(); size.max(1)];
 
-fn find_size_of_smallest_dir_to_delete(total_size: u64, node: &Node) -> u64 {
-    let mut smallest_dir_size = u64::MAX;
-    let size = node.size();
+        for directory in 1..subdirectories.len() {
+            let parent = rng.below(directory);
+            subdirectories[parent].push(directory);
+        }
+
+        let mut lines = vec!["$ cd /".to_string()];
+        let mut total_size = 0;
+        list_directory(rng, 0, &subdirectories, &mut total_size, &mut lines);
 
-    if viable_size(total_size, size) && size < smallest_dir_size {
-        smallest_dir_size = size;
+        Some(lines.join("\n") + "\n")
     }
+}
+
+// None if deleting no directory frees enough space. `node` is in a tree of `total_size`.
+fn find_size_of_smallest_dir_to_delete(total_size: u64, node: &Node) -> Option<u64> {
+    let size = node.size()?;
+    let mut smallest_dir_size = viable_size(total_size, size).then_some(size);
 
     for child in node.children.values() {
         match child.borrow().value {
@@ -176,25 +233,31 @@ fn find_size_of_smallest_dir_to_delete(total_size: u64, node: &Node) -> u64 {
             NodeValue::Dir(_) => {
                 let child_size = find_size_of_smallest_dir_to_delete(total_size, &child.borrow());
 
-                if viable_size(total_size, size) && child_size < smallest_dir_size {
-                    smallest_dir_size = child_size;
+                if let Some(child_size) = child_size {
+                    smallest_dir_size = Some(
+                        smallest_dir_size.map_or(child_size, |smallest| smallest.min(child_size)),
+                    );
                 }
             }
         }
     }
 
-    return smallest_dir_size;
+    smallest_dir_size
 }
 
+// Whether deleting a directory of `size` leaves enough free space.
 fn viable_size(total_size: u64, size: u64) -> bool {
-    (AVAILABLE_SPACE - total_size + size) >= MIN_FREE_SPACE
+    total_size
+        .checked_sub(size)
+        .and_then(|used| AVAILABLE_SPACE.checked_sub(used))
+        .is_some_and(|free| free >= MIN_FREE_SPACE)
 }
 
 fn calc_size(node: &Node) -> u64 {
     let mut total = 0;
-    let size = node.size();
 
-    if size <= 100000 {
+    // Directories too big for a u64 are over 100000 too.
+    if let Some(size) = node.size().filter(|size| *size <= 100000) {
         total += size;
     }
 
@@ -207,30 +270,128 @@ fn calc_size(node: &Node) -> u64 {
         }
     }
 
-    return total;
+    total
+}
+
+// Lists a generated directory, and then goes into each of its subdirectories. The files are kept
+// well within the available space.
+fn list_directory(
+    rng: &mut Rng,
+    directory: usize,
+    subdirectories: &[Vec<usize>],
+    total_size: &mut u64,
+    lines: &mut Vec<String>,
+) {
+    let mut names = HashSet::new();
+    let mut name = |rng: &mut Rng, extension: bool| loop {
+        let length = rng.range(1..9) as usize;
+        let mut name = rng.word(length);
+
+        if extension && rng.chance(0.5) {
+            name = format!("{}.{}", name, rng.word(3));
+        }
+
+        if names.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut directories = Vec::new();
+
+    for &subdirectory in &subdirectories[directory] {
+        let name = name(rng, false);
+        entries.push(format!("dir {}", name));
+        directories.push((subdirectory, name));
+    }
+
+    for _ in 0..rng.range(0..5) {
+        let size = rng.range(1000..300000) as u64;
+
+        if *total_size + size < AVAILABLE_SPACE / 2 {
+            *total_size += size;
+            entries.push(format!("{} {}", size, name(rng, true)));
+        }
+    }
+
+    rng.shuffle(&mut entries);
+    lines.push("$ ls".to_string());
+    lines.extend(entries);
+
+    for (subdirectory, name) in directories {
+        lines.push(format!("$ cd {}", name));
+        list_directory(rng, subdirectory, subdirectories, total_size, lines);
+
+        // Going back up from a directory in the root is the same as going to the root.
+        if directory == 0 && rng.chance(0.3) {
+            lines.push("$ cd /".to_string());
+        } else {
+            lines.push("$ cd ..".to_string());
+        }
+    }
 }
 
-fn parse_line(line: &str) -> Line {
-    if line.starts_with("$") {
-        let command = line[2..].trim();
+fn parse_line(line: &str) -> Result<Line, Error> {
+    if let Some(command) = line.strip_prefix('$') {
+        let command = command.trim();
 
-        if command.starts_with("cd") {
-            let dir = command[2..].trim();
-            Line::Command(Command::Cd(dir.to_string()))
+        if let Some(dir) = command.strip_prefix("cd") {
+            let dir = dir.trim();
+            Ok(Line::Command(Command::Cd(dir.to_string())))
         } else if command == "ls" {
-            Line::Command(Command::Ls)
+            Ok(Line::Command(Command::Ls))
         } else {
-            panic!("Unknown command: {}", command);
+            Err(Error::at(
+                line,
+                command,
+                format!("unknown command `{}`", command),
+            ))
         }
     } else {
-        if line.starts_with("dir") {
-            let dir_name = line[4..].trim();
-            Line::Dir(dir_name.to_string())
+        if let Some(dir_name) = line.strip_prefix("dir ") {
+            Ok(Line::Dir(dir_name.trim().to_string()))
         } else {
-            let mut parts = line.split_whitespace();
-            let size: u64 = parts.next().unwrap().to_string().parse::<u64>().unwrap();
-            let name = parts.next().unwrap().to_string();
-            Line::FileWithSize(name, size)
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [size, name] => Ok(Line::FileWithSize(
+                    name.to_string(),
+                    parse_number::<u64>(line, size)?,
+                )),
+                _ => Err(Error::at(line, line, "expected `<size> <file name>`")),
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| Day7::parse(input).unwrap_err().to_string();
+
+        assert_eq!(error(""), "line 1 col 1: expected `$ cd /`");
+        assert_eq!(error("$ ls\n100 a"), "line 1 col 1: expected `$ cd /`");
+        assert_eq!(
+            error("$ cd /\n$ cd a"),
+            "line 2 col 1: no such directory `a`"
+        );
+    }
+
+    #[test]
+    fn test_too_much_used_space() {
+        let root = Day7::parse("$ cd /\n$ ls\ndir a\n100 b\n$ cd a\n$ ls\n69999950 c").unwrap();
+        assert_eq!(Day7::part2(&root), Answer::Number(69999950));
+
+        let root = Day7::parse("$ cd /\n$ ls\n70000100 a").unwrap();
+        assert_eq!(Day7::part2(&root), Answer::Number(70000100));
+
+        let root =
+            Day7::parse("$ cd /\n$ ls\ndir a\n20000000 b\n$ cd a\n$ ls\n90000000 c").unwrap();
+        assert_eq!(Day7::part2(&root), Answer::Number(90000000));
+
+        let root = Day7::parse("$ cd /\n$ ls\n18446744073709551615 a\n1 b").unwrap();
+        assert_eq!(Day7::part1(&root), Answer::Number(0));
+        assert_eq!(Day7::part2(&root), Answer::Unsolved);
+    }
+}
@@ -1,158 +1,106 @@
+use crate::{
+    error::Error,
+    geometry::Direction4,
+    grid::{Grid, Position},
+    random::Rng,
+    solution::{Answer, Solution},
+};
+
 type Tree = u8;
 
 #[derive(Debug)]
-struct Forest {
-    trees: Vec<Vec<Tree>>,
-    rows: usize,
-    columns: usize,
+pub struct Forest {
+    trees: Grid<Tree>,
 }
 
 impl Forest {
-    pub fn new(input: &str) -> Forest {
-        let mut forest = Forest {
-            trees: Vec::new(),
-            rows: 0,
-            columns: 0,
-        };
-
-        for line in input.lines() {
-            let mut row: Vec<Tree> = Vec::new();
-
-            for c in line.chars() {
-                let tree = c.to_digit(10).expect("Every tree should be a digit") as Tree;
-                row.push(tree);
-            }
-
-            forest.trees.push(row);
-        }
-
-        forest.rows = forest.trees.len();
-        forest.columns = forest.trees[0].len();
-
-        return forest;
-    }
-
-    fn on_edge(&self, row_index: usize, tree_index: usize) -> bool {
-        return row_index == 0
-            || tree_index == 0
-            || row_index == self.rows - 1
-            || tree_index == self.columns - 1;
-    }
+    pub fn new(input: &str) -> Result<Forest, Error> {
+        let trees = Grid::parse(input, "a tree height (a digit)", |c| {
+            c.to_digit(10).map(|height| height as Tree)
+        })?;
 
-    fn is_visible(&self, row_index: usize, tree_index: usize) -> bool {
-        if self.on_edge(row_index, tree_index) {
-            return true;
+        if trees.is_empty() {
+            return Err(Error::new("expected at least one row of trees"));
         }
 
-        let tree = self.trees[row_index][tree_index];
-
-        let mut visible_from_left = true;
-        let mut visible_from_right = true;
-        let mut visible_from_top = true;
-        let mut visible_from_bottom = true;
-
-        for row_tree in &self.trees[row_index][0..tree_index] {
-            if row_tree >= &tree {
-                visible_from_left = false;
-            }
-        }
-        for row_tree in &self.trees[row_index][tree_index + 1..] {
-            if row_tree >= &tree {
-                visible_from_right = false;
-            }
-        }
-
-        for row in &self.trees[0..row_index] {
-            if row[tree_index] >= tree {
-                visible_from_top = false;
-            }
-        }
-        for row in &self.trees[row_index + 1..] {
-            if row[tree_index] >= tree {
-                visible_from_bottom = false;
-            }
-        }
-
-        return visible_from_bottom || visible_from_top || visible_from_left || visible_from_right;
+        Ok(Forest { trees })
     }
 
-    fn scenic_score(&self, row_index: usize, tree_index: usize) -> u64 {
-        let mut trees_on_left = 0;
-        let mut trees_on_right = 0;
-        let mut trees_on_top = 0;
-        let mut trees_on_bottom = 0;
+    // Trees are visible when all the trees between them and an edge are shorter. Trees on the
+    // edges are always visible.
+    fn is_visible(&self, position: Position) -> bool {
+        let tree = self.trees[position];
 
-        let tree = self.trees[row_index][tree_index];
+        Direction4::ALL.iter().any(|direction| {
+            self.trees
+                .ray(position, direction.step())
+                .all(|(_, other_tree)| *other_tree < tree)
+        })
+    }
 
-        if self.on_edge(row_index, tree_index) {
-            return 0;
-        }
+    // The product of the viewing distances in each direction, up to the first tree that is at
+    // least as tall (or the edge).
+    fn scenic_score(&self, position: Position) -> u64 {
+        let tree = self.trees[position];
 
-        for row_tree in self.trees[row_index][0..tree_index].iter().rev() {
-            if row_tree < &tree {
-                trees_on_left += 1;
-            } else {
-                trees_on_left += 1;
-                break;
-            }
-        }
+        Direction4::ALL
+            .iter()
+            .map(|direction| {
+                let mut viewing_distance = 0;
 
-        for row_tree in &self.trees[row_index][tree_index + 1..] {
-            if row_tree < &tree {
-                trees_on_right += 1;
-            } else {
-                trees_on_right += 1;
-                break;
-            }
-        }
+                for (_, other_tree) in self.trees.ray(position, direction.step()) {
+                    viewing_distance += 1;
 
-        for row in self.trees[0..row_index].iter().rev() {
-            if row[tree_index] < tree {
-                trees_on_top += 1;
-            } else {
-                trees_on_top += 1;
-                break;
-            }
-        }
+                    if *other_tree >= tree {
+                        break;
+                    }
+                }
 
-        for row in &self.trees[row_index + 1..] {
-            if row[tree_index] < tree {
-                trees_on_bottom += 1;
-            } else {
-                trees_on_bottom += 1;
-                break;
-            }
-        }
-
-        return trees_on_left * trees_on_right * trees_on_top * trees_on_bottom;
+                viewing_distance
+            })
+            .product()
     }
 }
 
-pub fn run(input: &str) {
-    let forest = Forest::new(input);
+pub struct Day8;
 
-    let mut visible = 0;
+impl Solution for Day8 {
+    type Input = Forest;
 
-    for (row_index, row) in forest.trees.iter().enumerate() {
-        for (tree_index, _) in row.iter().enumerate() {
-            if forest.is_visible(row_index, tree_index) {
-                visible += 1;
-            };
-        }
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Forest::new(input)
     }
 
-    println!("Visible trees: {}", visible);
-
-    let mut max_scenic_score = 0;
+    fn part1(forest: &Self::Input) -> Answer {
+        forest
+            .trees
+            .positions()
+            .filter(|position| forest.is_visible(*position))
+            .count()
+            .into()
+    }
 
-    for (row_index, row) in forest.trees.iter().enumerate() {
-        for (tree_index, _) in row.iter().enumerate() {
-            let scenic_score = forest.scenic_score(row_index, tree_index);
-            if scenic_score > max_scenic_score {
-                max_scenic_score = scenic_score;
-            }
-        }
+    fn part2(forest: &Self::Input) -> Answer {
+        forest
+            .trees
+            .positions()
+            .map(|position| forest.scenic_score(position))
+            .max()
+            .unwrap()
+            .into()
     }
 
-    println!("Max scenic score: {}", max_scenic_score);
+    // A forest of `size` by `size` trees.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let rows = (0..size.max(1))
+            .map(|_| {
+                (0..size.max(1))
+                    .map(|_| char::from_digit(rng.below(10) as u32, 10).unwrap())
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect();
+
+        Some(rows)
+    }
 }
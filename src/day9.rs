@@ -1,121 +1,125 @@
 use core::fmt;
 use std::collections::HashSet;
 
-type Position = (i32, i32);
-
-// Grid looks like this:
-// (2, 0) (2, 1) (2, 2) (2, 3)
-// (1, 0) (1, 1) (1, 2) (1, 3)
-// (0, 0) (0, 1) (0, 2) (0, 3)
-
-#[derive(PartialEq, Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-struct Move {
-    direction: Direction,
+use crate::{
+    error::{parse_lines, parse_number, Error},
+    geometry::{Direction4, Point2},
+    random::Rng,
+    solution::{Answer, Param, Solution},
+    visualize::{self, Frame, Palette, Rgb},
+};
+
+type Position = Point2<i32>;
+
+const PALETTE: Palette = &[
+    ('#', Rgb(70, 70, 110)),
+    ('H', Rgb(240, 90, 60)),
+    ('T', Rgb(250, 210, 70)),
+];
+
+const KNOTS: Param = Param {
+    name: "knots",
+    default: 10,
+    min: 2,
+    max: 1000,
+    description: "Knots in the rope, including the head (part 2)",
+};
+
+pub struct Move {
+    direction: Direction4,
     distance: usize,
 }
 
 impl Move {
-    pub fn from_line(line: &str) -> Move {
-        let (direction, distance) = line.split_at(1);
-        let distance = distance.trim().parse::<usize>().unwrap();
+    pub fn from_line(line: &str) -> Result<Move, Error> {
+        let (direction, distance) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        let distance = parse_number::<usize>(line, distance)?;
 
         let direction = match direction {
-            "U" => Direction::Up,
-            "D" => Direction::Down,
-            "L" => Direction::Left,
-            "R" => Direction::Right,
-            _ => panic!("Unknown direction: {}", direction),
+            "U" => Direction4::North,
+            "D" => Direction4::South,
+            "L" => Direction4::West,
+            "R" => Direction4::East,
+            _ => {
+                return Err(Error::at(
+                    line,
+                    direction,
+                    format!("unknown direction `{}`", direction),
+                ))
+            }
         };
 
-        Move {
+        Ok(Move {
             direction,
             distance,
-        }
+        })
     }
 }
 
 #[derive(Debug)]
 struct Rope {
-    knots: [Position; 10],
+    knots: Vec<Position>,
 }
 
 impl Rope {
-    fn move_head(&mut self, move_: &Move) {
-        match move_.direction {
-            Direction::Up => self.knots[0].0 += 1 as i32,
-            Direction::Down => self.knots[0].0 -= 1 as i32,
-            Direction::Left => self.knots[0].1 -= 1 as i32,
-            Direction::Right => self.knots[0].1 += 1 as i32,
+    fn new(knots: usize) -> Self {
+        Self {
+            knots: vec![Point2::ORIGIN; knots],
         }
     }
 
+    fn move_head(&mut self, move_: &Move) {
+        self.knots[0] += move_.direction.step();
+    }
+
     fn update_other_knots(&mut self, visited_positions: &mut HashSet<Position>) {
         for index in 1..self.knots.len() {
-            // This knot is already close enough to the next.
-            if self.distance_between_knot_and_next(index) <= 1 {
+            let (previous, knot) = (self.knots[index - 1], self.knots[index]);
+
+            // This knot is already close enough to the next (touching, maybe diagonally).
+            if knot.chebyshev_distance(previous) <= 1 {
                 break;
             }
 
-            if self.knots[index - 1].0 == self.knots[index].0 {
-                if self.knots[index - 1].1 > self.knots[index].1 {
-                    self.knots[index].1 += 1;
-                } else {
-                    self.knots[index].1 -= 1;
-                }
-            } else if self.knots[index - 1].1 == self.knots[index].1 {
-                if self.knots[index - 1].0 > self.knots[index].0 {
-                    self.knots[index].0 += 1;
-                } else {
-                    self.knots[index].0 -= 1;
-                }
-            } else {
-                // Diagonal.
-                if self.knots[index - 1].0 > self.knots[index].0 {
-                    self.knots[index].0 += 1;
-                } else {
-                    self.knots[index].0 -= 1;
-                }
-
-                if self.knots[index - 1].1 > self.knots[index].1 {
-                    self.knots[index].1 += 1;
-                } else {
-                    self.knots[index].1 -= 1;
-                }
-            }
+            // One step towards the previous knot, diagonally when not in its row or column.
+            self.knots[index] += (previous - knot).signum();
         }
 
-        visited_positions.insert(self.knots[self.knots.len() - 1].clone());
+        visited_positions.insert(self.knots[self.knots.len() - 1]);
     }
 
-    fn distance_between_knot_and_next(&self, knot_index: usize) -> usize {
-        if (self.knots[knot_index - 1].0 == self.knots[knot_index].0 + 1
-            || self.knots[knot_index - 1].0 == self.knots[knot_index].0 - 1)
-            && (self.knots[knot_index - 1].1 == self.knots[knot_index].1 + 1
-                || self.knots[knot_index - 1].1 == self.knots[knot_index].1 - 1)
-        {
-            1
-        } else {
-            ((self.knots[knot_index - 1].0 - self.knots[knot_index].0).abs()
-                + (self.knots[knot_index - 1].1 - self.knots[knot_index].1).abs())
-                as usize
+    // The knots over the positions the tail visited, following the head. Knots are drawn by their
+    // index, except for the head and the tail.
+    fn frame(&self, visited_positions: &HashSet<Position>, caption: String) -> Frame {
+        let to_frame = |position: Position| Point2::new(position.x as i64, position.y as i64);
+        let mut frame = Frame::new(caption, PALETTE).with_focus(to_frame(self.knots[0]));
+
+        for position in visited_positions {
+            frame.set(to_frame(*position), '#');
         }
+
+        // From the tail, so that knots closer to the head are drawn over the others.
+        for (index, knot) in self.knots.iter().enumerate().rev() {
+            let cell = match index {
+                0 => 'H',
+                _ if index == self.knots.len() - 1 => 'T',
+                _ => char::from_digit(index as u32 % 10, 10).unwrap(),
+            };
+
+            frame.set(to_frame(*knot), cell);
+        }
+
+        frame
     }
 }
 
 impl fmt::Display for Rope {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in (-20..20).rev() {
-            for column in -20..20 {
+        for y in -20..20 {
+            for x in -20..20 {
                 let mut found = false;
                 for index in 0..self.knots.len() {
-                    if self.knots[index] == (row, column) {
+                    if self.knots[index] == Point2::new(x, y) {
                         write!(f, "{}", index)?;
                         found = true;
                     }
@@ -126,28 +130,73 @@ impl fmt::Display for Rope {
                 };
             }
 
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
 
-pub fn run(input: &str) {
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<Move>;
+
+    const PARAMS: &'static [Param] = &[KNOTS];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, Move::from_line)
+    }
+
+    fn part1(moves: &Self::Input) -> Answer {
+        simulate_rope(moves, 2).into()
+    }
+
+    fn part2(moves: &Self::Input) -> Answer {
+        simulate_rope(moves, KNOTS.value()).into()
+    }
+
+    // `size` motions of the head.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let motions = (0..size.max(1))
+            .map(|_| {
+                format!(
+                    "{} {}\n",
+                    rng.choose(&["U", "D", "L", "R"]),
+                    rng.range(1..20)
+                )
+            })
+            .collect();
+
+        Some(motions)
+    }
+}
+
+// Returns the number of positions visited by the tail of a rope with the given number of knots.
+fn simulate_rope(moves: &[Move], knots: usize) -> usize {
     let mut visited_positions: HashSet<Position> = HashSet::new();
 
-    let mut rope = Rope {
-        knots: [(0, 0); 10],
-    };
+    let mut rope = Rope::new(knots);
 
-    for move_ in input.lines().map(Move::from_line) {
+    for (index, move_) in moves.iter().enumerate() {
         for _ in 0..move_.distance {
-            rope.move_head(&move_);
+            rope.move_head(move_);
             rope.update_other_knots(&mut visited_positions);
+
+            visualize::emit(|| {
+                let caption = format!(
+                    "{} knots, move {} of {}: {} positions visited by the tail",
+                    knots,
+                    index + 1,
+                    moves.len(),
+                    visited_positions.len()
+                );
+                rope.frame(&visited_positions, caption)
+            });
         }
     }
 
-    println!("Visited {} positions", visited_positions.len());
+    visited_positions.len()
 }
 
 #[cfg(test)]
@@ -156,20 +205,20 @@ mod tests {
 
     #[test]
     fn test_move() {
-        let move_ = Move::from_line("U 1");
-        assert_eq!(move_.direction, Direction::Up);
+        let move_ = Move::from_line("U 1").unwrap();
+        assert_eq!(move_.direction, Direction4::North);
         assert_eq!(move_.distance, 1);
 
-        let move_ = Move::from_line("D 5");
-        assert_eq!(move_.direction, Direction::Down);
+        let move_ = Move::from_line("D 5").unwrap();
+        assert_eq!(move_.direction, Direction4::South);
         assert_eq!(move_.distance, 5);
 
-        let move_ = Move::from_line("L 11");
-        assert_eq!(move_.direction, Direction::Left);
+        let move_ = Move::from_line("L 11").unwrap();
+        assert_eq!(move_.direction, Direction4::West);
         assert_eq!(move_.distance, 11);
 
-        let move_ = Move::from_line("R 1");
-        assert_eq!(move_.direction, Direction::Right);
+        let move_ = Move::from_line("R 1").unwrap();
+        assert_eq!(move_.direction, Direction4::East);
         assert_eq!(move_.distance, 1);
     }
 }
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::{
+    error::Error,
+    random::Rng,
+    solution::{Answer, Day},
+};
+
+/// A part of a day whose answer differs from the answer of the day's reference solver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub part: usize,
+    pub expected: Answer,
+    pub actual: Answer,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "part {}: {} instead of {} (reference)",
+            self.part, self.actual, self.expected
+        )
+    }
+}
+
+// Runs the day and its reference solver on the input, and returns the parts where they disagree,
+// or None if the day has no reference solver. Parts that the reference doesn't solve are skipped.
+pub fn compare(day: &Day, input: &str) -> Result<Option<Vec<Mismatch>>, Error> {
+    let Some((part1, part2)) = (day.reference)(input)? else {
+        return Ok(None);
+    };
+
+    let report = (day.run)(input)?;
+
+    let mismatches = [(1, part1, report.part1), (2, part2, report.part2)]
+        .into_iter()
+        .filter(|(_, expected, actual)| *expected != Answer::Unsolved && expected != actual)
+        .map(|(part, expected, actual)| Mismatch {
+            part,
+            expected,
+            actual,
+        })
+        .collect();
+
+    Ok(Some(mismatches))
+}
+
+// Removes lines from a failing input for as long as it keeps failing, first in large chunks and
+// then in smaller ones down to single lines (delta debugging), so that what's left is small enough
+// to debug by hand. `fails` should only accept inputs that fail the same way as the original one.
+pub fn minimize(input: &str, mut fails: impl FnMut(&str) -> bool) -> String {
+    let mut lines = input.lines().collect::<Vec<&str>>();
+    let mut chunks = 2;
+
+    while lines.len() >= 2 {
+        let chunk_size = lines.len().div_ceil(chunks);
+
+        let smaller = (0..lines.len()).step_by(chunk_size).find_map(|start| {
+            let mut candidate = lines.clone();
+            candidate.drain(start..(start + chunk_size).min(lines.len()));
+            fails(&join_lines(&candidate)).then_some(candidate)
+        });
+
+        match smaller {
+            Some(smaller) => {
+                lines = smaller;
+                chunks = (chunks - 1).max(2);
+            }
+            None if chunk_size == 1 => break,
+            None => chunks = (chunks * 2).min(lines.len()),
+        }
+    }
+
+    join_lines(&lines)
+}
+
+// The smallest generated input that fails, with its seed and size: the first seed (from 1 to
+// `seeds`) whose input fails, at the smallest size up to `size`. None if none of them fails.
+pub fn smallest_generated(
+    day: &Day,
+    size: usize,
+    seeds: u64,
+    mut fails: impl FnMut(&str) -> bool,
+) -> Option<(u64, usize, String)> {
+    (1..=size).find_map(|size| {
+        (1..=seeds).find_map(|seed| {
+            let input = (day.generate)(&mut Rng::new(seed), size)?;
+            fails(&input).then_some((seed, size, input))
+        })
+    })
+}
+
+fn join_lines(lines: &[&str]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimize() {
+        let input = (1..=20).map(|n| format!("{}\n", n)).collect::<String>();
+        let fails =
+            |input: &str| input.lines().any(|l| l == "7") && input.lines().any(|l| l == "13");
+
+        assert_eq!(minimize(&input, fails), "7\n13\n");
+        assert_eq!(minimize("1\n2\n", |input| input.contains('2')), "2\n");
+    }
+
+    #[test]
+    fn test_mismatch_display() {
+        let mismatch = Mismatch {
+            part: 2,
+            expected: Answer::Number(1707),
+            actual: Answer::Number(1651),
+        };
+
+        assert_eq!(
+            mismatch.to_string(),
+            "part 2: 1651 instead of 1707 (reference)"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::{fmt, str::FromStr};
+
+/// An error in a day's input, or in running a day, like
+/// "day11: line 14 col 23: expected `divisible by`".
+///
+/// Parsers report errors relative to the text they're looking at (usually a line), and callers
+/// move them to their position in the whole input with `within`. The runner adds the day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    // The input the error is in (like "day11_test"), or the file for errors in other files.
+    pub day: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Error {
+            day: None,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    // An error at `part`, which must be a slice of `text`.
+    pub fn at(text: &str, part: &str, message: impl Into<String>) -> Self {
+        let (line, column) = position(text, part);
+
+        Error {
+            line: Some(line),
+            column: Some(column),
+            ..Error::new(message)
+        }
+    }
+
+    // Moves an error in `part` (a slice of `text`) to its position in `text`. Errors without a
+    // position are reported on the line where `part` starts.
+    pub fn within(self, text: &str, part: &str) -> Self {
+        let (part_line, part_column) = position(text, part);
+
+        match self.line {
+            Some(1) => Error {
+                line: Some(part_line),
+                column: self.column.map(|column| column + part_column - 1),
+                ..self
+            },
+            Some(line) => Error {
+                line: Some(line + part_line - 1),
+                ..self
+            },
+            None => Error {
+                line: Some(part_line),
+                ..self
+            },
+        }
+    }
+
+    pub fn in_day(self, day: &str) -> Self {
+        Error {
+            day: Some(day.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = &self.day {
+            write!(f, "{}: ", day)?;
+        }
+
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {} col {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            _ => (),
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// The 1-based line and column where `part` starts in `text`.
+fn position(text: &str, part: &str) -> (usize, usize) {
+    let offset = (part.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+    assert!(
+        offset <= text.len(),
+        "{:?} is not a part of {:?}",
+        part,
+        text
+    );
+
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    (before.matches('\n').count() + 1, offset - line_start + 1)
+}
+
+// Parses each line of the input, reporting errors at their line.
+pub fn parse_lines<T>(
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    input
+        .lines()
+        .map(|line| parse(line).map_err(|error| error.within(input, line)))
+        .collect()
+}
+
+// Parses `part` (a slice of `text`), ignoring surrounding whitespace.
+pub fn parse_number<T: FromStr>(text: &str, part: &str) -> Result<T, Error> {
+    let trimmed = part.trim();
+
+    trimmed.parse::<T>().map_err(|_| {
+        Error::at(
+            text,
+            trimmed,
+            format!("expected a number, got `{}`", trimmed),
+        )
+    })
+}
+
+// Like `str::split_once`, but reports the missing delimiter at the start of `part` (a slice of
+// `text`).
+pub fn split_once<'a>(
+    text: &str,
+    part: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str), Error> {
+    part.split_once(delimiter).ok_or_else(|| {
+        Error::at(
+            text,
+            part.trim_start(),
+            format!("expected `{}`", delimiter.escape_debug()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = Error::new("expected `divisible by`");
+        assert_eq!(error.to_string(), "expected `divisible by`");
+
+        let error = Error {
+            line: Some(14),
+            column: Some(23),
+            ..error.in_day("day11")
+        };
+        assert_eq!(
+            error.to_string(),
+            "day11: line 14 col 23: expected `divisible by`"
+        );
+
+        let error = Error {
+            column: None,
+            ..error
+        };
+        assert_eq!(error.to_string(), "day11: line 14: expected `divisible by`");
+    }
+
+    #[test]
+    fn test_at_and_within() {
+        let input = "Monkey 0:\n  Test: divisible by 23\n  Test: divided by 19";
+        let (_, last_line) = input.rsplit_once('\n').unwrap();
+
+        let error = split_once(last_line, last_line, "divisible by").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(1), Some(3)));
+
+        let error = error.within(input, last_line);
+        assert_eq!((error.line, error.column), (Some(3), Some(3)));
+
+        let error = Error::new("no monkeys").within(input, last_line);
+        assert_eq!((error.line, error.column), (Some(3), None));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let numbers = parse_lines("1\n2\n3", |line| parse_number::<u8>(line, line));
+        assert_eq!(numbers, Ok(vec![1, 2, 3]));
+
+        let error = parse_lines("1\n2\n 3x", |line| parse_number::<u8>(line, line)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3 col 2: expected a number, got `3x`"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use aoc22::{
+    error::Error,
+    visualize::{Frame, Rgb, Viewport},
+};
+
+/// The file format of the exported frames, one file per frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+    // Only the animated GIF, if any.
+    None,
+}
+
+impl ImageFormat {
+    pub fn parse(format: &str) -> Result<Self, Error> {
+        match format {
+            "png" => Ok(ImageFormat::Png),
+            "ppm" => Ok(ImageFormat::Ppm),
+            "none" => Ok(ImageFormat::None),
+            format => Err(Error::new(format!(
+                "unknown image format `{}`, expected png, ppm or none",
+                format
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub directory: PathBuf,
+    pub format: ImageFormat,
+    pub gif: bool,
+    // The size of a cell, in pixels.
+    pub scale: usize,
+    // The size of the images, in cells.
+    pub width: i64,
+    pub height: i64,
+    // Only every nth frame is exported.
+    pub every: usize,
+    pub fps: usize,
+}
+
+/// Writes the frames of a simulation to image files in a directory as they're emitted, named after
+/// the input and numbered from 1 (like `day14_test_00001.png`), and optionally to an animated GIF
+/// (`day14_test.gif`). All the images have the same size, and follow the action like the terminal
+/// player does.
+pub struct Exporter {
+    options: ExportOptions,
+    name: String,
+    viewport: Viewport,
+    gif: Option<gif::Encoder<BufWriter<File>>>,
+    received: usize,
+    exported: usize,
+    // The first error, after which nothing else is written.
+    error: Option<Error>,
+}
+
+impl Exporter {
+    pub fn new(options: ExportOptions, input_name: &str) -> Result<Self, Error> {
+        fs::create_dir_all(&options.directory).map_err(|error| {
+            Error::new(format!(
+                "can't create {}: {}",
+                options.directory.display(),
+                error
+            ))
+        })?;
+
+        Ok(Exporter {
+            viewport: Viewport::new(options.width, options.height),
+            options,
+            name: input_name.to_string(),
+            gif: None,
+            received: 0,
+            exported: 0,
+            error: None,
+        })
+    }
+
+    pub fn export(&mut self, frame: &Frame) {
+        self.received += 1;
+        self.viewport.follow(frame);
+
+        if self.error.is_some() || !(self.received - 1).is_multiple_of(self.options.every.max(1)) {
+            return;
+        }
+
+        if let Err(error) = self.write(frame) {
+            self.error = Some(error);
+        }
+    }
+
+    // The number of frames emitted so far.
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    // Completes the GIF, returning the number of frames exported or the first error.
+    pub fn finish(&mut self) -> Result<usize, Error> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        if let Some(encoder) = self.gif.take() {
+            let path = self.gif_path();
+            encoder
+                .into_inner()
+                .and_then(|mut writer| writer.flush())
+                .map_err(|error| write_error(&path, error))?;
+        }
+
+        Ok(self.exported)
+    }
+
+    fn write(&mut self, frame: &Frame) -> Result<(), Error> {
+        let (min, max) = self.viewport.area();
+        let scale = self.options.scale.max(1);
+        let width = self.viewport.width as usize * scale;
+        let height = self.viewport.height as usize * scale;
+        let pixels = frame.pixels(min, max, scale);
+
+        self.exported += 1;
+
+        let path = self
+            .options
+            .directory
+            .join(format!("{}_{:05}", self.name, self.exported));
+
+        match self.options.format {
+            ImageFormat::Png => write_png(&path.with_extension("png"), width, height, &pixels)?,
+            ImageFormat::Ppm => write_ppm(&path.with_extension("ppm"), width, height, &pixels)?,
+            ImageFormat::None => (),
+        }
+
+        if self.options.gif {
+            self.write_gif_frame(width, height, &pixels)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_gif_frame(
+        &mut self,
+        width: usize,
+        height: usize,
+        pixels: &[Rgb],
+    ) -> Result<(), Error> {
+        let path = self.gif_path();
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(Error::new(format!(
+                    "can't write {}: {}x{} pixels is too large for a GIF",
+                    path.display(),
+                    width,
+                    height
+                )))
+            }
+        };
+
+        if self.gif.is_none() {
+            let file = create(&path)?;
+            let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &[])
+                .map_err(|error| write_error(&path, error))?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(|error| write_error(&path, error))?;
+            self.gif = Some(encoder);
+        }
+
+        let (indices, palette) = index_colors(pixels).ok_or_else(|| {
+            Error::new(format!(
+                "can't write {}: a frame has more than 256 colours",
+                path.display()
+            ))
+        })?;
+
+        let mut gif_frame = gif::Frame::from_palette_pixels(width, height, indices, palette, None);
+        // In hundredths of a second.
+        gif_frame.delay = (100 / self.options.fps.max(1)).max(1) as u16;
+
+        self.gif
+            .as_mut()
+            .unwrap()
+            .write_frame(&gif_frame)
+            .map_err(|error| write_error(&path, error))
+    }
+
+    fn gif_path(&self) -> PathBuf {
+        self.options.directory.join(format!("{}.gif", self.name))
+    }
+}
+
+fn write_png(path: &Path, width: usize, height: usize, pixels: &[Rgb]) -> Result<(), Error> {
+    let mut encoder = png::Encoder::new(BufWriter::new(create(path)?), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&rgb_bytes(pixels)))
+        .map_err(|error| write_error(path, error))
+}
+
+// A binary PPM ("P6"): a text header, then the red, green and blue bytes of each pixel.
+fn write_ppm(path: &Path, width: usize, height: usize, pixels: &[Rgb]) -> Result<(), Error> {
+    let mut writer = BufWriter::new(create(path)?);
+
+    write!(writer, "P6\n{} {}\n255\n", width, height)
+        .and_then(|_| writer.write_all(&rgb_bytes(pixels)))
+        .and_then(|_| writer.flush())
+        .map_err(|error| write_error(path, error))
+}
+
+fn create(path: &Path) -> Result<File, Error> {
+    File::create(path).map_err(|error| write_error(path, error))
+}
+
+fn write_error(path: &Path, error: impl std::fmt::Display) -> Error {
+    Error::new(format!("can't write {}: {}", path.display(), error))
+}
+
+fn rgb_bytes(pixels: &[Rgb]) -> Vec<u8> {
+    pixels.iter().flat_map(|&Rgb(r, g, b)| [r, g, b]).collect()
+}
+
+// The index of each pixel's colour in a palette of the colours used (as red, green and blue
+// bytes), or None if there are too many colours for a GIF.
+fn index_colors(pixels: &[Rgb]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut indices_by_color = HashMap::new();
+    let mut palette = Vec::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+
+    for &color in pixels {
+        let index = match indices_by_color.get(&color) {
+            Some(&index) => index,
+            None => {
+                let index = u8::try_from(indices_by_color.len()).ok()?;
+                indices_by_color.insert(color, index);
+                palette.extend([color.0, color.1, color.2]);
+                index
+            }
+        };
+
+        indices.push(index);
+    }
+
+    Some((indices, palette))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_colors() {
+        let (red, blue) = (Rgb(255, 0, 0), Rgb(0, 0, 255));
+
+        assert_eq!(
+            index_colors(&[red, blue, red, red]),
+            Some((vec![0, 1, 0, 0], vec![255, 0, 0, 0, 0, 255]))
+        );
+
+        let colors = (0..=256).map(|i| Rgb((i % 256) as u8, (i / 256) as u8, 0));
+        assert_eq!(index_colors(&colors.collect::<Vec<Rgb>>()), None);
+    }
+
+    #[test]
+    fn test_image_format_parse() {
+        assert_eq!(ImageFormat::parse("png"), Ok(ImageFormat::Png));
+        assert_eq!(ImageFormat::parse("ppm"), Ok(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::parse("none"), Ok(ImageFormat::None));
+        assert!(ImageFormat::parse("jpg").is_err());
+    }
+}
@@ -0,0 +1,442 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// The integer types points can have as coordinates.
+pub trait Coordinate:
+    Copy
+    + Ord
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+
+    fn signum(self) -> Self;
+}
+
+macro_rules! coordinate {
+    ($($type:ty),*) => {
+        $(
+            impl Coordinate for $type {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn abs(self) -> Self {
+                    <$type>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$type>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize);
+
+/// A point in 2D, or the vector between two points. `y` grows downwards, like the rows of the
+/// maps in the inputs, so "north" is towards smaller `y`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point2<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T: Coordinate> Point2<T> {
+    pub const ORIGIN: Self = Point2::new(T::ZERO, T::ZERO);
+
+    // The number of steps between two points when moving horizontally and vertically.
+    pub fn manhattan_distance(self, other: Self) -> T {
+        let difference = other - self;
+        difference.x.abs() + difference.y.abs()
+    }
+
+    // The number of steps between two points when also moving diagonally.
+    pub fn chebyshev_distance(self, other: Self) -> T {
+        let difference = other - self;
+        difference.x.abs().max(difference.y.abs())
+    }
+
+    // The vector with each coordinate replaced by its sign, so a single step (maybe diagonal) in
+    // the same general direction.
+    pub fn signum(self) -> Self {
+        Point2::new(self.x.signum(), self.y.signum())
+    }
+
+    // Rotates by 90° counterclockwise (on the map, with `y` growing downwards) around the origin.
+    pub fn rotate_left(self) -> Self {
+        Point2::new(self.y, -self.x)
+    }
+
+    // Rotates by 90° clockwise (on the map, with `y` growing downwards) around the origin.
+    pub fn rotate_right(self) -> Self {
+        Point2::new(-self.y, self.x)
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point2::new(x, y)
+    }
+}
+
+/// A point in 3D, or the vector between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3<T = i64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub const ORIGIN: Self = Point3::new(T::ZERO, T::ZERO, T::ZERO);
+
+    pub fn manhattan_distance(self, other: Self) -> T {
+        let difference = other - self;
+        difference.x.abs() + difference.y.abs() + difference.z.abs()
+    }
+
+    pub fn chebyshev_distance(self, other: Self) -> T {
+        let difference = other - self;
+        difference
+            .x
+            .abs()
+            .max(difference.y.abs())
+            .max(difference.z.abs())
+    }
+
+    pub fn signum(self) -> Self {
+        Point3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    // The 6 points that share a face with this one, when points are unit cubes.
+    pub fn neighbors6(self) -> [Self; 6] {
+        let (one, zero) = (T::ONE, T::ZERO);
+
+        [
+            Point3::new(one, zero, zero),
+            Point3::new(-one, zero, zero),
+            Point3::new(zero, one, zero),
+            Point3::new(zero, -one, zero),
+            Point3::new(zero, zero, one),
+            Point3::new(zero, zero, -one),
+        ]
+        .map(|step| self + step)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3::new(x, y, z)
+    }
+}
+
+// The same operators for both kinds of points, coordinate by coordinate (and by a scalar for
+// multiplication).
+macro_rules! operators {
+    ($point:ident { $($field:ident),* }) => {
+        impl<T: Coordinate> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $point { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $point { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                $point { $($field: self.$field * factor),* }
+            }
+        }
+
+        impl<T: Coordinate> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $point { $($field: -self.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Coordinate> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+operators!(Point2 { x, y });
+operators!(Point3 { x, y, z });
+
+/// The 4 directions on a map, clockwise from the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction4 {
+    pub const ALL: [Direction4; 4] = [
+        Direction4::North,
+        Direction4::East,
+        Direction4::South,
+        Direction4::West,
+    ];
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    // The step to the next point in this direction, with `y` growing downwards.
+    pub fn step<T: Coordinate>(self) -> Point2<T> {
+        let (one, zero) = (T::ONE, T::ZERO);
+
+        match self {
+            Direction4::North => Point2::new(zero, -one),
+            Direction4::East => Point2::new(one, zero),
+            Direction4::South => Point2::new(zero, one),
+            Direction4::West => Point2::new(-one, zero),
+        }
+    }
+}
+
+// Drawn as an arrow.
+impl fmt::Display for Direction4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self {
+            Direction4::North => '^',
+            Direction4::East => '>',
+            Direction4::South => 'v',
+            Direction4::West => '<',
+        };
+
+        write!(f, "{}", arrow)
+    }
+}
+
+/// The 8 directions on a map, including diagonals, clockwise from the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    // Turns by 45°.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    // Turns by 45°.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    pub fn step<T: Coordinate>(self) -> Point2<T> {
+        let (one, zero) = (T::ONE, T::ZERO);
+
+        match self {
+            Direction8::North => Point2::new(zero, -one),
+            Direction8::NorthEast => Point2::new(one, -one),
+            Direction8::East => Point2::new(one, zero),
+            Direction8::SouthEast => Point2::new(one, one),
+            Direction8::South => Point2::new(zero, one),
+            Direction8::SouthWest => Point2::new(-one, one),
+            Direction8::West => Point2::new(-one, zero),
+            Direction8::NorthWest => Point2::new(-one, -one),
+        }
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        match direction {
+            Direction4::North => Direction8::North,
+            Direction4::East => Direction8::East,
+            Direction4::South => Direction8::South,
+            Direction4::West => Direction8::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(-3, 5);
+
+        assert_eq!(a + b, Point2::new(-2, 7));
+        assert_eq!(a - b, Point2::new(4, -3));
+        assert_eq!(a * 3, Point2::new(3, 6));
+        assert_eq!(-a, Point2::new(-1, -2));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+
+        assert_eq!(
+            Point3::new(1, 2, 3) + Point3::new(1, 1, 1) * 2,
+            Point3::new(3, 4, 5)
+        );
+        assert_eq!(Point2::from((4_i32, 2)), Point2::new(4_i32, 2));
+    }
+
+    #[test]
+    fn test_manhattan_distance() {
+        let a = Point2::new(23, 12);
+        assert_eq!(a.manhattan_distance(a), 0);
+
+        let a = Point2::new(1, 1);
+        let b = Point2::new(2, 2);
+        assert_eq!(a.manhattan_distance(b), 2);
+
+        let a = Point2::new(1, 2);
+        let b = Point2::new(2, 2);
+        assert_eq!(a.manhattan_distance(b), 1);
+
+        let a = Point2::new(-1, -1);
+        let b = Point2::new(2, 2);
+        assert_eq!(a.manhattan_distance(b), 6);
+
+        assert_eq!(
+            Point3::new(1, 2, 3).manhattan_distance(Point3::new(-1, 2, 5)),
+            4
+        );
+    }
+
+    #[test]
+    fn test_chebyshev_distance() {
+        assert_eq!(Point2::new(0, 0).chebyshev_distance(Point2::new(1, 1)), 1);
+        assert_eq!(Point2::new(0, 0).chebyshev_distance(Point2::new(-2, 1)), 2);
+        assert_eq!(
+            Point3::new(0, 0, 0).chebyshev_distance(Point3::new(1, -3, 2)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_rotation() {
+        let east = Direction4::East.step::<i64>();
+
+        assert_eq!(east.rotate_left(), Direction4::North.step());
+        assert_eq!(east.rotate_right(), Direction4::South.step());
+        assert_eq!(
+            Point2::new(2, 1).rotate_right().rotate_right(),
+            Point2::new(-2, -1)
+        );
+        assert_eq!(
+            Point2::new(2, 1).rotate_left().rotate_right(),
+            Point2::new(2, 1)
+        );
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction4::North.turn_left(), Direction4::West);
+        assert_eq!(Direction4::West.turn_right(), Direction4::North);
+        assert_eq!(Direction4::East.opposite(), Direction4::West);
+
+        assert_eq!(Direction8::North.turn_left(), Direction8::NorthWest);
+        assert_eq!(Direction8::NorthWest.turn_right(), Direction8::North);
+        assert_eq!(Direction8::SouthEast.opposite(), Direction8::NorthWest);
+
+        for direction in Direction4::ALL {
+            assert_eq!(direction.opposite().step(), -direction.step::<i32>());
+            assert_eq!(
+                direction.turn_right().step(),
+                direction.step::<i32>().rotate_right()
+            );
+            assert_eq!(Direction8::from(direction).step::<i32>(), direction.step());
+        }
+
+        for direction in Direction8::ALL {
+            assert_eq!(direction.opposite().step(), -direction.step::<i32>());
+            assert_eq!(
+                direction.step::<i32>().chebyshev_distance(Point2::ORIGIN),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_neighbors6() {
+        let neighbors = Point3::new(5, 5, 5).neighbors6();
+
+        assert!(neighbors.contains(&Point3::new(5, 5, 4)));
+        assert!(neighbors.contains(&Point3::new(6, 5, 5)));
+        assert!(neighbors
+            .iter()
+            .all(|neighbor| neighbor.manhattan_distance(Point3::new(5, 5, 5)) == 1));
+    }
+}
@@ -0,0 +1,555 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Index, IndexMut, Range},
+};
+
+use crate::{
+    error::Error,
+    geometry::{Direction4, Direction8, Point2},
+};
+
+/// A position in a grid: `x` is the column and `y` the row, which grows downwards like in the
+/// puzzle inputs.
+pub type Position = Point2;
+
+/// A 2D map of cells. Dense grids have a fixed number of rows and columns starting at (0, 0), all
+/// of them set, like the maps in the inputs. Sparse grids only have the cells that were set,
+/// anywhere (including negative positions), so they work for infinite maps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    storage: Storage<T>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Storage<T> {
+    Dense {
+        cells: Vec<T>,
+        rows: usize,
+        columns: usize,
+    },
+    Sparse(HashMap<Position, T>),
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, columns: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            storage: Storage::Dense {
+                cells: vec![fill; rows * columns],
+                rows,
+                columns,
+            },
+        }
+    }
+
+    pub fn sparse() -> Self {
+        Grid {
+            storage: Storage::Sparse(HashMap::new()),
+        }
+    }
+
+    // A dense grid with a row for each line of the input and a cell for each character, converted
+    // by `cell`. Lines shorter than the longest one are padded with the cell of a space, if there
+    // is one. `expected` describes the characters `cell` accepts, for errors.
+    pub fn parse(
+        input: &str,
+        expected: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Self, Error> {
+        let lines = input
+            .trim_end_matches(['\n', '\r'])
+            .lines()
+            .collect::<Vec<&str>>();
+
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(lines.len() * columns);
+
+        for line in &lines {
+            for (index, c) in line.char_indices() {
+                cells.push(cell(c).ok_or_else(|| {
+                    Error::at(
+                        input,
+                        &line[index..],
+                        format!("expected {}, got `{}`", expected, c),
+                    )
+                })?);
+            }
+
+            for _ in line.chars().count()..columns {
+                cells.push(cell(' ').ok_or_else(|| {
+                    Error::at(
+                        input,
+                        &line[line.len()..],
+                        format!("expected {} columns", columns),
+                    )
+                })?);
+            }
+        }
+
+        Ok(Grid {
+            storage: Storage::Dense {
+                cells,
+                rows: lines.len(),
+                columns,
+            },
+        })
+    }
+
+    // The number of rows and columns of a dense grid, or of the bounds of a sparse one.
+    pub fn size(&self) -> (usize, usize) {
+        match &self.storage {
+            Storage::Dense { rows, columns, .. } => (*rows, *columns),
+            Storage::Sparse(_) => match self.bounds() {
+                Some((min, max)) => ((max.y - min.y + 1) as usize, (max.x - min.x + 1) as usize),
+                None => (0, 0),
+            },
+        }
+    }
+
+    // The top left and bottom right positions of the grid (inclusive), if it has any cell.
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        match &self.storage {
+            Storage::Dense { rows, columns, .. } if *rows > 0 && *columns > 0 => Some((
+                Point2::ORIGIN,
+                Point2::new(*columns as i64 - 1, *rows as i64 - 1),
+            )),
+            Storage::Dense { .. } => None,
+            Storage::Sparse(cells) => {
+                let mut positions = cells.keys();
+                let first = *positions.next()?;
+
+                Some(positions.fold((first, first), |(min, max), position| {
+                    (
+                        Point2::new(min.x.min(position.x), min.y.min(position.y)),
+                        Point2::new(max.x.max(position.x), max.y.max(position.y)),
+                    )
+                }))
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense { cells, .. } => cells.len(),
+            Storage::Sparse(cells) => cells.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        match &self.storage {
+            Storage::Dense { cells, .. } => cells.get(self.dense_index(position)?),
+            Storage::Sparse(cells) => cells.get(&position),
+        }
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        match self.dense_index(position) {
+            Some(index) => match &mut self.storage {
+                Storage::Dense { cells, .. } => cells.get_mut(index),
+                Storage::Sparse(_) => unreachable!(),
+            },
+            None => match &mut self.storage {
+                Storage::Dense { .. } => None,
+                Storage::Sparse(cells) => cells.get_mut(&position),
+            },
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.get(position).is_some()
+    }
+
+    // Panics when the position is outside of a dense grid.
+    pub fn set(&mut self, position: Position, value: T) {
+        match self.dense_index(position) {
+            Some(index) => match &mut self.storage {
+                Storage::Dense { cells, .. } => cells[index] = value,
+                Storage::Sparse(_) => unreachable!(),
+            },
+            None => match &mut self.storage {
+                Storage::Dense { rows, columns, .. } => {
+                    panic!("{:?} is outside of the {}x{} grid", position, rows, columns)
+                }
+                Storage::Sparse(cells) => {
+                    cells.insert(position, value);
+                }
+            },
+        }
+    }
+
+    // Only sparse grids can have cells removed, dense grids panic.
+    pub fn remove(&mut self, position: Position) -> Option<T> {
+        match &mut self.storage {
+            Storage::Dense { .. } => panic!("cannot remove cells from a dense grid"),
+            Storage::Sparse(cells) => cells.remove(&position),
+        }
+    }
+
+    // The cells of the grid, row by row for dense grids and in no particular order for sparse
+    // ones.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Position, &T)> + '_> {
+        match &self.storage {
+            Storage::Dense { cells, columns, .. } => {
+                Box::new(cells.iter().enumerate().map(move |(index, cell)| {
+                    let (row, column) = (index / columns, index % columns);
+                    (Point2::new(column as i64, row as i64), cell)
+                }))
+            }
+            Storage::Sparse(cells) => {
+                Box::new(cells.iter().map(|(position, cell)| (*position, cell)))
+            }
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.iter().map(|(position, _)| position)
+    }
+
+    // The cells of a row, from left to right (skipping the unset cells of sparse grids).
+    pub fn row(&self, row: i64) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let columns = self.bounds().map_or(0..0, |(min, max)| min.x..max.x + 1);
+        columns.filter_map(move |column| {
+            let position = Point2::new(column, row);
+            Some((position, self.get(position)?))
+        })
+    }
+
+    // The cells of a column, from top to bottom (skipping the unset cells of sparse grids).
+    pub fn column(&self, column: i64) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let rows = self.bounds().map_or(0..0, |(min, max)| min.y..max.y + 1);
+        rows.filter_map(move |row| {
+            let position = Point2::new(column, row);
+            Some((position, self.get(position)?))
+        })
+    }
+
+    // The cells seen from a position (excluded) going in steps of `step`, like (1, 0) to look
+    // right, until the edge of the grid (skipping the unset cells of sparse grids).
+    pub fn ray(&self, from: Position, step: Position) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let bounds = self.bounds();
+
+        (1..)
+            .map(move |distance| from + step * distance)
+            .take_while(move |position| bounds.is_some_and(|bounds| in_bounds(bounds, *position)))
+            .filter_map(move |position| Some((position, self.get(position)?)))
+    }
+
+    // The cells above, right, below and left of a position, when set.
+    pub fn neighbors4(&self, position: Position) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let steps = Direction4::ALL.map(Direction4::step);
+        self.neighbors(position, steps)
+    }
+
+    // The cells around a position, including diagonally, when set.
+    pub fn neighbors8(&self, position: Position) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let steps = Direction8::ALL.map(Direction8::step);
+        self.neighbors(position, steps)
+    }
+
+    fn neighbors<const N: usize>(
+        &self,
+        position: Position,
+        steps: [Position; N],
+    ) -> impl Iterator<Item = (Position, &T)> + '_ {
+        steps.into_iter().filter_map(move |step| {
+            let neighbor = position + step;
+            Some((neighbor, self.get(neighbor)?))
+        })
+    }
+
+    // Takes a step from a position, coming back from the other side when leaving the bounds of
+    // the grid. The step must be smaller than the grid.
+    pub fn wrapping_step(&self, position: Position, step: Position) -> Position {
+        let (min, max) = self.bounds().expect("cannot wrap around an empty grid");
+        let (columns, rows) = (max.x - min.x + 1, max.y - min.y + 1);
+
+        Point2::new(
+            min.x + (position.x + step.x - min.x).rem_euclid(columns),
+            min.y + (position.y + step.y - min.y).rem_euclid(rows),
+        )
+    }
+
+    // Draws the grid, with a line for each row. `cell` draws each position, which is unset when
+    // sparse grids don't have it.
+    pub fn render<D: fmt::Display>(&self, cell: impl Fn(Position, Option<&T>) -> D) -> String {
+        match self.bounds() {
+            Some((min, max)) => self.render_area(min, max, cell),
+            None => String::new(),
+        }
+    }
+
+    // Like `render`, but only between the top left and bottom right positions (inclusive), which
+    // can be outside of the grid.
+    pub fn render_area<D: fmt::Display>(
+        &self,
+        min: Position,
+        max: Position,
+        cell: impl Fn(Position, Option<&T>) -> D,
+    ) -> String {
+        (min.y..=max.y)
+            .map(|row| {
+                (min.x..=max.x)
+                    .map(|column| {
+                        let position = Point2::new(column, row);
+                        cell(position, self.get(position)).to_string()
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Adds a row at the bottom of a dense grid, which must have as many cells as the other rows.
+    pub fn push_row(&mut self, row: Vec<T>) {
+        match &mut self.storage {
+            Storage::Dense {
+                cells,
+                rows,
+                columns,
+            } => {
+                assert_eq!(row.len(), *columns, "rows must have {} cells", columns);
+
+                cells.extend(row);
+                *rows += 1;
+            }
+            Storage::Sparse(_) => panic!("cannot push rows to a sparse grid"),
+        }
+    }
+
+    // Removes the given rows of a dense grid, moving the rows below them up.
+    pub fn remove_rows(&mut self, range: Range<usize>) {
+        match &mut self.storage {
+            Storage::Dense {
+                cells,
+                rows,
+                columns,
+            } => {
+                cells.drain(range.start * *columns..range.end * *columns);
+                *rows -= range.len();
+            }
+            Storage::Sparse(_) => panic!("cannot remove rows from a sparse grid"),
+        }
+    }
+
+    // Keeps only the rows of a dense grid for which `keep` returns true.
+    pub fn retain_rows(&mut self, mut keep: impl FnMut(&[T]) -> bool) {
+        match &mut self.storage {
+            Storage::Dense {
+                cells,
+                rows,
+                columns,
+            } => {
+                let mut kept = Vec::with_capacity(cells.len());
+                let mut kept_rows = 0;
+                let mut remaining = std::mem::take(cells).into_iter();
+
+                for _ in 0..*rows {
+                    let row = remaining.by_ref().take(*columns).collect::<Vec<T>>();
+
+                    if keep(&row) {
+                        kept.extend(row);
+                        kept_rows += 1;
+                    }
+                }
+
+                *cells = kept;
+                *rows = kept_rows;
+            }
+            Storage::Sparse(_) => panic!("cannot retain rows of a sparse grid"),
+        }
+    }
+
+    // The cells of a row of a dense grid.
+    pub fn row_slice(&self, row: usize) -> &[T] {
+        match &self.storage {
+            Storage::Dense { cells, columns, .. } => &cells[row * columns..(row + 1) * columns],
+            Storage::Sparse(_) => panic!("sparse grids don't have row slices"),
+        }
+    }
+
+    fn dense_index(&self, Point2 { x, y }: Position) -> Option<usize> {
+        match &self.storage {
+            Storage::Dense { rows, columns, .. }
+                if (0..*rows as i64).contains(&y) && (0..*columns as i64).contains(&x) =>
+            {
+                Some(y as usize * columns + x as usize)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn in_bounds((min, max): (Position, Position), position: Position) -> bool {
+    (min.x..=max.x).contains(&position.x) && (min.y..=max.y).contains(&position.y)
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position)
+            .unwrap_or_else(|| panic!("no cell at {:?}", position))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("no cell at {:?}", position))
+    }
+}
+
+// Unset cells of sparse grids are drawn as ".".
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let drawing = self.render(|_, cell| match cell {
+            Some(cell) => cell.to_string(),
+            None => ".".to_string(),
+        });
+
+        write!(f, "{}", drawing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (row, column), in the order the tests read.
+    fn at(row: i64, column: i64) -> Position {
+        Point2::new(column, row)
+    }
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, "a digit", |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456\n");
+
+        assert_eq!(grid.size(), (2, 3));
+        assert_eq!(grid.bounds(), Some((at(0, 0), at(1, 2))));
+        assert_eq!(grid[at(1, 0)], 4);
+        assert_eq!(grid.get(at(2, 0)), None);
+        assert_eq!(grid.get(at(0, -1)), None);
+
+        let error = Grid::parse("12\n3x", "a digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(error.to_string(), "line 2 col 2: expected a digit, got `x`");
+
+        // Short lines are padded with spaces, when they're allowed.
+        let grid = Grid::parse("  #\n.", "`.`, `#` or a space", Some).unwrap();
+        assert_eq!(grid.to_string(), "  #\n.  ");
+
+        let error = Grid::parse("123\n4", "a digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(error.to_string(), "line 2 col 2: expected 3 columns");
+
+        assert!(digits("").is_empty());
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut grid = Grid::sparse();
+        assert_eq!(grid.bounds(), None);
+
+        grid.set(at(-2, 3), '#');
+        grid.set(at(1, -1), '#');
+
+        assert_eq!(grid.bounds(), Some((at(-2, -1), at(1, 3))));
+        assert_eq!(grid.size(), (4, 5));
+        assert!(grid.contains(at(1, -1)));
+        assert!(!grid.contains(at(0, 0)));
+        assert_eq!(grid.to_string(), "....#\n.....\n.....\n#....");
+
+        assert_eq!(grid.remove(at(-2, 3)), Some('#'));
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn test_rows_columns_and_rays() {
+        let grid = digits("123\n456\n789");
+
+        let values = |cells: Vec<(Position, &u32)>| {
+            cells
+                .into_iter()
+                .map(|(_, value)| *value)
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(values(grid.row(1).collect()), [4, 5, 6]);
+        assert_eq!(values(grid.column(2).collect()), [3, 6, 9]);
+        assert_eq!(
+            values(grid.ray(at(2, 2), Direction8::NorthWest.step()).collect()),
+            [5, 1]
+        );
+        assert_eq!(
+            values(grid.ray(at(1, 0), Direction4::West.step()).collect()),
+            []
+        );
+        assert_eq!(values(grid.iter().collect()), [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = digits("123\n456\n789");
+
+        let mut neighbors = grid
+            .neighbors4(at(0, 0))
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        neighbors.sort();
+        assert_eq!(neighbors, [at(1, 0), at(0, 1)]);
+
+        assert_eq!(grid.neighbors4(at(1, 1)).count(), 4);
+        assert_eq!(grid.neighbors8(at(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(at(2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn test_wrapping_step() {
+        let grid = digits("123\n456");
+
+        assert_eq!(
+            grid.wrapping_step(at(0, 2), Direction4::East.step()),
+            at(0, 0)
+        );
+        assert_eq!(
+            grid.wrapping_step(at(0, 0), Direction4::North.step()),
+            at(1, 0)
+        );
+        assert_eq!(
+            grid.wrapping_step(at(1, 1), Direction4::West.step()),
+            at(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut grid = Grid::new(0, 2, '.');
+
+        grid.push_row(vec!['#', '#']);
+        grid.push_row(vec!['.', '.']);
+        grid.push_row(vec!['#', '.']);
+        assert_eq!(grid.to_string(), "##\n..\n#.");
+
+        grid.retain_rows(|row| row.contains(&'#'));
+        assert_eq!(grid.to_string(), "##\n#.");
+        assert_eq!(grid.row_slice(1), ['#', '.']);
+
+        grid.remove_rows(0..1);
+        assert_eq!(grid.to_string(), "#.");
+        assert_eq!(grid.size(), (1, 2));
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+pub const INPUTS_DIR: &str = "inputs";
+
+/// Where to read a day's input from.
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    /// A file in the inputs directory, named after the day and an optional variant, like "day23"
+    /// (inputs/day23.txt) or "day23_small_test" (inputs/day23_small_test.txt).
+    Named(String),
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            Source::Named(name) => fs::read_to_string(path(name)),
+            Source::Path(path) => fs::read_to_string(path),
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Named(name) => write!(f, "{}", name),
+            Source::Path(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+pub fn path(name: &str) -> PathBuf {
+    Path::new(INPUTS_DIR).join(format!("{}.txt", name))
+}
+
+// Splits an input name into the day and the variant, if any: "day23_small_test" is the
+// "small_test" variant of day23.
+pub fn split_name(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('_') {
+        Some((day, variant)) => (day, Some(variant)),
+        None => (name, None),
+    }
+}
+
+// The variants with a file in the inputs directory for the given day, sorted by name. The real
+// input (inputs/day23.txt) isn't a variant.
+pub fn variants(day: &str) -> Vec<String> {
+    let entries = match fs::read_dir(INPUTS_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let file_names = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect::<Vec<String>>();
+
+    variants_in(day, &file_names)
+}
+
+fn variants_in(day: &str, file_names: &[String]) -> Vec<String> {
+    let mut variants = file_names
+        .iter()
+        .filter_map(|file_name| {
+            let (file_day, variant) = split_name(file_name.strip_suffix(".txt")?);
+
+            if file_day == day {
+                variant.map(str::to_string)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<String>>();
+
+    variants.sort();
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(split_name("day23"), ("day23", None));
+        assert_eq!(split_name("day23_test"), ("day23", Some("test")));
+        assert_eq!(
+            split_name("day23_small_test"),
+            ("day23", Some("small_test"))
+        );
+    }
+
+    #[test]
+    fn test_variants_in() {
+        let file_names = [
+            "day23_test.txt",
+            "day2_test.txt",
+            "day23.txt",
+            "day23_small_test.txt",
+            "notes.md",
+        ]
+        .map(str::to_string);
+
+        assert_eq!(variants_in("day23", &file_names), ["small_test", "test"]);
+        assert_eq!(variants_in("day2", &file_names), ["test"]);
+        assert!(variants_in("day24", &file_names).is_empty());
+    }
+}
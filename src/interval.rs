@@ -0,0 +1,266 @@
+use std::ops::Range;
+
+/// A set of integers, stored as the sorted ranges of consecutive integers it contains. Ranges
+/// that overlap or touch are merged when inserted, so each range is separated from the next one by
+/// a gap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // The intervals that overlap or touch the range, which merge with it.
+        let first = self
+            .intervals
+            .partition_point(|interval| interval.end < range.start);
+        let last = self
+            .intervals
+            .partition_point(|interval| interval.start <= range.end);
+
+        let mut merged = range;
+
+        if first < last {
+            merged.start = merged.start.min(self.intervals[first].start);
+            merged.end = merged.end.max(self.intervals[last - 1].end);
+        }
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut union = self.clone();
+
+        for interval in &other.intervals {
+            union.insert(interval.clone());
+        }
+
+        union
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+
+        for interval in &self.intervals {
+            for other in other.overlapping(interval) {
+                intervals.push(interval.start.max(other.start)..interval.end.min(other.end));
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    // The integers of this set that are not in the other one.
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+
+        for interval in &self.intervals {
+            let mut start = interval.start;
+
+            for other in other.overlapping(interval) {
+                if other.start > start {
+                    intervals.push(start..other.start);
+                }
+
+                start = other.end;
+            }
+
+            if start < interval.end {
+                intervals.push(start..interval.end);
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.end <= value);
+        self.intervals
+            .get(index)
+            .is_some_and(|interval| interval.start <= value)
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn is_disjoint(&self, other: &IntervalSet) -> bool {
+        self.intervals
+            .iter()
+            .all(|interval| other.overlapping(interval).is_empty())
+    }
+
+    // The number of integers in the set.
+    pub fn len(&self) -> i64 {
+        self.intervals
+            .iter()
+            .map(|interval| interval.end - interval.start)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // The ranges of integers in the set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.intervals.iter().cloned()
+    }
+
+    // The ranges of integers missing between the smallest and the largest ones of the set, in
+    // order.
+    pub fn gaps(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.intervals
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+    }
+
+    // The intervals of this set that overlap the range.
+    fn overlapping(&self, range: &Range<i64>) -> &[Range<i64>] {
+        let first = self
+            .intervals
+            .partition_point(|interval| interval.end <= range.start);
+        let last = self
+            .intervals
+            .partition_point(|interval| interval.start < range.end);
+
+        &self.intervals[first..last.max(first)]
+    }
+}
+
+impl From<Range<i64>> for IntervalSet {
+    fn from(range: Range<i64>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(ranges: I) -> Self {
+        let mut set = IntervalSet::new();
+
+        for range in ranges {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_insert() {
+        let mut set = IntervalSet::new();
+
+        set.insert(5..8);
+        set.insert(1..3);
+        set.insert(10..12);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [1..3, 5..8, 10..12]);
+
+        // Touching ranges are merged, and so are the ones a range overlaps.
+        set.insert(3..4);
+        set.insert(7..10);
+        set.insert(20..20);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [1..4, 5..12]);
+
+        assert_eq!(set.len(), 10);
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![4..5]);
+        assert!(set.contains(11));
+        assert!(!set.contains(4));
+        assert!(!set.contains(12));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = IntervalSet::from_iter([0..10, 20..30]);
+        let b = IntervalSet::from(5..25);
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b), IntervalSet::from_iter([5..10, 20..25]));
+        assert_eq!(a.difference(&b), IntervalSet::from_iter([0..5, 25..30]));
+        assert_eq!(b.difference(&a), IntervalSet::from(10..20));
+
+        assert!(a.is_superset(&IntervalSet::from(21..29)));
+        assert!(!a.is_superset(&b));
+        assert!(a.is_disjoint(&IntervalSet::from(10..20)));
+        assert!(!a.is_disjoint(&b));
+    }
+
+    // A model of the set, with every integer in it.
+    fn values(set: &IntervalSet) -> BTreeSet<i64> {
+        set.iter().flatten().collect()
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<Range<i64>>> {
+        prop::collection::vec((-50_i64..50, 0_i64..20), 0..8).prop_map(|ranges| {
+            ranges
+                .into_iter()
+                .map(|(start, len)| start..start + len)
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn intervals_are_sorted_and_apart(ranges in ranges()) {
+            let set = IntervalSet::from_iter(ranges.clone());
+
+            for interval in set.iter() {
+                prop_assert!(!interval.is_empty());
+            }
+
+            for gap in set.gaps() {
+                prop_assert!(!gap.is_empty());
+            }
+
+            let expected = ranges.into_iter().flatten().collect::<BTreeSet<i64>>();
+            prop_assert_eq!(values(&set), expected.clone());
+            prop_assert_eq!(set.len(), expected.len() as i64);
+        }
+
+        #[test]
+        fn operations_match_the_model(a in ranges(), b in ranges(), value in -60_i64..80) {
+            let (a, b) = (IntervalSet::from_iter(a), IntervalSet::from_iter(b));
+            let (model_a, model_b) = (values(&a), values(&b));
+
+            prop_assert_eq!(values(&a.union(&b)), &model_a | &model_b);
+            prop_assert_eq!(values(&a.intersection(&b)), &model_a & &model_b);
+            prop_assert_eq!(values(&a.difference(&b)), &model_a - &model_b);
+            prop_assert_eq!(a.contains(value), model_a.contains(&value));
+            prop_assert_eq!(a.is_superset(&b), model_a.is_superset(&model_b));
+            prop_assert_eq!(a.is_disjoint(&b), model_a.is_disjoint(&model_b));
+        }
+
+        #[test]
+        fn gaps_are_the_missing_values(ranges in ranges()) {
+            let set = IntervalSet::from_iter(ranges);
+            let model = values(&set);
+
+            if let (Some(min), Some(max)) = (model.first(), model.last()) {
+                let missing = (*min..=*max)
+                    .filter(|value| !model.contains(value))
+                    .collect::<BTreeSet<i64>>();
+
+                prop_assert_eq!(set.gaps().flatten().collect::<BTreeSet<i64>>(), missing);
+            }
+        }
+    }
+}
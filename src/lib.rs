@@ -0,0 +1,103 @@
+//! Solutions to Advent of Code 2022, as a library.
+//!
+//! Each day lives in its own module (`day1` to `day25`), with a type implementing
+//! [`Solution`](solution::Solution) that parses the input and solves both parts, next to the
+//! day's core types, like [`day13::LinkedList`] (packets and their ordering) or [`day25::SNAFU`]
+//! (the SNAFU number codec). [`DAYS`] lists every day by name for running them uniformly.
+//!
+//! ```no_run
+//! use aoc22::{day25::SNAFU, solution::Solution};
+//!
+//! let number: SNAFU = "2=-01".parse().unwrap();
+//! assert_eq!(number.to_int(), 976);
+//!
+//! let input = std::fs::read_to_string("inputs/day13.txt").unwrap();
+//! let packets = aoc22::day13::Day13::parse(&input).unwrap();
+//! println!("{}", aoc22::day13::Day13::part1(&packets));
+//! ```
+
+pub mod answers;
+pub mod cycle;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod differential;
+pub mod error;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod parse;
+pub mod random;
+pub mod search;
+pub mod solution;
+pub mod visualize;
+
+use solution::{run, run_reference, Day, Solution};
+
+macro_rules! days {
+    ($($module:ident::$solution:ident),* $(,)?) => {
+        [$(Day {
+            name: stringify!($module),
+            params: <$module::$solution as Solution>::PARAMS,
+            run: run::<$module::$solution>,
+            generate: <$module::$solution as Solution>::generate,
+            reference: run_reference::<$module::$solution>,
+        }),*]
+    };
+}
+
+pub const DAYS: &[Day] = &days![
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::Day7,
+    day8::Day8,
+    day9::Day9,
+    day10::Day10,
+    day11::Day11,
+    day12::Day12,
+    day13::Day13,
+    day14::Day14,
+    day15::Day15,
+    day16::Day16,
+    day17::Day17,
+    day18::Day18,
+    day19::Day19,
+    day20::Day20,
+    day21::Day21,
+    day22::Day22,
+    day23::Day23,
+    day24::Day24,
+    day25::Day25,
+];
+
+// Finds a day by its name, like "day11" (without a variant).
+pub fn find_day(name: &str) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.name == name)
+}
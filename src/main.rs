@@ -1,91 +1,1212 @@
-use std::{env, fs, time::Instant};
-
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use std::{
+    cell::RefCell,
+    env, fmt, fs,
+    path::PathBuf,
+    process,
+    rc::Rc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use aoc22::{
+    answers, differential,
+    error::Error,
+    input::{self, Source},
+    random::Rng,
+    solution::{self, Answer, Day, Report, Verbosity},
+    visualize, DAYS,
+};
+use export::{ExportOptions, Exporter, ImageFormat};
+use json::Json;
+use player::Player;
+
+mod bench;
+mod export;
+mod json;
+mod parallel;
+mod player;
+mod scaffold;
+
+// What the size of generated inputs counts depends on the day, like monkeys for day 11.
+const DEFAULT_GENERATED_SIZE: usize = 10;
+
+// Generated inputs are compared on small sizes, for the reference solvers.
+const DEFAULT_COMPARED_SIZE: usize = 5;
+const DEFAULT_COMPARED_SEEDS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Human,
+    // One JSON object per line for each run, see `report_json`.
+    Json,
+}
 
 fn main() {
-    let day_arg = env::args()
-        .nth(1)
-        .expect("Specify a day (day11) or a day with tests (day11_test)");
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+
+    let format = take_verbosity(&mut args)
+        .map(solution::set_verbosity)
+        .and_then(|_| take_format(&mut args));
+
+    let result = format.and_then(|format| {
+        let command = args.first().ok_or_else(|| {
+            Error::new(
+                "Specify a day (day11), a day with a variant (day11_test), \"all\", \"verify\", \"bench\", \"variants\", \"params\", \"new\", \"generate\", or \"compare\"",
+            )
+        })?;
+
+        if format == Format::Json {
+            solution::capture_diagnostics(true);
+        }
+
+        match (command.as_str(), format) {
+            ("all", _) => run_all(&args[1..], format),
+            (_, Format::Json) if ["bench", "compare", "generate", "new", "params", "variants", "verify"].contains(&command.as_str()) => {
+                Err(Error::new(format!("{} doesn't support --format json", command)))
+            }
+            ("bench", _) => run_bench(&args[1..]),
+            ("compare", _) => compare(&args[1..]),
+            ("generate", _) => generate(&args[1..]),
+            ("new", _) => new_day(&args[1..]),
+            ("params", _) => {
+                list_params(&args[1..]);
+                Ok(())
+            }
+            ("variants", _) => {
+                list_variants(&args[1..]);
+                Ok(())
+            }
+            ("verify", _) => verify(),
+            _ => run_one(&args, format),
+        }
+    });
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+// Removes "--format <format>" from the arguments, wherever it is.
+fn take_format(args: &mut Vec<String>) -> Result<Format, Error> {
+    let index = match args.iter().position(|arg| arg == "--format") {
+        Some(index) => index,
+        None => return Ok(Format::Human),
+    };
+
+    args.remove(index);
+
+    if index == args.len() {
+        return Err(Error::new("--format expects human or json"));
+    }
+
+    match args.remove(index).as_str() {
+        "human" => Ok(Format::Human),
+        "json" => Ok(Format::Json),
+        format => Err(Error::new(format!(
+            "unknown format `{}`, expected human or json",
+            format
+        ))),
+    }
+}
+
+// Removes "--quiet", "--verbose" or "--trace" from the arguments, wherever it is.
+fn take_verbosity(args: &mut Vec<String>) -> Result<Verbosity, Error> {
+    let mut verbosities = Vec::new();
+
+    args.retain(|arg| {
+        let verbosity = match arg.as_str() {
+            "--quiet" => Verbosity::Quiet,
+            "--verbose" => Verbosity::Verbose,
+            "--trace" => Verbosity::Trace,
+            _ => return true,
+        };
+
+        verbosities.push(verbosity);
+        false
+    });
+
+    match verbosities[..] {
+        [] => Ok(Verbosity::Normal),
+        [verbosity] => Ok(verbosity),
+        _ => Err(Error::new(
+            "only one of --quiet, --verbose and --trace can be given",
+        )),
+    }
+}
+
+// Finds the day of an input name like "day11" or "day23_small_test".
+fn find_day(input_name: &str) -> Result<&'static Day, Error> {
+    let (day_name, _variant) = input::split_name(input_name);
+
+    aoc22::find_day(day_name).ok_or_else(|| Error::new("no such day").in_day(input_name))
+}
+
+// Runs a day, reporting errors in the given input.
+fn run_day(day: &Day, input_name: &str, input: &str) -> Result<Report, Error> {
+    (day.run)(input).map_err(|error| error.in_day(input_name))
+}
+
+// Usage: <day>[_<variant>] [--variant <variant> | --input <path> | -] [--param <name>=<value>]...
+// [--visualize] [--fps N] [--export <dir> [--image png|ppm|none] [--gif] [--scale N] [--size WxH]
+// [--every N]] where "-" (or "--input -") reads the input from stdin.
+fn run_one(args: &[String], format: Format) -> Result<(), Error> {
+    let day_arg = &args[0];
+    let day = find_day(day_arg)?;
+
+    let mut source = Source::Named(day_arg.to_string());
+    let mut overrides = Vec::new();
+    let mut visualize = false;
+    let mut fps = 30;
+    let mut export = None;
+    let mut image_format = ImageFormat::Png;
+    let mut gif = false;
+    let mut scale = 4;
+    let mut size = (160, 120);
+    let mut every = 1;
+    let mut args = args[1..].iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-" => source = Source::Stdin,
+            "--input" => match args.next().map(String::as_str) {
+                Some("-") => source = Source::Stdin,
+                Some(path) => source = Source::Path(PathBuf::from(path)),
+                None => return Err(Error::new("--input expects a path, or - for stdin")),
+            },
+            "--variant" => match args.next() {
+                Some(variant) => source = Source::Named(format!("{}_{}", day.name, variant)),
+                None => return Err(Error::new("--variant expects a variant name, like test")),
+            },
+            "--param" => overrides.push(parse_param(day, args.next())?),
+            "--visualize" => visualize = true,
+            "--fps" => fps = parse_count(args.next(), "--fps")?.max(1),
+            "--export" => match args.next() {
+                Some(directory) => export = Some(PathBuf::from(directory)),
+                None => return Err(Error::new("--export expects a directory")),
+            },
+            "--image" => match args.next() {
+                Some(image) => image_format = ImageFormat::parse(image)?,
+                None => return Err(Error::new("--image expects png, ppm or none")),
+            },
+            "--gif" => gif = true,
+            "--scale" => scale = parse_count(args.next(), "--scale")?.max(1),
+            "--size" => size = parse_size(args.next())?,
+            "--every" => every = parse_count(args.next(), "--every")?.max(1),
+            _ => return Err(Error::new(format!("unknown argument `{}`", arg))),
+        }
+    }
+
+    if (visualize || export.is_some()) && format == Format::Json {
+        return Err(Error::new(
+            "--visualize and --export don't support --format json",
+        ));
+    }
+
+    solution::override_params(overrides);
+
+    let input = read_input(&source)?;
+
+    let input_name = match &source {
+        Source::Named(name) => name.as_str(),
+        _ => day.name,
+    };
+
+    if format == Format::Json {
+        let result = run_day(day, input_name, &input);
+        println!("{}", report_json(day, &source, &result));
+        return result.map(|_| ());
+    }
 
-    let day = if day_arg.ends_with("_test") {
-        &day_arg[0..day_arg.len() - 5]
+    // Quiet runs only print the answers.
+    let quiet = is_quiet();
+    print_header(format_args!("Running {source}"));
+
+    let report = if visualize || export.is_some() {
+        let player = visualize.then(|| Player::new(fps as f64, source != Source::Stdin));
+        let exporter = match export {
+            Some(directory) => {
+                let options = ExportOptions {
+                    directory,
+                    format: image_format,
+                    gif,
+                    scale,
+                    width: size.0,
+                    height: size.1,
+                    every,
+                    fps,
+                };
+
+                Some(Exporter::new(options, input_name)?)
+            }
+            None => None,
+        };
+
+        run_visualized(day, input_name, &input, player, exporter)?
     } else {
-        day_arg.as_str()
+        run_day(day, input_name, &input)?
     };
 
-    let input = read_file_for_day(&day_arg);
+    println!("Part 1: {}", report.part1);
+    println!("Part 2: {}", report.part2);
+
+    if !quiet {
+        println!("\nCompleted {} in {:.2?}", source, report.total_time());
+    }
+
+    Ok(())
+}
+
+// Runs a day while playing the frames its simulations emit in the terminal, exporting them to
+// files, or both. The player's controls are read from stdin, unless the input comes from there.
+// Diagnostics are left out while playing, since they would scroll the frames away.
+fn run_visualized(
+    day: &Day,
+    input_name: &str,
+    input: &str,
+    mut player: Option<Player>,
+    exporter: Option<Exporter>,
+) -> Result<Report, Error> {
+    let shown = player.as_ref().map(Player::shown);
+    let exporter = exporter.map(|exporter| Rc::new(RefCell::new(exporter)));
+    let exporting = exporter.clone();
+
+    solution::capture_diagnostics(player.is_some());
+    visualize::play(move |frame| {
+        if let Some(exporter) = &exporting {
+            exporter.borrow_mut().export(&frame);
+        }
+
+        if let Some(player) = &mut player {
+            player.show(frame);
+        }
+    });
+
+    let report = run_day(day, input_name, input);
+
+    visualize::stop_playing();
+    solution::capture_diagnostics(false);
+
+    let mut frames = shown.map_or(0, |shown| shown.get());
+
+    if let Some(exporter) = exporter {
+        let mut exporter = exporter.borrow_mut();
+        let exported = exporter.finish()?;
+
+        frames = frames.max(exporter.received());
+
+        if exported > 0 {
+            println!("Exported {} of {} frames\n", exported, exporter.received());
+        }
+    }
+
+    if frames == 0 {
+        println!("{} has nothing to visualize\n", day.name);
+    }
+
+    report
+}
+
+// Parses a "<name>=<value>" override of one of the day's parameters.
+fn parse_param(day: &Day, arg: Option<&String>) -> Result<(&'static str, i64), Error> {
+    let (name, value) = arg
+        .and_then(|arg| arg.split_once('='))
+        .ok_or_else(|| Error::new("--param expects <name>=<value>, like rounds=20"))?;
+
+    day.param(name, value)
+}
 
-    println!("== Running {day_arg} ==\n");
+// Lists the parameters of each day (or only of the given days) with their defaults.
+fn list_params(args: &[String]) {
+    let days = DAYS
+        .iter()
+        .filter(|day| !day.params.is_empty())
+        .filter(|day| args.is_empty() || args.iter().any(|arg| arg == day.name))
+        .collect::<Vec<&Day>>();
 
+    let width = days
+        .iter()
+        .flat_map(|day| day.params)
+        .map(|param| format!("{}={}", param.name, param.default).len())
+        .max()
+        .unwrap_or(0);
+
+    for day in days {
+        println!("{}", day.name);
+
+        for param in day.params {
+            let default = format!("{}={}", param.name, param.default);
+            println!("  {:<width$}  {}", default, param.description);
+        }
+    }
+}
+
+// Lists the inputs that exist for each day (or only for the given days).
+fn list_variants(args: &[String]) {
+    let days = DAYS
+        .iter()
+        .filter(|day| args.is_empty() || args.iter().any(|arg| arg == day.name))
+        .collect::<Vec<&Day>>();
+
+    let width = days.iter().map(|day| day.name.len()).max().unwrap_or(0);
+
+    for day in days {
+        let mut inputs = Vec::new();
+
+        if input::path(day.name).exists() {
+            inputs.push("(real)".to_string());
+        }
+
+        inputs.extend(input::variants(day.name));
+
+        if inputs.is_empty() {
+            println!("{:<width$}  (no inputs)", day.name);
+        } else {
+            println!("{:<width$}  {}", day.name, inputs.join(", "));
+        }
+    }
+}
+
+// Runs every day that has an input file and prints a summary table of answers and timings (or a
+// JSON object per day). Days that fail, or panic, don't stop the others.
+// Usage: all [--jobs N]
+fn run_all(args: &[String], format: Format) -> Result<(), Error> {
+    let mut jobs = 1;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jobs" => jobs = parse_count(args.next(), "--jobs")?.max(1),
+            arg => return Err(Error::new(format!("unknown argument `{}`", arg))),
+        }
+    }
+
+    // With more than one job, the output of each day is printed once it's done so that days
+    // don't interleave, rather than as it happens.
+    let buffered = format == Format::Json || jobs > 1;
+    solution::capture_diagnostics(buffered);
+
+    let mut rows = Vec::new();
+    let mut tasks = Vec::new();
+
+    for day in DAYS.iter() {
+        match Source::Named(day.name.to_string()).read() {
+            Ok(input) => {
+                rows.push((day.name, Err("(error)")));
+                tasks.push((day, rows.len() - 1, input));
+            }
+            Err(_) => rows.push((day.name, Err("(no input)"))),
+        }
+    }
+
+    let mut errors = Vec::new();
     let start_time = Instant::now();
+    let start_cpu_time = parallel::cpu_time();
+
+    // Panics are reported as errors of their day once it's done.
+    parallel::quiet_panics(|| {
+        parallel::run(
+            &tasks,
+            jobs,
+            |(day, _, input)| {
+                if !buffered {
+                    print_header(format_args!("Running {}", day.name));
+                }
+
+                parallel::catch_panic(|| run_day(day, day.name, input)).unwrap_or_else(|message| {
+                    Err(Error::new(format!("panicked: {}", message)).in_day(day.name))
+                })
+            },
+            |index, result| {
+                let (day, row, _) = &tasks[index];
+
+                match format {
+                    Format::Json => println!(
+                        "{}",
+                        report_json(day, &Source::Named(day.name.to_string()), &result)
+                    ),
+                    Format::Human if buffered => {
+                        print_header(format_args!("Running {}", day.name));
+
+                        for diagnostic in result.iter().flat_map(|report| &report.diagnostics) {
+                            println!("{}", diagnostic);
+                        }
+                    }
+                    Format::Human => (),
+                }
+
+                match result {
+                    Ok(report) => rows[*row].1 = Ok(report),
+                    Err(error) => errors.push((*row, error)),
+                }
+            },
+        )
+    });
+
+    let wall_time = start_time.elapsed();
+    let cpu_time = start_cpu_time
+        .zip(parallel::cpu_time())
+        .map(|(start, end)| end.saturating_sub(start));
+
+    if format == Format::Human {
+        if !is_quiet() {
+            println!();
+        }
+
+        print!("{}", summary_table(&rows));
+        println!();
+        println!("{}", time_summary(&rows, wall_time, cpu_time, jobs));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        if format == Format::Human {
+            println!();
+        }
+
+        // Days finish in any order with more than one job.
+        errors.sort_by_key(|(row, _)| *row);
+
+        for (_, error) in &errors {
+            eprintln!("{}", error);
+        }
+
+        Err(Error::new(format!("{} days failed", errors.len())))
+    }
+}
+
+// How long running all days took, compared with the CPU time they used. Where the CPU time isn't
+// available, it's the sum of the time each day took instead (which is how long they'd take one
+// after the other).
+fn time_summary(
+    rows: &[(&str, Result<Report, &str>)],
+    wall_time: Duration,
+    cpu_time: Option<Duration>,
+    jobs: usize,
+) -> String {
+    let (label, time) = match cpu_time {
+        Some(cpu_time) => ("CPU time", cpu_time),
+        None => (
+            "summed day time",
+            rows.iter()
+                .filter_map(|(_, report)| report.as_ref().ok())
+                .map(Report::total_time)
+                .sum::<Duration>(),
+        ),
+    };
+
+    format!(
+        "Wall-clock time: {:.2?} on {} {}, {}: {:.2?} ({:.2}x)",
+        wall_time,
+        jobs,
+        if jobs == 1 { "thread" } else { "threads" },
+        label,
+        time,
+        time.as_secs_f64() / wall_time.as_secs_f64().max(f64::MIN_POSITIVE),
+    )
+}
+
+// Benchmarks a day (or every day with "all") and compares the median timings with the saved
+// baseline, if there is one. Usage: bench <day> [--warmup N] [--runs N] [--save]
+fn run_bench(args: &[String]) -> Result<(), Error> {
+    let mut inputs = Vec::new();
+    let mut warmup = 1;
+    let mut runs = 10;
+    let mut save = false;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--warmup" => warmup = parse_count(args.next(), "--warmup")?,
+            "--runs" => runs = parse_count(args.next(), "--runs")?.max(1),
+            "--save" => save = true,
+            "all" => inputs.extend(DAYS.iter().map(|day| day.name.to_string())),
+            input => inputs.push(input.to_string()),
+        }
+    }
+
+    let mut benches = Vec::new();
+
+    for input_name in inputs {
+        let day = find_day(&input_name)?;
+
+        // Days without an input can't be benchmarked, so they're skipped like in "all".
+        let input = match Source::Named(input_name.clone()).read() {
+            Ok(input) => input,
+            Err(_) => continue,
+        };
+
+        print_header(format_args!(
+            "Benchmarking {input_name} ({warmup} warmup, {runs} runs)"
+        ));
+
+        let bench = bench::bench(&input_name, day, &input, warmup, runs)
+            .map_err(|error| error.in_day(&input_name))?;
+        benches.push(bench);
+    }
+
+    let baseline = bench::load_baseline()?;
+
+    if !is_quiet() {
+        println!();
+    }
+
+    print!("{}", bench::table(&benches, &baseline));
+
+    if save {
+        bench::save_baseline(baseline, &benches)?;
+        println!("\nSaved baseline to {}", bench::BASELINE_FILE);
+    }
+
+    Ok(())
+}
+
+// Usage: new <day>
+fn new_day(args: &[String]) -> Result<(), Error> {
+    let name = match args {
+        [name] => name,
+        _ => return Err(Error::new("new expects a day, like `new day26`")),
+    };
+
+    for path in scaffold::new_day(name)? {
+        println!("Wrote {}", path);
+    }
+
+    Ok(())
+}
+
+// Usage: generate <day> [--seed N] [--size N] [--param <name>=<value>]... [--save <variant>]
+// Prints a random input for the day, or saves it as a variant of its inputs. Without a seed, a new
+// one is picked and reported, so that the same input can be generated again.
+fn generate(args: &[String]) -> Result<(), Error> {
+    let day_arg = args
+        .first()
+        .ok_or_else(|| Error::new("generate expects a day, like `generate day11`"))?;
+    let day = aoc22::find_day(day_arg).ok_or_else(|| Error::new("no such day").in_day(day_arg))?;
+
+    let mut seed = None;
+    let mut size = DEFAULT_GENERATED_SIZE;
+    let mut overrides = Vec::new();
+    let mut variant = None;
+    let mut args = args[1..].iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_count(args.next(), "--seed")? as u64),
+            "--size" => size = parse_count(args.next(), "--size")?,
+            "--param" => overrides.push(parse_param(day, args.next())?),
+            "--save" => match args.next() {
+                Some(name) => variant = Some(name),
+                None => return Err(Error::new("--save expects a variant name, like gen")),
+            },
+            _ => return Err(Error::new(format!("unknown argument `{}`", arg))),
+        }
+    }
+
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+
+    // Some generators depend on the parameters, like the search area of day 15.
+    solution::override_params(overrides);
+
+    let input = (day.generate)(&mut Rng::new(seed), size)
+        .ok_or_else(|| Error::new("no input generator").in_day(day.name))?;
+
+    let Some(variant) = variant else {
+        print!("{}", input);
+        eprintln!(
+            "Generated {} with seed {} and size {}",
+            day.name, seed, size
+        );
+        return Ok(());
+    };
+
+    let path = input::path(&format!("{}_{}", day.name, variant));
+
+    if path.exists() {
+        return Err(Error::new(format!("{} already exists", path.display())));
+    }
+
+    fs::write(&path, input)
+        .map_err(|error| Error::new(format!("could not write {}: {}", path.display(), error)))?;
+    println!("Wrote {} (seed {}, size {})", path.display(), seed, size);
+
+    Ok(())
+}
+
+// Usage: compare <day>|all [--seeds N] [--size N] [--param <name>=<value>]...
+// Runs days and their reference solvers (see `Solution::reference`) on the day's test inputs and
+// on a generated input for each seed from 1, and shows the first input they disagree on, made as
+// small as possible. Fails if any day disagrees, or fails.
+fn compare(args: &[String]) -> Result<(), Error> {
+    let day_arg = args
+        .first()
+        .ok_or_else(|| Error::new("compare expects a day or all, like `compare day16`"))?;
+
+    let days = match day_arg.as_str() {
+        "all" => DAYS.iter().collect::<Vec<&Day>>(),
+        day_arg => {
+            vec![aoc22::find_day(day_arg)
+                .ok_or_else(|| Error::new("no such day").in_day(day_arg))?]
+        }
+    };
+
+    let mut seeds = DEFAULT_COMPARED_SEEDS;
+    let mut size = DEFAULT_COMPARED_SIZE;
+    let mut overrides = Vec::new();
+    let mut args = args[1..].iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seeds" => seeds = parse_count(args.next(), "--seeds")? as u64,
+            "--size" => size = parse_count(args.next(), "--size")?,
+            "--param" if days.len() == 1 => overrides.push(parse_param(days[0], args.next())?),
+            "--param" => return Err(Error::new("--param needs a single day")),
+            _ => return Err(Error::new(format!("unknown argument `{}`", arg))),
+        }
+    }
+
+    solution::override_params(overrides);
+    solution::capture_diagnostics(true);
+
+    // Panics are reported with the input that caused them, and are expected while minimising.
+    let failed = parallel::quiet_panics(|| {
+        days.iter()
+            .filter(|day| !compare_day(day, seeds, size))
+            .map(|day| day.name)
+            .collect::<Vec<&str>>()
+    });
+
+    if !failed.is_empty() {
+        return Err(Error::new(format!(
+            "{} disagreed with their reference solvers",
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+// Compares a day with its reference solver, returning false at the first input they disagree on.
+fn compare_day(day: &Day, seeds: u64, size: usize) -> bool {
+    let compare = |input: &str| parallel::catch_panic(|| differential::compare(day, input));
+    let disagrees =
+        |input: &str| matches!(compare(input), Ok(Ok(Some(mismatches))) if !mismatches.is_empty());
+
+    // The test inputs, then the generated ones with their seed.
+    let mut inputs = input::variants(day.name)
+        .into_iter()
+        .filter(|variant| variant.ends_with("test"))
+        .filter_map(|variant| {
+            let input_name = format!("{}_{}", day.name, variant);
+            let input = Source::Named(input_name.clone()).read().ok()?;
+            Some((input_name, input, None))
+        })
+        .collect::<Vec<(String, String, Option<u64>)>>();
+
+    for seed in 1..=seeds {
+        if let Some(input) = (day.generate)(&mut Rng::new(seed), size) {
+            inputs.push((format!("seed {}, size {}", seed, size), input, Some(seed)));
+        }
+    }
+
+    let mut compared = 0;
 
-    match day {
-        "day1" => day1::run(input.as_str()),
-        "day2" => day2::run(input.as_str()),
-        "day3" => day3::run(input.as_str()),
-        "day4" => day4::run(input.as_str()),
-        "day5" => day5::run(input.as_str()),
-        "day6" => day6::run(input.as_str()),
-        "day7" => day7::run(input.as_str()),
-        "day8" => day8::run(input.as_str()),
-        "day9" => day9::run(input.as_str()),
-        "day10" => day10::run(input.as_str()),
-        "day11" => day11::run(input.as_str()),
-        "day12" => day12::run(input.as_str()),
-        "day13" => day13::run(input.as_str()),
-        "day14" => day14::run(input.as_str()),
-        "day15" => day15::run(input.as_str()),
-        "day16" => day16::run(input.as_str()),
-        "day17" => day17::run(input.as_str()),
-        "day18" => day18::run(input.as_str()),
-        "day19" => day19::run(input.as_str()),
-        "day20" => day20::run(input.as_str()),
-        "day21" => day21::run(input.as_str()),
-        "day22" => day22::run(input.as_str()),
-        "day23" => day23::run(input.as_str()),
-        "day24" => day24::run(input.as_str()),
-        "day25" => day25::run(input.as_str()),
-        _ => println!("No such day: {}", day),
-    }
-
-    println!("\nCompleted {} in {:.2?}", day_arg, start_time.elapsed());
-}
-
-fn read_file_for_day(day: &str) -> String {
-    let contents = fs::read_to_string(format!("inputs/{day}.txt"));
-
-    match contents {
-        Ok(contents) => contents,
-        Err(_) => {
-            if day.ends_with("_test") {
-                panic!("No test input file found for day {}", day);
-            } else {
-                panic!("{day} not implemented yet");
+    for (name, input, seed) in inputs {
+        let mismatches = match compare(&input) {
+            Ok(Ok(Some(mismatches))) => mismatches,
+            Ok(Ok(None)) => {
+                println!("{}: no reference solver", day.name);
+                return true;
+            }
+            Ok(Err(error)) => {
+                println!("{} ({}): {}", day.name, name, error);
+                return false;
+            }
+            Err(message) => {
+                println!("{} ({}): panicked: {}", day.name, name, message);
+                return false;
+            }
+        };
+
+        if mismatches.is_empty() {
+            compared += 1;
+            continue;
+        }
+
+        for mismatch in mismatches {
+            println!("{} ({}): {}", day.name, name, mismatch);
+        }
+
+        // Smaller generated inputs are more likely to be readable than what's left of a larger
+        // one after removing lines.
+        let mut input = input;
+
+        if let Some(seed) = seed {
+            let Some((seed, size, smallest)) =
+                differential::smallest_generated(day, size, seed, disagrees)
+            else {
+                println!(
+                    "{} ({}): the disagreement couldn't be generated again",
+                    day.name, name
+                );
+                return false;
             };
+
+            println!(
+                "Smallest generated input that disagrees: seed {}, size {}",
+                seed, size
+            );
+            input = smallest;
+        }
+
+        let minimized = differential::minimize(&input, disagrees);
+        println!(
+            "Minimised from {} to {} lines:\n\n{}",
+            input.lines().count(),
+            minimized.lines().count(),
+            minimized
+        );
+
+        if let Ok(Ok(Some(mismatches))) = compare(&minimized) {
+            for mismatch in mismatches {
+                println!("{}", mismatch);
+            }
         }
+
+        println!();
+        return false;
+    }
+
+    println!("{}: agrees on {} inputs", day.name, compared);
+    true
+}
+
+// Parses a "<width>x<height>" size, in cells.
+fn parse_size(arg: Option<&String>) -> Result<(i64, i64), Error> {
+    arg.and_then(|size| size.split_once('x'))
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| Error::new("--size expects a size, like 160x120"))
+}
+
+fn parse_count(arg: Option<&String>, flag: &str) -> Result<usize, Error> {
+    arg.and_then(|count| count.parse().ok())
+        .ok_or_else(|| Error::new(format!("{} expects a number", flag)))
+}
+
+// Quiet runs only print the results, without the headers of the days they run.
+fn is_quiet() -> bool {
+    solution::verbosity() == Verbosity::Quiet
+}
+
+fn print_header(title: impl fmt::Display) {
+    if !is_quiet() {
+        println!("== {} ==\n", title);
+    }
+}
+
+// Runs every input listed in the answers file and compares the results with the expected answers.
+// Fails if any answer doesn't match.
+fn verify() -> Result<(), Error> {
+    let mut results = Vec::new();
+
+    for expected in answers::load()? {
+        let day = find_day(&expected.input)?;
+
+        let input = match Source::Named(expected.input.clone()).read() {
+            Ok(input) => input,
+            Err(_) => {
+                results.push((expected.input, vec!["no input file".to_string()]));
+                continue;
+            }
+        };
+
+        print_header(format_args!("Running {}", expected.input));
+
+        let mismatches = match expected
+            .overrides(day)
+            .map(solution::override_params)
+            .and_then(|_| run_day(day, &expected.input, &input))
+        {
+            Ok(report) => expected.mismatches(&report),
+            Err(error) => vec![error.to_string()],
+        };
+
+        results.push((expected.input, mismatches));
+    }
+
+    if !is_quiet() {
+        println!();
+    }
+
+    let width = results
+        .iter()
+        .map(|(input, _)| input.len())
+        .max()
+        .unwrap_or(0);
+    let mut failures = 0;
+
+    for (input, mismatches) in &results {
+        if mismatches.is_empty() {
+            println!("{:<width$}  ok", input);
+        } else {
+            failures += 1;
+            println!("{:<width$}  FAILED: {}", input, mismatches.join("; "));
+        }
+    }
+
+    println!("\n{} inputs verified, {} failed", results.len(), failures);
+
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "{} inputs failed verification",
+            failures
+        )))
+    }
+}
+
+// For example:
+// {"day":"day11","variant":"test","input":"day11_test","answers":{"part1":10605,"part2":2713310158},
+//  "timings":{"parse_ns":10233,"part1_ns":11549,"part2_ns":3179411,"total_ns":3201193},
+//  "diagnostics":[],"error":null}
+// Answers that aren't numbers are strings, and unsolved parts are null. When the day fails, the
+// answers and timings are null and the error is set.
+fn report_json(day: &Day, source: &Source, result: &Result<Report, Error>) -> Json {
+    let variant = match source {
+        Source::Named(name) => input::split_name(name).1,
+        _ => None,
+    };
+
+    let answer = |answer: &Answer| match answer {
+        Answer::Number(number) => Json::Number(*number),
+        Answer::Text(text) => Json::from(text.as_str()),
+        Answer::Unsolved => Json::Null,
+    };
+
+    let nanos = |time: Duration| Json::Number(time.as_nanos() as i128);
+
+    let (answers, timings, diagnostics, error) = match result {
+        Ok(report) => (
+            Json::Object(vec![
+                ("part1", answer(&report.part1)),
+                ("part2", answer(&report.part2)),
+            ]),
+            Json::Object(vec![
+                ("parse_ns", nanos(report.parse_time)),
+                ("part1_ns", nanos(report.part1_time)),
+                ("part2_ns", nanos(report.part2_time)),
+                ("total_ns", nanos(report.total_time())),
+            ]),
+            report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| Json::from(diagnostic.as_str()))
+                .collect(),
+            Json::Null,
+        ),
+        Err(error) => (Json::Null, Json::Null, vec![], error.to_string().into()),
+    };
+
+    Json::Object(vec![
+        ("day", day.name.into()),
+        ("variant", variant.into()),
+        ("input", source.to_string().into()),
+        ("answers", answers),
+        ("timings", timings),
+        ("diagnostics", Json::Array(diagnostics)),
+        ("error", error),
+    ])
+}
+
+// Rows without a report have a note (like "(no input)") instead.
+fn summary_table(rows: &[(&str, Result<Report, &str>)]) -> String {
+    let header = [
+        "Day", "Part 1", "Part 2", "Parse", "Part 1", "Part 2", "Total",
+    ];
+
+    let mut lines: Vec<[String; 7]> = Vec::new();
+    let mut totals = [Duration::ZERO; 4];
+
+    for (name, report) in rows {
+        match report {
+            Ok(report) => {
+                let times = [
+                    report.parse_time,
+                    report.part1_time,
+                    report.part2_time,
+                    report.total_time(),
+                ];
+
+                for (total, time) in totals.iter_mut().zip(times) {
+                    *total += time;
+                }
+
+                lines.push([
+                    name.to_string(),
+                    table_answer(&report.part1),
+                    table_answer(&report.part2),
+                    format!("{:.2?}", times[0]),
+                    format!("{:.2?}", times[1]),
+                    format!("{:.2?}", times[2]),
+                    format!("{:.2?}", times[3]),
+                ]);
+            }
+            Err(note) => lines.push([
+                name.to_string(),
+                note.to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]),
+        }
+    }
+
+    lines.push([
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        format!("{:.2?}", totals[0]),
+        format!("{:.2?}", totals[1]),
+        format!("{:.2?}", totals[2]),
+        format!("{:.2?}", totals[3]),
+    ]);
+
+    let mut widths = header.map(str::len);
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.map(str::to_string);
+    let separator = widths.map(|width| "-".repeat(width));
+    let (total_line, day_lines) = lines.split_last().unwrap();
+
+    let mut table = String::new();
+
+    table.push_str(&table_line(&header, &widths));
+    table.push_str(&table_line(&separator, &widths));
+
+    for line in day_lines {
+        table.push_str(&table_line(line, &widths));
+    }
+
+    table.push_str(&table_line(&separator, &widths));
+    table.push_str(&table_line(total_line, &widths));
+
+    table
+}
+
+// Answers are left-aligned and timings are right-aligned.
+fn table_line(cells: &[String; 7], widths: &[usize; 7]) -> String {
+    let mut line = String::new();
+
+    for (index, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if index > 0 {
+            line.push_str("  ");
+        }
+
+        if index < 3 {
+            line.push_str(&format!("{:<width$}", cell));
+        } else {
+            line.push_str(&format!("{:>width$}", cell));
+        }
+    }
+
+    line.trim_end().to_string() + "\n"
+}
+
+// Multi-line answers (like a CRT screen that couldn't be read) don't fit in a table cell.
+fn table_answer(answer: &Answer) -> String {
+    let answer = answer.to_string();
+
+    if answer.trim().contains('\n') {
+        "(multi-line)".to_string()
+    } else {
+        answer.trim().to_string()
+    }
+}
+
+fn read_input(source: &Source) -> Result<String, Error> {
+    source.read().map_err(|error| match source {
+        Source::Named(name) => Error::new(format!(
+            "could not read {}: {}",
+            input::path(name).display(),
+            error
+        ))
+        .in_day(name),
+        _ => Error::new(format!(
+            "could not read the input from {}: {}",
+            source, error
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_table() {
+        let report = Report {
+            part1: Answer::Number(24),
+            part2: Answer::Text("ZFBFHGUP".to_string()),
+            parse_time: Duration::from_millis(1),
+            part1_time: Duration::from_millis(2),
+            part2_time: Duration::from_millis(3),
+            diagnostics: Vec::new(),
+        };
+
+        let table = summary_table(&[("day10", Ok(report)), ("day24", Err("(no input)"))]);
+
+        assert_eq!(
+            table,
+            [
+                "Day    Part 1      Part 2     Parse  Part 1  Part 2   Total",
+                "-----  ----------  --------  ------  ------  ------  ------",
+                "day10  24          ZFBFHGUP  1.00ms  2.00ms  3.00ms  6.00ms",
+                "day24  (no input)",
+                "-----  ----------  --------  ------  ------  ------  ------",
+                "Total                        1.00ms  2.00ms  3.00ms  6.00ms",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_time_summary() {
+        let report = Report {
+            part1: Answer::Number(24),
+            part2: Answer::Number(93),
+            parse_time: Duration::from_millis(100),
+            part1_time: Duration::from_millis(200),
+            part2_time: Duration::from_millis(300),
+            diagnostics: Vec::new(),
+        };
+
+        let rows = [
+            ("day14", Ok(report.clone())),
+            ("day15", Ok(report)),
+            ("day24", Err("(no input)")),
+        ];
+
+        assert_eq!(
+            time_summary(&rows, Duration::from_millis(400), None, 4),
+            "Wall-clock time: 400.00ms on 4 threads, summed day time: 1.20s (3.00x)"
+        );
+        assert_eq!(
+            time_summary(
+                &rows,
+                Duration::from_millis(400),
+                Some(Duration::from_secs(1)),
+                4
+            ),
+            "Wall-clock time: 400.00ms on 4 threads, CPU time: 1.00s (2.50x)"
+        );
+    }
+
+    #[test]
+    fn test_parse_param() {
+        let day11 = find_day("day11").unwrap();
+        let param = |arg: &str| parse_param(day11, Some(&arg.to_string()));
+
+        assert_eq!(param("rounds=20"), Ok(("rounds", 20)));
+        assert_eq!(
+            param("round=20").unwrap_err().to_string(),
+            "day11 has no parameter `round`, expected one of: relief_rounds, rounds"
+        );
+        assert_eq!(
+            param("rounds=-1").unwrap_err().to_string(),
+            "rounds expects a number from 0 to 65535, got `-1`"
+        );
+        assert_eq!(
+            param("rounds=70000").unwrap_err().to_string(),
+            "rounds expects a number from 0 to 65535, got `70000`"
+        );
+        assert!(param("rounds").is_err());
+
+        let day1 = find_day("day1").unwrap();
+        assert_eq!(
+            parse_param(day1, Some(&"rounds=20".to_string()))
+                .unwrap_err()
+                .to_string(),
+            "day1 has no parameters"
+        );
+    }
+
+    #[test]
+    fn test_report_json() {
+        let day = find_day("day10_test").unwrap();
+        let source = Source::Named("day10_test".to_string());
+
+        let report = Report {
+            part1: Answer::Number(13140),
+            part2: Answer::Unsolved,
+            parse_time: Duration::from_nanos(1),
+            part1_time: Duration::from_nanos(2),
+            part2_time: Duration::from_nanos(3),
+            diagnostics: vec!["Cycle 20".to_string()],
+        };
+
+        assert_eq!(
+            report_json(day, &source, &Ok(report)).to_string(),
+            concat!(
+                r#"{"day":"day10","variant":"test","input":"day10_test","#,
+                r#""answers":{"part1":13140,"part2":null},"#,
+                r#""timings":{"parse_ns":1,"part1_ns":2,"part2_ns":3,"total_ns":6},"#,
+                r#""diagnostics":["Cycle 20"],"error":null}"#
+            )
+        );
+
+        let error = Error::new("no input").in_day("day10");
+        assert_eq!(
+            report_json(day, &Source::Stdin, &Err(error)).to_string(),
+            concat!(
+                r#"{"day":"day10","variant":null,"input":"stdin","answers":null,"#,
+                r#""timings":null,"diagnostics":[],"error":"day10: no input"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_take_format() {
+        let mut args = ["day10", "--format", "json", "--variant", "test"]
+            .map(str::to_string)
+            .to_vec();
+        assert_eq!(take_format(&mut args), Ok(Format::Json));
+        assert_eq!(args, ["day10", "--variant", "test"]);
+
+        assert_eq!(take_format(&mut args), Ok(Format::Human));
+
+        let mut args = ["all", "--format"].map(str::to_string).to_vec();
+        assert!(take_format(&mut args).is_err());
+
+        let mut args = ["all", "--format", "yaml"].map(str::to_string).to_vec();
+        assert!(take_format(&mut args).is_err());
+    }
+
+    #[test]
+    fn test_take_verbosity() {
+        let mut args = ["day15", "--trace", "--variant", "test"]
+            .map(str::to_string)
+            .to_vec();
+        assert_eq!(take_verbosity(&mut args), Ok(Verbosity::Trace));
+        assert_eq!(args, ["day15", "--variant", "test"]);
+
+        assert_eq!(take_verbosity(&mut args), Ok(Verbosity::Normal));
+
+        let mut args = ["all", "--quiet", "--verbose"].map(str::to_string).to_vec();
+        assert!(take_verbosity(&mut args).is_err());
     }
 }
@@ -0,0 +1,157 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+// Calls `work` on each task from `jobs` worker threads, which take the next task as soon as they're
+// done with one. `done` is called on this thread with the index of each task and its result, as
+// soon as it's ready: in order with a single job (which runs on this thread), in no particular
+// order otherwise.
+pub fn run<T: Sync, R: Send>(
+    tasks: &[T],
+    jobs: usize,
+    work: impl Fn(&T) -> R + Sync,
+    mut done: impl FnMut(usize, R),
+) {
+    if jobs <= 1 {
+        for (index, task) in tasks.iter().enumerate() {
+            done(index, work(task));
+        }
+
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(tasks.len()) {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+
+                match tasks.get(index) {
+                    Some(task) => {
+                        if sender.send((index, work(task))).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            });
+        }
+
+        // The workers hold the only other senders, so this ends once they're all done.
+        drop(sender);
+
+        for (index, result) in receiver {
+            done(index, result);
+        }
+    });
+}
+
+// Runs `f`, turning a panic into an error with the panic's message instead of unwinding further.
+pub fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(&*payload))
+}
+
+// Runs `f` without printing the message of the panics it catches, which the caller reports.
+pub fn quiet_panics<R>(f: impl FnOnce() -> R) -> R {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let result = f();
+
+    panic::set_hook(hook);
+    result
+}
+
+// The CPU time used by every thread of the process so far, on platforms that report it.
+#[cfg(unix)]
+pub fn cpu_time() -> Option<Duration> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+
+    // SAFETY: `getrusage` fills in `usage` when it succeeds.
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+
+        usage.assume_init()
+    };
+
+    let duration = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    };
+
+    Some(duration(usage.ru_utime) + duration(usage.ru_stime))
+}
+
+#[cfg(not(unix))]
+pub fn cpu_time() -> Option<Duration> {
+    None
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "(no message)".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let tasks = (1..=20).collect::<Vec<u64>>();
+
+        for jobs in [1, 4, 50] {
+            let mut results = vec![0; tasks.len()];
+            run(
+                &tasks,
+                jobs,
+                |task| task * task,
+                |index, result| results[index] = result,
+            );
+
+            assert_eq!(
+                results,
+                tasks.iter().map(|task| task * task).collect::<Vec<u64>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| 42), Ok(42));
+
+        let result = catch_panic(|| -> u8 { panic!("unexpected state at {}", 3) });
+        assert_eq!(result, Err("unexpected state at 3".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cpu_time() {
+        let start = cpu_time().unwrap();
+        let busy = Instant::now();
+
+        while busy.elapsed() < Duration::from_millis(50) {}
+
+        assert!(cpu_time().unwrap() - start >= Duration::from_millis(20));
+    }
+}
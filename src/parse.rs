@@ -0,0 +1,332 @@
+use std::{str::FromStr, sync::OnceLock};
+
+use crate::error::{parse_number, Error};
+
+/// A template for a piece of input, like `"move {crates} from {from} to {to}"`, with named fields
+/// between literal text.
+///
+/// Any run of whitespace in the literal text matches any run of whitespace (newlines included),
+/// so templates can span several lines and don't depend on indentation. A field takes the text up
+/// to the next literal text on the same line (or up to the end of the line), trimmed. Fields named
+/// `_` are matched but ignored.
+///
+/// The template is split into pieces on first use and kept, so patterns belong in `static`s
+/// rather than `const`s (a `const` would be a new, unparsed copy at every use).
+#[derive(Debug)]
+pub struct Pattern {
+    template: &'static str,
+    pieces: OnceLock<Vec<Piece>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Piece {
+    Literal(&'static str),
+    Field(&'static str),
+}
+
+impl Pattern {
+    pub const fn new(template: &'static str) -> Self {
+        Pattern {
+            template,
+            pieces: OnceLock::new(),
+        }
+    }
+
+    // Matches the whole text (ignoring surrounding whitespace). Errors are relative to the text.
+    pub fn parse<'a>(&self, text: &'a str) -> Result<Captures<'a>, Error> {
+        let pieces = self.pieces();
+        let mut fields = Vec::new();
+        let mut rest = text.trim_start();
+        let mut index = 0;
+
+        while index < pieces.len() {
+            match pieces[index] {
+                Piece::Literal(literal) => {
+                    let end = match_literal(rest, literal)
+                        .map_err(|_| Error::at(text, rest.trim_start(), expected(literal)))?;
+
+                    rest = &rest[end..];
+                }
+                Piece::Field(name) => {
+                    let line_end = rest.find('\n').unwrap_or(rest.len());
+
+                    if let Some(Piece::Literal(literal)) = pieces.get(index + 1) {
+                        let (start, end) = find_literal(text, rest, line_end, literal)?;
+
+                        fields.push((name, rest[..start].trim()));
+                        rest = &rest[end..];
+                        index += 1;
+                    } else {
+                        fields.push((name, rest[..line_end].trim()));
+                        rest = &rest[line_end..];
+                    }
+                }
+            }
+
+            index += 1;
+        }
+
+        let leftover = rest.trim();
+
+        if let Some(line) = leftover.lines().next() {
+            return Err(Error::at(text, leftover, format!("unexpected `{}`", line)));
+        }
+
+        Ok(Captures { text, fields })
+    }
+
+    fn pieces(&self) -> &[Piece] {
+        self.pieces.get_or_init(|| self.split_template())
+    }
+
+    fn split_template(&self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut rest = self.template.trim();
+
+        while let Some(open) = rest.find('{') {
+            let close = open
+                + rest[open..]
+                    .find('}')
+                    .unwrap_or_else(|| panic!("unclosed `{{` in pattern {:?}", self.template));
+
+            if open > 0 {
+                pieces.push(Piece::Literal(&rest[..open]));
+            }
+
+            pieces.push(Piece::Field(&rest[open + 1..close]));
+            rest = &rest[close + 1..];
+        }
+
+        if !rest.is_empty() {
+            pieces.push(Piece::Literal(rest));
+        }
+
+        pieces
+    }
+}
+
+/// The fields matched by a `Pattern`, as slices of the text it matched.
+#[derive(Debug, Clone)]
+pub struct Captures<'a> {
+    text: &'a str,
+    fields: Vec<(&'static str, &'a str)>,
+}
+
+impl<'a> Captures<'a> {
+    // Panics if the pattern has no such field, which is a bug in the pattern rather than the input.
+    pub fn get(&self, name: &str) -> &'a str {
+        self.fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| *value)
+            .unwrap_or_else(|| panic!("no field `{}` in the pattern", name))
+    }
+
+    pub fn number<T: FromStr>(&self, name: &str) -> Result<T, Error> {
+        parse_number(self.text, self.get(name))
+    }
+
+    // A field with numbers separated by `separator`, like "79, 98".
+    pub fn numbers<T: FromStr>(&self, name: &str, separator: &str) -> Result<Vec<T>, Error> {
+        list(self.get(name), separator, |item| {
+            parse_number(self.text, item)
+        })
+    }
+}
+
+// Parses each block of lines of the input (separated by blank lines), reporting errors at their
+// position in the input. The blocks are slices of the input.
+pub fn blocks<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    input
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| parse(block).map_err(|error| error.within(input, block)))
+        .collect()
+}
+
+// Parses each item of `part` separated by `separator`, trimmed. The items are slices of `part`,
+// so `parse` can report errors at their position.
+pub fn list<'a, T>(
+    part: &'a str,
+    separator: &str,
+    parse: impl FnMut(&'a str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    if part.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    part.split(separator).map(str::trim).map(parse).collect()
+}
+
+// Splits a line of a drawing into columns of `width` characters (the last one can be shorter).
+pub fn columns(line: &str, width: usize) -> impl Iterator<Item = &str> {
+    let starts = line
+        .char_indices()
+        .map(|(index, _)| index)
+        .step_by(width.max(1))
+        .collect::<Vec<usize>>();
+    let ends = starts
+        .iter()
+        .skip(1)
+        .copied()
+        .chain([line.len()])
+        .collect::<Vec<usize>>();
+
+    starts
+        .into_iter()
+        .zip(ends)
+        .map(move |(start, end)| &line[start..end])
+}
+
+// The length of `literal` matched at the start of `text`, or how much of `literal` matched before
+// the first difference.
+fn match_literal(text: &str, literal: &str) -> Result<usize, usize> {
+    let mut text_chars = text.char_indices().peekable();
+    let mut literal_chars = literal.char_indices().peekable();
+
+    while let Some(&(progress, literal_char)) = literal_chars.peek() {
+        if literal_char.is_whitespace() {
+            while literal_chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+            if text_chars.next_if(|(_, c)| c.is_whitespace()).is_none() {
+                return Err(progress);
+            }
+
+            while text_chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        } else if text_chars.next_if(|(_, c)| *c == literal_char).is_some() {
+            literal_chars.next();
+        } else {
+            return Err(progress);
+        }
+    }
+
+    Ok(text_chars.peek().map_or(text.len(), |(index, _)| *index))
+}
+
+// The start and end of the first match of `literal` in `rest` starting on its first line (which
+// ends at `line_end`). When there is none, the error is at the closest match, if any part of the
+// literal matched, or else at the end of the line.
+fn find_literal(
+    text: &str,
+    rest: &str,
+    line_end: usize,
+    literal: &str,
+) -> Result<(usize, usize), Error> {
+    let mut closest = (0, line_end);
+
+    let starts = rest[..line_end]
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain([line_end]);
+
+    for start in starts {
+        match match_literal(&rest[start..], literal) {
+            Ok(length) => return Ok((start, start + length)),
+            Err(progress) if progress > closest.0 => closest = (progress, start),
+            Err(_) => (),
+        }
+    }
+
+    let at = match closest {
+        (0, _) => &rest[line_end..],
+        (_, start) => rest[start..].trim_start(),
+    };
+
+    Err(Error::at(text, at, expected(literal)))
+}
+
+fn expected(literal: &str) -> String {
+    let words = literal.split_whitespace().collect::<Vec<&str>>();
+
+    if words.is_empty() {
+        "expected whitespace".to_string()
+    } else {
+        format!("expected `{}`", words.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static MOVE: Pattern = Pattern::new("move {crates} from {from} to {to}");
+
+    #[test]
+    fn test_pattern() {
+        let captures = MOVE.parse("move 3 from 1 to 2").unwrap();
+
+        assert_eq!(captures.get("crates"), "3");
+        assert_eq!(captures.number::<u8>("from"), Ok(1));
+        assert_eq!(captures.number::<u8>("to"), Ok(2));
+
+        // Whitespace is flexible, and fields can be empty.
+        let pattern = Pattern::new("Valve {id} has flow rate={rate};\n tunnel{_} lead{_} to {to}");
+        let captures = pattern
+            .parse("Valve AA has flow rate=0;   tunnel leads to BB, CC\n")
+            .unwrap();
+
+        assert_eq!(captures.get("id"), "AA");
+        assert_eq!(captures.get("rate"), "0");
+        assert_eq!(captures.get("_"), "");
+        assert_eq!(captures.numbers::<u8>("rate", ","), Ok(vec![0]));
+        assert_eq!(captures.get("to"), "BB, CC");
+    }
+
+    #[test]
+    fn test_pattern_errors() {
+        let error = |text| MOVE.parse(text).unwrap_err().to_string();
+
+        assert_eq!(error("mov 3 from 1 to 2"), "line 1 col 1: expected `move`");
+        assert_eq!(error("move 3 form 1 to 2"), "line 1 col 8: expected `from`");
+        assert_eq!(error("move 3"), "line 1 col 7: expected `from`");
+        assert_eq!(
+            error("move 3 from 1 to 2\nmove"),
+            "line 2 col 1: unexpected `move`"
+        );
+        assert_eq!(
+            MOVE.parse("move x from 1 to 2")
+                .unwrap()
+                .number::<u8>("crates")
+                .unwrap_err()
+                .to_string(),
+            "line 1 col 6: expected a number, got `x`"
+        );
+
+        let pattern = Pattern::new("Operation: {operation}\n Test: divisible by {divisor}");
+        assert_eq!(
+            pattern
+                .parse("Operation: old * 19\n  Test: divided by 23")
+                .unwrap_err()
+                .to_string(),
+            "line 2 col 3: expected `Test: divisible by`"
+        );
+    }
+
+    #[test]
+    fn test_blocks_and_lists() {
+        let input = "1, 2\n\n3\n\n\n5, x\n";
+        let numbers = |block: &str| list(block, ",", |item| parse_number::<u8>(block, item));
+
+        assert_eq!(blocks("1, 2\n\n3", numbers), Ok(vec![vec![1, 2], vec![3]]));
+        assert_eq!(
+            list("", ",", |item| parse_number::<u8>(item, item)),
+            Ok(vec![])
+        );
+        assert_eq!(
+            blocks(input, numbers).unwrap_err().to_string(),
+            "line 6 col 4: expected a number, got `x`"
+        );
+    }
+
+    #[test]
+    fn test_columns() {
+        assert_eq!(
+            columns("[Z] [M] [P]", 4).collect::<Vec<_>>(),
+            vec!["[Z] ", "[M] ", "[P]"]
+        );
+        assert_eq!(columns("", 4).count(), 0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    cell::Cell,
+    env,
+    io::{self, BufRead, Write},
+    rc::Rc,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use aoc22::visualize::{Frame, Viewport};
+
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const CURSOR_HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+// The lines below the frame, for its caption and the status.
+const STATUS_LINES: i64 = 2;
+
+// Commands typed while playing, each followed by Enter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Control {
+    fn parse(line: &str) -> Option<Control> {
+        match line.trim() {
+            "p" => Some(Control::TogglePause),
+            "" | "s" => Some(Control::Step),
+            "+" => Some(Control::Faster),
+            "-" => Some(Control::Slower),
+            "q" => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Plays the frames of a simulation in the terminal as they're emitted, at a given number of
+/// frames per second, which holds the simulation back. The screen is the size of the `COLUMNS`
+/// and `LINES` environment variables, or 80x24.
+pub struct Player {
+    fps: f64,
+    viewport: Viewport,
+    // None when the input comes from stdin, which can't be used for the controls then.
+    controls: Option<Receiver<Control>>,
+    paused: bool,
+    quit: bool,
+    last_frame: Option<Instant>,
+    shown: Rc<Cell<usize>>,
+}
+
+impl Player {
+    pub fn new(fps: f64, with_controls: bool) -> Self {
+        let size = |name, default| {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or(default)
+        };
+
+        Player {
+            fps,
+            viewport: Viewport::new(size("COLUMNS", 80), size("LINES", 24) - STATUS_LINES),
+            controls: with_controls.then(read_controls),
+            paused: false,
+            quit: false,
+            last_frame: None,
+            shown: Rc::new(Cell::new(0)),
+        }
+    }
+
+    // The number of frames shown so far, which can be read after the player was handed over.
+    pub fn shown(&self) -> Rc<Cell<usize>> {
+        self.shown.clone()
+    }
+
+    pub fn show(&mut self, frame: Frame) {
+        self.handle_controls();
+
+        // After quitting, the simulation runs to the end without being shown.
+        if self.quit {
+            return;
+        }
+
+        if let Some(last_frame) = self.last_frame {
+            let interval = Duration::from_secs_f64(1.0 / self.fps);
+            thread::sleep(interval.saturating_sub(last_frame.elapsed()));
+        }
+
+        self.viewport.follow(&frame);
+        let (min, max) = self.viewport.area();
+        self.shown.set(self.shown.get() + 1);
+
+        let mut screen = String::new();
+
+        if self.shown.get() == 1 {
+            screen += CLEAR_SCREEN;
+            screen += HIDE_CURSOR;
+        }
+
+        screen += CURSOR_HOME;
+
+        for line in frame.render(min, max).lines() {
+            screen += line;
+            screen += CLEAR_LINE;
+            screen += "\n";
+        }
+
+        screen += &format!("{}{}\n", frame.caption, CLEAR_LINE);
+        screen += &format!("{}{}", self.status(), CLEAR_LINE);
+
+        print!("{}", screen);
+        io::stdout().flush().unwrap();
+
+        self.last_frame = Some(Instant::now());
+    }
+
+    fn status(&self) -> String {
+        let state = if self.paused { "paused" } else { "playing" };
+        let mut status = format!("frame {} · {} fps · {}", self.shown.get(), self.fps, state);
+
+        if self.controls.is_some() {
+            status += " · p pause, Enter step, + faster, - slower, q quit (then Enter)";
+        }
+
+        status
+    }
+
+    // Applies the commands typed since the last frame, and waits for the next one while paused.
+    fn handle_controls(&mut self) {
+        let Some(controls) = &self.controls else {
+            return;
+        };
+
+        loop {
+            let control = if self.paused {
+                controls.recv().map_err(|_| TryRecvError::Disconnected)
+            } else {
+                controls.try_recv()
+            };
+
+            match control {
+                Ok(Control::TogglePause) => self.paused = !self.paused,
+                Ok(Control::Step) if self.paused => return,
+                Ok(Control::Step) => (),
+                Ok(Control::Faster) => self.fps = (self.fps * 2.0).min(1000.0),
+                Ok(Control::Slower) => self.fps = (self.fps / 2.0).max(0.25),
+                Ok(Control::Quit) => {
+                    self.quit = true;
+                    return;
+                }
+                // Without stdin, playing can't be paused anymore.
+                Err(TryRecvError::Disconnected) => {
+                    self.paused = false;
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+            }
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        if self.shown.get() > 0 {
+            println!("{}\n", SHOW_CURSOR);
+        }
+    }
+}
+
+// Reads commands from stdin on a separate thread, so that frames aren't held back waiting for
+// them.
+fn read_controls() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            if let Some(control) = Control::parse(&line) {
+                if sender.send(control).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_parse() {
+        assert_eq!(Control::parse("p\n"), Some(Control::TogglePause));
+        assert_eq!(Control::parse(""), Some(Control::Step));
+        assert_eq!(Control::parse("+"), Some(Control::Faster));
+        assert_eq!(Control::parse("-"), Some(Control::Slower));
+        assert_eq!(Control::parse("q"), Some(Control::Quit));
+        assert_eq!(Control::parse("x"), None);
+    }
+}
@@ -0,0 +1,90 @@
+use std::ops::Range;
+
+/// A small, seeded pseudo-random number generator (SplitMix64), for generating puzzle inputs. The
+/// same seed always gives the same numbers, so generated inputs can be reproduced from their seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in the range, which must not be empty.
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        assert!(!range.is_empty(), "empty range {:?}", range);
+
+        let width = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % width) as i64)
+    }
+
+    // A number from 0 to `n` (excluded), which must be positive.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.range(0..n as i64) as usize
+    }
+
+    // True with the given probability (between 0 and 1).
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    // One of the items, which must not be empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+
+    // A word of random lowercase letters.
+    pub fn word(&mut self, length: usize) -> String {
+        (0..length)
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeds() {
+        let numbers = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| rng.next_u64()).collect::<Vec<u64>>()
+        };
+
+        assert_eq!(numbers(42), numbers(42));
+        assert_ne!(numbers(42), numbers(43));
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(7);
+        let numbers = (0..1000).map(|_| rng.range(-3..4)).collect::<Vec<i64>>();
+
+        assert!(numbers.iter().all(|number| (-3..4).contains(number)));
+        assert!((-3..4).all(|number| numbers.contains(&number)));
+        assert_eq!(rng.range(i64::MIN..i64::MIN + 1), i64::MIN);
+
+        let mut items = (0..10).collect::<Vec<i32>>();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<i32>>());
+    }
+}
@@ -0,0 +1,278 @@
+use std::{fs, path::Path};
+
+use aoc22::{answers::ANSWERS_FILE, error::Error, input};
+
+const SOURCE_DIR: &str = "src";
+const LIB_FILE: &str = "src/lib.rs";
+
+const DAYS_START: &str = "pub const DAYS: &[Day] = &days![";
+
+const TEMPLATE: &str = "use crate::{
+    error::Error,
+    solution::{Answer, Solution},
+};
+
+pub struct DAY_TYPE;
+
+impl Solution for DAY_TYPE {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.lines().map(str::to_string).collect())
+    }
+
+    fn part1(_lines: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+
+    fn part2(_lines: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!(\"../inputs/DAY_NAME_test.txt\");
+
+    #[test]
+    fn test_part1() {
+        let lines = DAY_TYPE::parse(TEST_INPUT).unwrap();
+        assert_eq!(DAY_TYPE::part1(&lines), Answer::Unsolved);
+    }
+
+    #[test]
+    fn test_part2() {
+        let lines = DAY_TYPE::parse(TEST_INPUT).unwrap();
+        assert_eq!(DAY_TYPE::part2(&lines), Answer::Unsolved);
+    }
+}
+";
+
+// Creates the module of a new day from a template, empty inputs for it (unless they already
+// exist) and a line for its test input in the answers file, and registers the day in the library. Returns the files that were created or changed.
+pub fn new_day(name: &str) -> Result<Vec<String>, Error> {
+    let number = day_number(name)?;
+
+    let module_path = Path::new(SOURCE_DIR).join(format!("{}.rs", name));
+    if module_path.exists() {
+        return Err(Error::new(format!("{} already exists", module_path.display())).in_day(name));
+    }
+
+    let lib = fs::read_to_string(LIB_FILE).map_err(|error| {
+        Error::new(format!("could not read {}: {}", LIB_FILE, error)).in_day(name)
+    })?;
+    let lib = register(&lib, number).map_err(|error| error.in_day(LIB_FILE))?;
+
+    let mut changed = Vec::new();
+
+    write(&module_path, &module(number))?;
+    changed.push(module_path.display().to_string());
+
+    for input_name in [name.to_string(), format!("{}_test", name)] {
+        let input_path = input::path(&input_name);
+
+        if !input_path.exists() {
+            write(&input_path, "")?;
+            changed.push(input_path.display().to_string());
+        }
+    }
+
+    write(Path::new(LIB_FILE), &lib)?;
+    changed.push(LIB_FILE.to_string());
+
+    // Every test input needs expected answers for the tests over test inputs, even if unknown.
+    let test_name = format!("{}_test", name);
+    let answers = fs::read_to_string(ANSWERS_FILE).unwrap_or_default();
+
+    if !answers
+        .lines()
+        .any(|line| line.split_whitespace().next() == Some(&test_name))
+    {
+        write(Path::new(ANSWERS_FILE), &add_answers(&answers, &test_name))?;
+        changed.push(ANSWERS_FILE.to_string());
+    }
+
+    Ok(changed)
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents)
+        .map_err(|error| Error::new(format!("could not write {}: {}", path.display(), error)))
+}
+
+fn day_number(name: &str) -> Result<u32, Error> {
+    name.strip_prefix("day")
+        .and_then(|number| number.parse().ok())
+        .filter(|number| *number > 0)
+        .ok_or_else(|| Error::new(format!("expected a day like `day26`, got `{}`", name)))
+}
+
+fn module(number: u32) -> String {
+    TEMPLATE
+        .replace("DAY_TYPE", &format!("Day{}", number))
+        .replace("DAY_NAME", &format!("day{}", number))
+}
+
+// Adds the day's module to the list of modules (sorted by name, like rustfmt does), and its
+// solution to DAYS (in the order of the days).
+fn register(lib: &str, number: u32) -> Result<String, Error> {
+    let module = format!("day{}", number);
+    let entry = format!("    day{0}::Day{0},", number);
+
+    let mut lines = lib.lines().map(str::to_string).collect::<Vec<String>>();
+
+    let modules = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let module = line.strip_prefix("pub mod ")?.strip_suffix(';')?;
+            module.starts_with("day").then_some((index, module))
+        })
+        .collect::<Vec<(usize, &str)>>();
+
+    let last_module = match modules.last() {
+        Some((index, _)) => *index,
+        None => return Err(Error::new("expected a list of `pub mod dayN;`")),
+    };
+
+    let module_index = modules
+        .iter()
+        .find(|(_, other)| module.as_str() < *other)
+        .map_or(last_module + 1, |(index, _)| *index);
+
+    lines.insert(module_index, format!("pub mod {};", module));
+
+    let days_start = lines
+        .iter()
+        .position(|line| line == DAYS_START)
+        .ok_or_else(|| Error::new(format!("expected `{}`", DAYS_START)))?;
+
+    let days_end = days_start
+        + lines[days_start..]
+            .iter()
+            .position(|line| line == "];")
+            .ok_or_else(|| Error::new("expected `];` at the end of DAYS"))?;
+
+    let entry_index = (days_start + 1..days_end)
+        .find(|index| entry_number(&lines[*index]).is_some_and(|other| other > number))
+        .unwrap_or(days_end);
+
+    lines.insert(entry_index, entry);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+// Adds a line with unknown answers for the input, with its columns aligned like the other lines.
+fn add_answers(answers: &str, input_name: &str) -> String {
+    let columns = answers
+        .lines()
+        .find(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let part2 = line.rfind(' ').map_or(0, |index| index + 1);
+            let part1 = line[..part2]
+                .trim_end()
+                .rfind(' ')
+                .map_or(0, |index| index + 1);
+            (part1, part2)
+        });
+
+    let line = match columns {
+        Some((part1, part2)) if part1 > input_name.len() => {
+            format!(
+                "{:<part1$}{:<width$}-",
+                input_name,
+                "-",
+                width = part2 - part1
+            )
+        }
+        _ => format!("{} - -", input_name),
+    };
+
+    let mut answers = answers.to_string();
+
+    if !answers.is_empty() && !answers.ends_with('\n') {
+        answers.push('\n');
+    }
+
+    answers + &line + "\n"
+}
+
+// The number of the day of a DAYS entry, like 12 for "    day12::Day12,".
+fn entry_number(line: &str) -> Option<u32> {
+    let (module, _) = line.trim().strip_prefix("day")?.split_once("::")?;
+    module.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register() {
+        let lib = [
+            "pub mod day1;",
+            "pub mod day10;",
+            "pub mod day2;",
+            "pub mod day9;",
+            "pub mod error;",
+            "",
+            "pub const DAYS: &[Day] = &days![",
+            "    day1::Day1,",
+            "    day2::Day2,",
+            "    day9::Day9,",
+            "    day10::Day10,",
+            "];",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            register(&lib, 3).unwrap(),
+            [
+                "pub mod day1;",
+                "pub mod day10;",
+                "pub mod day2;",
+                "pub mod day3;",
+                "pub mod day9;",
+                "pub mod error;",
+                "",
+                "pub const DAYS: &[Day] = &days![",
+                "    day1::Day1,",
+                "    day2::Day2,",
+                "    day3::Day3,",
+                "    day9::Day9,",
+                "    day10::Day10,",
+                "];",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let registered = register(&lib, 26).unwrap();
+        assert!(registered.contains("pub mod day2;\npub mod day26;\npub mod day9;"));
+        assert!(registered.contains("    day10::Day10,\n    day26::Day26,\n];"));
+
+        assert!(register("fn main() {}", 3).is_err());
+    }
+
+    #[test]
+    fn test_add_answers() {
+        let answers = "# Expected answers\n\nday1        69626  206780\nday25_test  2=-1=0 -\n";
+
+        assert_eq!(
+            add_answers(answers, "day26_test"),
+            answers.to_string() + "day26_test  -      -\n"
+        );
+        assert_eq!(add_answers("", "day26_test"), "day26_test - -\n");
+    }
+
+    #[test]
+    fn test_day_number() {
+        assert_eq!(day_number("day26"), Ok(26));
+        assert!(day_number("day0").is_err());
+        assert!(day_number("26").is_err());
+        assert!(day_number("day26_test").is_err());
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// The shortest distances from the start nodes of a search to every node it reached, and the way
+/// back to the start nodes from each of them.
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    distances: HashMap<N, u64>,
+    previous: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash> Paths<N> {
+    fn new() -> Self {
+        Paths {
+            distances: HashMap::new(),
+            previous: HashMap::new(),
+        }
+    }
+
+    // None when the search didn't reach the node.
+    pub fn distance(&self, node: &N) -> Option<u64> {
+        self.distances.get(node).copied()
+    }
+
+    // The nodes of a shortest path from the start nodes to the given one (both included).
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.distances.get(node)?;
+
+        let mut path = vec![node.clone()];
+
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    // Every node the search reached, with its distance, in no particular order.
+    pub fn reached(&self) -> impl Iterator<Item = (&N, u64)> + '_ {
+        self.distances
+            .iter()
+            .map(|(node, distance)| (node, *distance))
+    }
+}
+
+// Searches breadth-first from all the start nodes at once, when each step costs the same.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> Paths<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if paths.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let distance = paths.distances[&node];
+
+        for neighbor in neighbors(&node) {
+            if !paths.distances.contains_key(&neighbor) {
+                paths.distances.insert(neighbor.clone(), distance + 1);
+                paths.previous.insert(neighbor.clone(), node.clone());
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    paths
+}
+
+// Dijkstra's algorithm from all the start nodes at once. `neighbors` gives each neighbour of a
+// node with the cost of the step to it.
+pub fn dijkstra<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> Paths<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut paths = Paths::new();
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        paths.distances.insert(start.clone(), 0);
+        queue.push(Candidate::new(start, 0, 0));
+    }
+
+    while let Some(Candidate { node, cost, .. }) = queue.pop() {
+        // A shorter way to this node was found after this candidate was queued.
+        if cost > paths.distances[&node] {
+            continue;
+        }
+
+        for (neighbor, step) in neighbors(&node) {
+            let distance = cost + step;
+
+            if paths
+                .distances
+                .get(&neighbor)
+                .is_none_or(|known| distance < *known)
+            {
+                paths.distances.insert(neighbor.clone(), distance);
+                paths.previous.insert(neighbor.clone(), node.clone());
+                queue.push(Candidate::new(neighbor, distance, distance));
+            }
+        }
+    }
+
+    paths
+}
+
+// A* from a start node to the first node that `is_goal`, returning its distance and the path to
+// it (both ends included). `heuristic` estimates the distance from a node to the goal, and must
+// never overestimate it for the path to be a shortest one.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(u64, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut paths = Paths::new();
+    let mut queue = BinaryHeap::new();
+
+    paths.distances.insert(start.clone(), 0);
+    queue.push(Candidate::new(start.clone(), 0, heuristic(&start)));
+
+    while let Some(Candidate { node, cost, .. }) = queue.pop() {
+        if is_goal(&node) {
+            return Some((cost, paths.path_to(&node).unwrap()));
+        }
+
+        if cost > paths.distances[&node] {
+            continue;
+        }
+
+        for (neighbor, step) in neighbors(&node) {
+            let distance = cost + step;
+
+            if paths
+                .distances
+                .get(&neighbor)
+                .is_none_or(|known| distance < *known)
+            {
+                let estimate = distance + heuristic(&neighbor);
+
+                paths.distances.insert(neighbor.clone(), distance);
+                paths.previous.insert(neighbor.clone(), node.clone());
+                queue.push(Candidate::new(neighbor, distance, estimate));
+            }
+        }
+    }
+
+    None
+}
+
+/// The shortest distance between each pair of nodes of a graph (Floyd–Warshall), in a dense
+/// matrix, for small graphs where most pairs are looked up.
+#[derive(Debug, Clone)]
+pub struct AllPairs<N> {
+    indices: HashMap<N, usize>,
+    distances: Vec<Option<u64>>,
+}
+
+impl<N: Clone + Eq + Hash> AllPairs<N> {
+    // `neighbors` gives each neighbour of a node with the cost of the step to it, and must only
+    // give nodes from `nodes`.
+    pub fn new<I>(nodes: impl IntoIterator<Item = N>, mut neighbors: impl FnMut(&N) -> I) -> Self
+    where
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        let nodes = nodes.into_iter().collect::<Vec<N>>();
+        let count = nodes.len();
+
+        let indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.clone(), index))
+            .collect::<HashMap<N, usize>>();
+
+        let mut distances = vec![None; count * count];
+
+        for (index, node) in nodes.iter().enumerate() {
+            distances[index * count + index] = Some(0);
+
+            for (neighbor, step) in neighbors(node) {
+                let cell = &mut distances[index * count + indices[&neighbor]];
+                *cell = Some(cell.map_or(step, |known: u64| known.min(step)));
+            }
+        }
+
+        for k in 0..count {
+            for i in 0..count {
+                let Some(i_k) = distances[i * count + k] else {
+                    continue;
+                };
+
+                for j in 0..count {
+                    if let Some(k_j) = distances[k * count + j] {
+                        let cell = &mut distances[i * count + j];
+
+                        if cell.is_none_or(|i_j| i_k + k_j < i_j) {
+                            *cell = Some(i_k + k_j);
+                        }
+                    }
+                }
+            }
+        }
+
+        AllPairs { indices, distances }
+    }
+
+    // None when there is no path between the nodes, or they aren't in the graph.
+    pub fn distance(&self, from: &N, to: &N) -> Option<u64> {
+        let (from, to) = (self.indices.get(from)?, self.indices.get(to)?);
+        self.distances[from * self.indices.len() + to]
+    }
+}
+
+// A node in the queue of a search, ordered so that the binary heap (a max-heap) pops the lowest
+// estimate first.
+struct Candidate<N> {
+    node: N,
+    cost: u64,
+    estimate: u64,
+}
+
+impl<N> Candidate<N> {
+    fn new(node: N, cost: u64, estimate: u64) -> Self {
+        Candidate {
+            node,
+            cost,
+            estimate,
+        }
+    }
+}
+
+impl<N> PartialEq for Candidate<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N> Eq for Candidate<N> {}
+
+impl<N> PartialOrd for Candidate<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Candidate<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 - 1 - 2
+    // |       |
+    // 3 ----- 4    5
+    fn line_neighbors(node: &u32) -> Vec<u32> {
+        match node {
+            0 => vec![1, 3],
+            1 => vec![0, 2],
+            2 => vec![1, 4],
+            3 => vec![0, 4],
+            4 => vec![2, 3],
+            _ => vec![],
+        }
+    }
+
+    // Like `line_neighbors`, but the steps to and from 3 cost 5.
+    fn weighted_neighbors(node: &u32) -> Vec<(u32, u64)> {
+        line_neighbors(node)
+            .into_iter()
+            .map(|neighbor| (neighbor, if neighbor == 3 || *node == 3 { 5 } else { 1 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let paths = bfs([0], line_neighbors);
+
+        assert_eq!(paths.distance(&4), Some(2));
+        assert_eq!(paths.distance(&2), Some(2));
+        assert_eq!(paths.distance(&5), None);
+        assert_eq!(paths.path_to(&4), Some(vec![0, 3, 4]));
+        assert_eq!(paths.path_to(&0), Some(vec![0]));
+        assert_eq!(paths.path_to(&5), None);
+        assert_eq!(paths.reached().count(), 5);
+
+        let paths = bfs([2, 3], line_neighbors);
+        assert_eq!(paths.distance(&4), Some(1));
+        assert_eq!(paths.distance(&1), Some(1));
+        assert_eq!(paths.distance(&3), Some(0));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let paths = dijkstra([0], weighted_neighbors);
+
+        assert_eq!(paths.distance(&4), Some(3));
+        assert_eq!(paths.path_to(&4), Some(vec![0, 1, 2, 4]));
+        assert_eq!(paths.distance(&3), Some(5));
+        assert_eq!(paths.distance(&5), None);
+
+        let paths = dijkstra([0, 4], weighted_neighbors);
+        assert_eq!(paths.distance(&2), Some(1));
+        assert_eq!(paths.distance(&3), Some(5));
+    }
+
+    #[test]
+    fn test_astar() {
+        let heuristic = |node: &u32| u64::from(4_u32.abs_diff(*node)).min(1);
+
+        assert_eq!(
+            astar(0, weighted_neighbors, heuristic, |node| *node == 4),
+            Some((3, vec![0, 1, 2, 4]))
+        );
+        assert_eq!(
+            astar(0, weighted_neighbors, |_| 0, |node| *node == 0),
+            Some((0, vec![0]))
+        );
+        assert_eq!(astar(0, weighted_neighbors, |_| 0, |node| *node == 5), None);
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let all_pairs = AllPairs::new(0..6, weighted_neighbors);
+
+        assert_eq!(all_pairs.distance(&0, &4), Some(3));
+        assert_eq!(all_pairs.distance(&4, &0), Some(3));
+        assert_eq!(all_pairs.distance(&3, &1), Some(6));
+        assert_eq!(all_pairs.distance(&2, &2), Some(0));
+        assert_eq!(all_pairs.distance(&0, &5), None);
+        assert_eq!(all_pairs.distance(&0, &6), None);
+
+        for from in 0..6 {
+            let paths = dijkstra([from], weighted_neighbors);
+
+            for to in 0..6 {
+                assert_eq!(all_pairs.distance(&from, &to), paths.distance(&to));
+            }
+        }
+    }
+}
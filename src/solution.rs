@@ -0,0 +1,463 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{error::Error, random::Rng};
+
+/// The answer to one part of a day's puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+    /// The part has no solution in this repo (yet), like day 24 or day 25's
+    /// second part (which doesn't exist).
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Unsolved => write!(f, "(unsolved)"),
+        }
+    }
+}
+
+// Anything that looks like a number is a number, everything else is text.
+impl FromStr for Answer {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<i128>() {
+            Ok(number) => Ok(Answer::Number(number)),
+            Err(_) => Ok(Answer::Text(s.to_string())),
+        }
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Answer {
+                fn from(number: $type) -> Self {
+                    Answer::Number(number as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i16, i32, i64, i128, isize, u16, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+/// A day's puzzle. The input is parsed once and then shared by both parts.
+pub trait Solution {
+    type Input;
+
+    /// The constants of the puzzle that can be overridden when running the day.
+    const PARAMS: &'static [Param] = &[];
+
+    fn parse(input: &str) -> Result<Self::Input, Error>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    /// A random input that both parts can solve, of roughly the given size (what the size counts
+    /// depends on the day), or None if the day has no generator.
+    fn generate(_rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+
+    /// The answers of a slow but obviously correct solver, to check both parts against on small
+    /// inputs, or None if the day has no reference solver. A part that the reference can't solve
+    /// (in a reasonable time, with the current params) is `Answer::Unsolved`.
+    fn reference(_input: &Self::Input) -> Option<(Answer, Answer)> {
+        None
+    }
+}
+
+/// A registered day, with its solution erased behind a function pointer so that days can be
+/// listed and run uniformly.
+pub struct Day {
+    pub name: &'static str,
+    pub params: &'static [Param],
+    pub run: fn(&str) -> Result<Report, Error>,
+    pub generate: fn(&mut Rng, usize) -> Option<String>,
+    pub reference: ReferenceSolver,
+}
+
+/// Parses an input and solves it with a day's reference solver, if it has one.
+pub type ReferenceSolver = fn(&str) -> Result<Option<(Answer, Answer)>, Error>;
+
+impl Day {
+    // The override of the parameter with the given name, checked against its range.
+    pub fn param(&self, name: &str, value: &str) -> Result<(&'static str, i64), Error> {
+        let param = self
+            .params
+            .iter()
+            .find(|param| param.name == name)
+            .ok_or_else(|| {
+                let names = self
+                    .params
+                    .iter()
+                    .map(|param| param.name)
+                    .collect::<Vec<&str>>();
+
+                if names.is_empty() {
+                    Error::new(format!("{} has no parameters", self.name))
+                } else {
+                    Error::new(format!(
+                        "{} has no parameter `{}`, expected one of: {}",
+                        self.name,
+                        name,
+                        names.join(", ")
+                    ))
+                }
+            })?;
+
+        let expected = match (param.min, param.max) {
+            (i64::MIN, i64::MAX) => "a number".to_string(),
+            (min, i64::MAX) => format!("a number of at least {}", min),
+            (min, max) => format!("a number from {} to {}", min, max),
+        };
+
+        match value.parse::<i64>() {
+            Ok(value) if (param.min..=param.max).contains(&value) => Ok((param.name, value)),
+            _ => Err(Error::new(format!(
+                "{} expects {}, got `{}`",
+                param.name, expected, value
+            ))),
+        }
+    }
+}
+
+/// A constant of a day's puzzle, like the number of rounds, which can be overridden from the
+/// command line (for example to use the smaller numbers of the sample).
+#[derive(Debug, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: i64,
+    pub min: i64,
+    pub max: i64,
+    pub description: &'static str,
+}
+
+impl Param {
+    // The overridden value of the parameter, if any, or its default.
+    pub fn value<T: TryFrom<i64>>(&self) -> T {
+        let value = PARAM_OVERRIDES.with(|overrides| {
+            overrides
+                .borrow()
+                .iter()
+                .find(|(name, _)| *name == self.name)
+                .map_or(self.default, |(_, value)| *value)
+        });
+
+        T::try_from(value)
+            .unwrap_or_else(|_| panic!("{} is out of range for parameter {}", value, self.name))
+    }
+}
+
+thread_local! {
+    static PARAM_OVERRIDES: RefCell<Vec<(&'static str, i64)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Overrides parameters for the days run on this thread from now on. The names are the ones of the
+// day's `Param`s.
+pub fn override_params(overrides: Vec<(&'static str, i64)>) {
+    PARAM_OVERRIDES.with(|current| *current.borrow_mut() = overrides);
+}
+
+/// The answers to both parts of a day, and how long each step took.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub part1: Answer,
+    pub part2: Answer,
+    pub parse_time: Duration,
+    pub part1_time: Duration,
+    pub part2_time: Duration,
+    // Only filled in when diagnostics are captured, see `capture_diagnostics`.
+    pub diagnostics: Vec<String>,
+}
+
+impl Report {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.part1_time + self.part2_time
+    }
+}
+
+/// How much the days print while they run, besides their answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    // Nothing at all.
+    Quiet,
+    // The progress of long loops, on a status line that's cleared once they're done.
+    Normal,
+    // Also what the days find on the way, like the path of day 12 or the equation of day 21.
+    Verbose,
+    // Also every step of the loops, like each round of day 23.
+    Trace,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    }
+}
+
+static CAPTURE_DIAGNOSTICS: AtomicBool = AtomicBool::new(false);
+
+// Whether a progress status line is on the screen, to be cleared before printing anything else.
+static STATUS_LINE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static DIAGNOSTICS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// Diagnostics are printed as they happen by default. When captured, they're collected in the
+// report of the run instead, so that they don't get mixed up with machine-readable output.
+pub fn capture_diagnostics(capture: bool) {
+    CAPTURE_DIAGNOSTICS.store(capture, Ordering::Relaxed);
+}
+
+// Diagnostics below the verbosity are dropped.
+pub fn emit_diagnostic(verbosity: Verbosity, message: String) {
+    if self::verbosity() < verbosity {
+        return;
+    }
+
+    if CAPTURE_DIAGNOSTICS.load(Ordering::Relaxed) {
+        DIAGNOSTICS.with(|diagnostics| diagnostics.borrow_mut().push(message));
+    } else {
+        clear_status_line();
+        println!("{}", message);
+    }
+}
+
+/// Like `println!`, for the debugging output of a day, shown with `--verbose`.
+macro_rules! diagnostic {
+    ($($arg:tt)*) => {
+        $crate::solution::emit_diagnostic(
+            $crate::solution::Verbosity::Verbose,
+            format!($($arg)*),
+        )
+    };
+}
+
+/// Like `diagnostic!`, for output on every step of a loop, shown with `--trace`.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::solution::emit_diagnostic(
+            $crate::solution::Verbosity::Trace,
+            format!($($arg)*),
+        )
+    };
+}
+
+pub(crate) use diagnostic;
+pub(crate) use trace;
+
+/// A status line for a long loop, like `Examining rows [=========>          ] 45%`, redrawn on
+/// stderr as the loop goes and cleared when dropped. It's only shown on a terminal, at the
+/// normal verbosity or above, and not when diagnostics are captured.
+pub struct Progress {
+    label: &'static str,
+    total: usize,
+    shown: bool,
+    // In thousandths of the total, so that the line is redrawn at most a thousand times.
+    drawn: Option<usize>,
+}
+
+impl Progress {
+    const WIDTH: usize = 30;
+
+    pub fn new(label: &'static str, total: usize) -> Self {
+        let shown = verbosity() >= Verbosity::Normal
+            && !CAPTURE_DIAGNOSTICS.load(Ordering::Relaxed)
+            && io::stderr().is_terminal();
+
+        Progress {
+            label,
+            total: total.max(1),
+            shown,
+            drawn: None,
+        }
+    }
+
+    // Sets how much of the total is done.
+    pub fn set(&mut self, done: usize) {
+        if !self.shown {
+            return;
+        }
+
+        let thousandths = done.min(self.total) * 1000 / self.total;
+
+        if self.drawn == Some(thousandths) && STATUS_LINE.load(Ordering::Relaxed) {
+            return;
+        }
+
+        self.drawn = Some(thousandths);
+        STATUS_LINE.store(true, Ordering::Relaxed);
+
+        let full = thousandths * Self::WIDTH / 1000;
+        let bar = format!("{}>", "=".repeat(full));
+
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{} [{:<width$}] {}%",
+            self.label,
+            &bar[..bar.len().min(Self::WIDTH)],
+            thousandths / 10,
+            width = Self::WIDTH
+        );
+        let _ = stderr.flush();
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.shown {
+            clear_status_line();
+        }
+    }
+}
+
+fn clear_status_line() {
+    if STATUS_LINE.swap(false, Ordering::Relaxed) {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+pub fn run<S: Solution>(input: &str) -> Result<Report, Error> {
+    DIAGNOSTICS.with(|diagnostics| diagnostics.borrow_mut().clear());
+
+    let start_time = Instant::now();
+    let parsed = S::parse(input)?;
+    let parse_time = start_time.elapsed();
+
+    let start_time = Instant::now();
+    let part1 = S::part1(&parsed);
+    let part1_time = start_time.elapsed();
+
+    let start_time = Instant::now();
+    let part2 = S::part2(&parsed);
+    let part2_time = start_time.elapsed();
+
+    Ok(Report {
+        part1,
+        part2,
+        parse_time,
+        part1_time,
+        part2_time,
+        diagnostics: DIAGNOSTICS.with(|diagnostics| diagnostics.take()),
+    })
+}
+
+// Parses the input and runs the day's reference solver on it, see `Solution::reference`.
+pub fn run_reference<S: Solution>(input: &str) -> Result<Option<(Answer, Answer)>, Error> {
+    Ok(S::reference(&S::parse(input)?))
+}
+
+#[cfg(test)]
+mod diagnostic_tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity() {
+        capture_diagnostics(true);
+        set_verbosity(Verbosity::Verbose);
+
+        DIAGNOSTICS.with(|diagnostics| diagnostics.borrow_mut().clear());
+        diagnostic!("Found {}", 42);
+        trace!("Step {}", 1);
+        assert_eq!(
+            DIAGNOSTICS.with(|diagnostics| diagnostics.take()),
+            ["Found 42"]
+        );
+
+        set_verbosity(Verbosity::Trace);
+        trace!("Step {}", 2);
+        assert_eq!(
+            DIAGNOSTICS.with(|diagnostics| diagnostics.take()),
+            ["Step 2"]
+        );
+
+        set_verbosity(Verbosity::Normal);
+        diagnostic!("Found {}", 42);
+        assert!(DIAGNOSTICS
+            .with(|diagnostics| diagnostics.take())
+            .is_empty());
+    }
+}
+
+#[cfg(test)]
+mod answer_tests {
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Answer::from(42_u64), Answer::Number(42));
+        assert_eq!(Answer::from(-3_isize), Answer::Number(-3));
+        assert_eq!(
+            Answer::from("ZFBFHGUP"),
+            Answer::Text("ZFBFHGUP".to_string())
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::Number(1538773034088).to_string(), "1538773034088");
+        assert_eq!(Answer::Text("2011-=2".to_string()).to_string(), "2011-=2");
+        assert_eq!(Answer::Unsolved.to_string(), "(unsolved)");
+    }
+}
+
+#[cfg(test)]
+mod param_tests {
+    use super::*;
+
+    #[test]
+    fn test_param_value() {
+        const ROUNDS: Param = Param {
+            name: "rounds",
+            default: 10000,
+            min: 1,
+            max: u16::MAX as i64,
+            description: "Rounds",
+        };
+
+        assert_eq!(ROUNDS.value::<u16>(), 10000);
+
+        override_params(vec![("rounds", 20)]);
+        assert_eq!(ROUNDS.value::<u16>(), 20);
+
+        override_params(Vec::new());
+        assert_eq!(ROUNDS.value::<u16>(), 10000);
+    }
+}
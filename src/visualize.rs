@@ -0,0 +1,313 @@
+use std::{cell::RefCell, iter};
+
+use ansi_term::Colour;
+
+use crate::{
+    geometry::Point2,
+    grid::{Grid, Position},
+};
+
+/// A colour of a palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// The colour of each kind of cell of a day's frames, by the character drawn for it. Characters
+/// without a colour keep the default one.
+pub type Palette = &'static [(char, Rgb)];
+
+// The colour of the empty cells of images, and of the cells the palette has no colour for.
+pub const BACKGROUND: Rgb = Rgb(0, 0, 0);
+pub const FOREGROUND: Rgb = Rgb(204, 204, 204);
+
+/// A picture of a simulation at one of its steps, as the character of each cell (anywhere, like in
+/// a sparse grid, with y growing downwards), the cell where the action is, and a caption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    // Unset cells are empty.
+    pub cells: Grid<char>,
+    pub focus: Option<Position>,
+    pub caption: String,
+    pub palette: Palette,
+}
+
+impl Frame {
+    pub fn new(caption: impl Into<String>, palette: Palette) -> Self {
+        Frame {
+            cells: Grid::sparse(),
+            focus: None,
+            caption: caption.into(),
+            palette,
+        }
+    }
+
+    pub fn with_focus(self, focus: Position) -> Self {
+        Frame {
+            focus: Some(focus),
+            ..self
+        }
+    }
+
+    pub fn set(&mut self, position: Position, cell: char) {
+        self.cells.set(position, cell);
+    }
+
+    // Copies the cells of a grid, drawn by `cell` (None leaves the cell empty).
+    pub fn draw<T>(&mut self, grid: &Grid<T>, cell: impl Fn(Position, &T) -> Option<char>) {
+        for (position, value) in grid.iter() {
+            if let Some(cell) = cell(position, value) {
+                self.cells.set(position, cell);
+            }
+        }
+    }
+
+    pub fn color(&self, cell: char) -> Option<Rgb> {
+        self.palette
+            .iter()
+            .find(|(character, _)| *character == cell)
+            .map(|(_, color)| *color)
+    }
+
+    // Draws the cells between the top left and bottom right positions (inclusive) with ANSI
+    // colours, with a line for each row.
+    pub fn render(&self, min: Position, max: Position) -> String {
+        self.cells.render_area(min, max, |_, cell| match cell {
+            Some(&cell) => match self.color(cell) {
+                Some(Rgb(r, g, b)) => Colour::RGB(r, g, b).paint(cell.to_string()).to_string(),
+                None => cell.to_string(),
+            },
+            None => " ".to_string(),
+        })
+    }
+
+    // Draws the cells between the top left and bottom right positions (inclusive) as an image,
+    // with a square of `scale` pixels for each cell. The pixels are listed row by row.
+    pub fn pixels(&self, min: Position, max: Position, scale: usize) -> Vec<Rgb> {
+        let scale = scale.max(1);
+        let mut pixels = Vec::new();
+
+        for y in min.y..=max.y {
+            let row = (min.x..=max.x)
+                .map(|x| match self.cells.get(Point2::new(x, y)) {
+                    Some(&cell) => self.color(cell).unwrap_or(FOREGROUND),
+                    None => BACKGROUND,
+                })
+                .flat_map(|color| iter::repeat_n(color, scale))
+                .collect::<Vec<Rgb>>();
+
+            for _ in 0..scale {
+                pixels.extend_from_slice(&row);
+            }
+        }
+
+        pixels
+    }
+}
+
+/// The part of the frames shown on a screen of a fixed size. Frames that fit are shown whole, and
+/// the others scroll to keep their focus away from the edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub width: i64,
+    pub height: i64,
+    // The top left position shown, once there was a frame to show.
+    origin: Option<Position>,
+}
+
+impl Viewport {
+    pub fn new(width: i64, height: i64) -> Self {
+        Viewport {
+            width: width.max(1),
+            height: height.max(1),
+            origin: None,
+        }
+    }
+
+    pub fn follow(&mut self, frame: &Frame) {
+        let Some((min, max)) = frame.cells.bounds() else {
+            return;
+        };
+
+        let focus = frame.focus.unwrap_or(min);
+        let origin = self.origin;
+
+        let x = follow_axis(
+            (min.x, max.x),
+            focus.x,
+            origin.map(|origin| origin.x),
+            self.width,
+        );
+        let y = follow_axis(
+            (min.y, max.y),
+            focus.y,
+            origin.map(|origin| origin.y),
+            self.height,
+        );
+
+        self.origin = Some(Point2::new(x, y));
+    }
+
+    // The top left and bottom right positions shown (inclusive).
+    pub fn area(&self) -> (Position, Position) {
+        let origin = self.origin.unwrap_or(Point2::ORIGIN);
+        (
+            origin,
+            origin + Point2::new(self.width - 1, self.height - 1),
+        )
+    }
+}
+
+// The first position shown on one axis, for a frame between `min` and `max` on it.
+fn follow_axis((min, max): (i64, i64), focus: i64, origin: Option<i64>, size: i64) -> i64 {
+    if max - min < size {
+        return min;
+    }
+
+    let margin = size / 4;
+
+    match origin {
+        // The focus is centred on the first frame.
+        None => focus - size / 2,
+        Some(origin) if focus < origin + margin => focus - margin,
+        Some(origin) if focus > origin + size - 1 - margin => focus - (size - 1 - margin),
+        Some(origin) => origin,
+    }
+}
+
+type Player = Box<dyn FnMut(Frame)>;
+
+thread_local! {
+    static PLAYER: RefCell<Option<Player>> = const { RefCell::new(None) };
+}
+
+// Sends the frames emitted on this thread from now on to `player`, until `stop_playing`.
+pub fn play(player: impl FnMut(Frame) + 'static) {
+    PLAYER.with(|current| *current.borrow_mut() = Some(Box::new(player)));
+}
+
+pub fn stop_playing() {
+    // Dropped outside of the borrow, in case dropping the player emits anything.
+    let player = PLAYER.with(|current| current.borrow_mut().take());
+    drop(player);
+}
+
+pub fn is_playing() -> bool {
+    PLAYER.with(|current| current.borrow().is_some())
+}
+
+// Builds a frame of a simulation and shows it, when frames are played. Frames are only built then,
+// so simulations can emit them at every step.
+pub fn emit(frame: impl FnOnce() -> Frame) {
+    let Some(mut player) = PLAYER.with(|current| current.borrow_mut().take()) else {
+        return;
+    };
+
+    player(frame());
+
+    PLAYER.with(|current| {
+        current.borrow_mut().get_or_insert(player);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    const PALETTE: Palette = &[('#', Rgb(255, 0, 0))];
+
+    fn frame(cells: &[(i64, i64)], focus: (i64, i64)) -> Frame {
+        let mut frame = Frame::new("test", PALETTE).with_focus(focus.into());
+
+        for &cell in cells {
+            frame.set(cell.into(), '#');
+        }
+
+        frame
+    }
+
+    #[test]
+    fn test_render() {
+        let mut frame = frame(&[(0, 0), (2, 1)], (0, 0));
+        frame.set(Point2::new(1, 0), 'o');
+
+        assert_eq!(frame.color('#'), Some(Rgb(255, 0, 0)));
+        assert_eq!(frame.color('o'), None);
+
+        let red = Colour::RGB(255, 0, 0).paint("#").to_string();
+        assert_eq!(
+            frame.render(Point2::new(0, 0), Point2::new(2, 1)),
+            format!("{red}o \n  {red}")
+        );
+    }
+
+    #[test]
+    fn test_pixels() {
+        let mut frame = frame(&[(0, 0)], (0, 0));
+        frame.set(Point2::new(1, 1), 'o');
+
+        let (red, grey, black) = (Rgb(255, 0, 0), FOREGROUND, BACKGROUND);
+
+        assert_eq!(
+            frame.pixels(Point2::new(0, 0), Point2::new(1, 1), 1),
+            vec![red, black, black, grey]
+        );
+        assert_eq!(
+            frame.pixels(Point2::new(0, 0), Point2::new(1, 0), 2),
+            vec![red, red, black, black, red, red, black, black]
+        );
+    }
+
+    #[test]
+    fn test_viewport() {
+        let mut viewport = Viewport::new(8, 4);
+
+        // Frames that fit are shown whole.
+        viewport.follow(&frame(&[(-2, 3), (3, 5)], (0, 4)));
+        assert_eq!(viewport.area(), (Point2::new(-2, 3), Point2::new(5, 6)));
+
+        // Otherwise the first frame is centred on its focus, and the next ones scroll when their
+        // focus gets within a quarter of the size of the edges.
+        let mut viewport = Viewport::new(8, 4);
+        viewport.follow(&frame(&[(0, 0), (100, 0)], (50, 0)));
+        assert_eq!(viewport.area(), (Point2::new(46, 0), Point2::new(53, 3)));
+
+        viewport.follow(&frame(&[(0, 0), (100, 0)], (51, 0)));
+        assert_eq!(viewport.area().0, Point2::new(46, 0));
+
+        viewport.follow(&frame(&[(0, 0), (100, 0)], (60, 0)));
+        assert_eq!(viewport.area().0, Point2::new(55, 0));
+
+        viewport.follow(&frame(&[(0, 0), (100, 0)], (40, 0)));
+        assert_eq!(viewport.area().0, Point2::new(38, 0));
+    }
+
+    #[test]
+    fn test_emit() {
+        let frames = Rc::new(Cell::new(0));
+        let built = Cell::new(0);
+
+        emit(|| {
+            built.set(built.get() + 1);
+            frame(&[], (0, 0))
+        });
+        assert_eq!(built.get(), 0);
+
+        let played = frames.clone();
+        play(move |_| played.set(played.get() + 1));
+        assert!(is_playing());
+
+        for _ in 0..3 {
+            emit(|| {
+                built.set(built.get() + 1);
+                frame(&[], (0, 0))
+            });
+        }
+
+        stop_playing();
+        emit(|| frame(&[], (0, 0)));
+
+        assert!(!is_playing());
+        assert_eq!((built.get(), frames.get()), (3, 3));
+    }
+}
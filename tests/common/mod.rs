@@ -0,0 +1,26 @@
+// What the integration tests share: a test per day, and the day to test with its params.
+
+use aoc22::solution::{capture_diagnostics, override_params, Day};
+
+// A test for each day, which calls `check(<day>, <size>, <overrides>)` with the size of the inputs
+// to generate and the params to override, like `day11: 6, "rounds" = 1000;`.
+#[allow(unused_macros)]
+macro_rules! day_tests {
+    ($($day:ident: $size:expr $(, $name:literal = $value:expr)*;)*) => {
+        $(
+            #[test]
+            fn $day() {
+                check(stringify!($day), $size, vec![$(($name, $value)),*]);
+            }
+        )*
+    };
+}
+
+// The day with the given name, set up to run with the overridden params and without printing
+// anything into the test output.
+pub fn day(name: &str, overrides: Vec<(&'static str, i64)>) -> &'static Day {
+    capture_diagnostics(true);
+    override_params(overrides);
+
+    aoc22::find_day(name).expect("no such day")
+}
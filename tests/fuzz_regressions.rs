@@ -0,0 +1,69 @@
+// Runs each day's parser on the inputs that made it panic while fuzzing (the files in
+// fuzz/regressions/<day>), which it should now either parse or reject with an error.
+
+use std::{fs, path::Path};
+
+use aoc22::solution::Solution;
+
+macro_rules! regression_tests {
+    ($($module:ident::$solution:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $module() {
+                for input in regressions(stringify!($module)) {
+                    let _ = aoc22::$module::$solution::parse(&input);
+                }
+            }
+        )*
+    };
+}
+
+regression_tests![
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::Day7,
+    day8::Day8,
+    day9::Day9,
+    day10::Day10,
+    day11::Day11,
+    day12::Day12,
+    day13::Day13,
+    day14::Day14,
+    day15::Day15,
+    day16::Day16,
+    day17::Day17,
+    day18::Day18,
+    day19::Day19,
+    day20::Day20,
+    day21::Day21,
+    day22::Day22,
+    day23::Day23,
+    day24::Day24,
+    day25::Day25,
+];
+
+// The inputs saved for a fuzz target, as the target sees them: up to the first invalid UTF-8.
+fn regressions(target: &str) -> Vec<String> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/regressions")
+        .join(target);
+
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    entries
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .map(|bytes| match String::from_utf8(bytes) {
+            Ok(input) => input,
+            Err(error) => {
+                let valid = error.utf8_error().valid_up_to();
+                String::from_utf8(error.into_bytes()[..valid].to_vec()).unwrap()
+            }
+        })
+        .collect()
+}
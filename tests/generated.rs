@@ -0,0 +1,101 @@
+// Runs each day against inputs from its generator, for a few seeds, and checks that both parts
+// solve them, with the same answers as the reference solver for the days that have one. The sizes
+// (and params, for the slowest days) are kept small so that the tests stay fast in debug builds.
+
+use aoc22::{
+    differential,
+    random::Rng,
+    solution::{Answer, Report},
+};
+
+#[macro_use]
+mod common;
+
+const SEEDS: [u64; 5] = [1, 2, 3, 42, 2022];
+
+// The parts that aren't solved (yet), which answer `Answer::Unsolved` to any input.
+const UNSOLVED: [(&str, usize); 4] = [("day22", 2), ("day24", 1), ("day24", 2), ("day25", 2)];
+
+day_tests! {
+    day1: 10;
+    day2: 10;
+    day3: 10;
+    day4: 10;
+    day5: 10;
+    day6: 10;
+    day7: 10;
+    day8: 10;
+    day9: 10;
+    day10: 10;
+    day11: 6, "rounds" = 1000;
+    day12: 10;
+    day13: 10;
+    day14: 10;
+    day15: 10, "search_area" = 20, "row" = 10;
+    day16: 8, "minutes" = 16, "minutes_alone" = 12;
+    day17: 1;
+    day18: 6;
+    day19: 2, "minutes" = 12, "total_minutes" = 14, "blueprints" = 2;
+    day20: 10, "decryption_key" = 811589153;
+    day21: 10;
+    day22: 3;
+    day23: 6;
+    day24: 3;
+    day25: 10;
+}
+
+fn check(day_name: &str, size: usize, overrides: Vec<(&'static str, i64)>) {
+    let day = common::day(day_name, overrides);
+
+    for seed in SEEDS {
+        let input = (day.generate)(&mut Rng::new(seed), size)
+            .unwrap_or_else(|| panic!("{} has no input generator", day_name));
+
+        assert_eq!(
+            (day.generate)(&mut Rng::new(seed), size),
+            Some(input.clone()),
+            "{} generated different inputs for seed {}",
+            day_name,
+            seed
+        );
+
+        let report = (day.run)(&input)
+            .unwrap_or_else(|error| panic!("seed {}: {}\n{}", seed, error.in_day(day_name), input));
+
+        for part in unsolved_parts(&report) {
+            assert!(
+                UNSOLVED.contains(&(day_name, part)),
+                "seed {}: {} part {} is unsolved\n{}",
+                seed,
+                day_name,
+                part,
+                input
+            );
+        }
+
+        let mismatches = differential::compare(day, &input)
+            .unwrap_or_else(|error| panic!("seed {}: {}\n{}", seed, error.in_day(day_name), input))
+            .unwrap_or_default();
+
+        assert!(
+            mismatches.is_empty(),
+            "seed {}: {}: {}\n{}",
+            seed,
+            day_name,
+            mismatches
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join("; "),
+            input
+        );
+    }
+}
+
+fn unsolved_parts(report: &Report) -> Vec<usize> {
+    [(1, &report.part1), (2, &report.part2)]
+        .into_iter()
+        .filter(|(_, answer)| **answer == Answer::Unsolved)
+        .map(|(part, _)| part)
+        .collect()
+}
@@ -0,0 +1,30 @@
+// Runs each day against its test inputs and checks the answers in answers.txt. The tests are
+// generated by build.rs, one for each inputs/*_test.txt file.
+
+use aoc22::{answers, input};
+
+mod common;
+
+include!(concat!(env!("OUT_DIR"), "/input_tests.rs"));
+
+// Unused when there are no test inputs (see build.rs).
+#[allow(dead_code)]
+fn check(input_name: &str) {
+    let expected = answers::load()
+        .unwrap()
+        .into_iter()
+        .find(|expected| expected.input == input_name)
+        .unwrap_or_else(|| panic!("{} has no expected answers in answers.txt", input_name));
+
+    let name = input::split_name(input_name).0;
+    let overrides = expected
+        .overrides(aoc22::find_day(name).expect("no such day"))
+        .unwrap_or_else(|error| panic!("{}", error.in_day(input_name)));
+    let day = common::day(name, overrides);
+    let input = input::Source::Named(input_name.to_string()).read().unwrap();
+
+    let report = (day.run)(&input).unwrap_or_else(|error| panic!("{}", error.in_day(input_name)));
+
+    let mismatches = expected.mismatches(&report);
+    assert!(mismatches.is_empty(), "{}", mismatches.join("; "));
+}
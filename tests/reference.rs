@@ -0,0 +1,77 @@
+// Runs the days that have a reference solver against it, on their test inputs and on generated
+// inputs for a few seeds, and fails with the smallest disagreeing input that can be found. The
+// params keep the slow reference solvers fast enough for debug builds.
+
+use std::panic;
+
+use aoc22::{
+    differential::{self, Mismatch},
+    input::{self, Source},
+    random::Rng,
+    solution::Day,
+};
+
+#[macro_use]
+mod common;
+
+const SEEDS: u64 = 10;
+
+day_tests! {
+    day4: 10;
+    day12: 6;
+    day13: 10;
+    day15: 10, "search_area" = 20, "row" = 10;
+    day16: 6, "minutes" = 12, "minutes_alone" = 14;
+    day17: 1, "rocks" = 500, "total_rocks" = 5000;
+    day18: 6;
+    day19: 1, "minutes" = 14, "total_minutes" = 16, "blueprints" = 1;
+    day20: 20;
+    day21: 8;
+}
+
+fn check(day_name: &str, size: usize, overrides: Vec<(&'static str, i64)>) {
+    let day = common::day(day_name, overrides);
+
+    let test_inputs = input::variants(day_name)
+        .into_iter()
+        .filter(|variant| variant.ends_with("test"))
+        .map(|variant| {
+            let input_name = format!("{}_{}", day_name, variant);
+            Source::Named(input_name).read().unwrap()
+        });
+
+    let generated = (1..=SEEDS).map(|seed| {
+        (day.generate)(&mut Rng::new(seed), size)
+            .unwrap_or_else(|| panic!("{} has no input generator", day_name))
+    });
+
+    for input in test_inputs.chain(generated) {
+        let mismatches =
+            compare(day, &input).unwrap_or_else(|| panic!("{} has no reference solver", day_name));
+
+        if !mismatches.is_empty() {
+            let disagrees = |input: &str| {
+                panic::catch_unwind(|| compare(day, input))
+                    .is_ok_and(|mismatches| mismatches.is_some_and(|m| !m.is_empty()))
+            };
+
+            let smallest = differential::smallest_generated(day, size, SEEDS, disagrees)
+                .map_or(input.clone(), |(_, _, smallest)| smallest);
+
+            panic!(
+                "{}: {}, on this input:\n{}",
+                day_name,
+                mismatches
+                    .iter()
+                    .map(Mismatch::to_string)
+                    .collect::<Vec<String>>()
+                    .join("; "),
+                differential::minimize(&smallest, disagrees)
+            );
+        }
+    }
+}
+
+fn compare(day: &Day, input: &str) -> Option<Vec<Mismatch>> {
+    differential::compare(day, input).unwrap_or_else(|error| panic!("{}", error.in_day(day.name)))
+}